@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::expr::SCENE;
+use crate::node::{FilterableStream, NodeType, OutputStream};
+use crate::time::{Duration, Timestamp};
+
+/*
+Analysis filters with parsed results.
+
+    Every analysis builds a graph ending in ``-f null -`` and knows how to parse
+    what its filter logs to stderr:
+
+        let analysis = silencedetect(&input, -50.0, Duration::from_millis(500));
+        let silences: Vec<Interval> = analysis.run("ffmpeg")?;
+
+    Runners select the audio or video of a whole input and take any other stream,
+    such as a filter output, as it is.
+
+    The parsers are public so output captured elsewhere (e.g. from a job runner)
+    can be parsed too.  They look for the filter's own log lines and ignore
+    everything else ffmpeg prints, including progress lines.
+*/
+
+// A graph to run and the parser for its stderr.
+pub struct Analysis<T> {
+    output: OutputStream,
+    parse: fn(&str) -> Result<T, String>,
+}
+
+impl<T> Analysis<T> {
+    fn new(stream: &FilterableStream, parse: fn(&str) -> Result<T, String>) -> Analysis<T> {
+        Analysis {
+            output: stream.output("-", crate::kwargs! {"f" => "null"}),
+            parse,
+        }
+    }
+
+    pub fn output(&self) -> &OutputStream {
+        &self.output
+    }
+
+    pub fn parse(&self, stderr: &str) -> Result<T, String> {
+        (self.parse)(stderr)
+    }
+
+    // Runs the graph with `cmd` and parses its stderr.
+    pub fn run(&self, cmd: &str) -> Result<T, Box<dyn Error>> {
+        let compiled = self.output.compile_command(cmd, false)?;
+        let output = compiled.command().output()?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() {
+            let last_line = stderr.lines().last().unwrap_or_default();
+            return Err(format!("{} failed: {}: {}", cmd, output.status, last_line).into());
+        }
+        Ok(self.parse(&stderr)?)
+    }
+}
+
+// A stretch of the input; `end` is `None` when the input ended inside it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Interval {
+    pub start: Timestamp,
+    pub end: Option<Timestamp>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ebur128Summary {
+    // LUFS.
+    pub integrated: f64,
+    pub integrated_threshold: f64,
+    // LU.
+    pub loudness_range: f64,
+    // LUFS.
+    pub lra_threshold: f64,
+    pub lra_low: f64,
+    pub lra_high: f64,
+    // dBFS.
+    pub true_peak: Option<f64>,
+}
+
+// The targets `loudnorm` normalizes to; the defaults are ffmpeg's.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LoudnormTarget {
+    // LUFS.
+    pub integrated: f64,
+    // dBTP.
+    pub true_peak: f64,
+    // LU.
+    pub loudness_range: f64,
+}
+
+impl Default for LoudnormTarget {
+    fn default() -> Self {
+        LoudnormTarget {
+            integrated: -24.0,
+            true_peak: -2.0,
+            loudness_range: 7.0,
+        }
+    }
+}
+
+// What `loudnorm` prints with `print_format=json`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LoudnormMeasurement {
+    pub input_i: f64,
+    pub input_tp: f64,
+    pub input_lra: f64,
+    pub input_thresh: f64,
+    pub output_i: f64,
+    pub output_tp: f64,
+    pub output_lra: f64,
+    pub output_thresh: f64,
+    pub normalization_type: String,
+    pub target_offset: f64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Crop {
+    pub width: u32,
+    pub height: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Crop {
+    pub fn apply(&self, stream: &FilterableStream) -> FilterableStream {
+        stream.filter(
+            "crop",
+            crate::kwargs! {"w" => self.width, "h" => self.height, "x" => self.x, "y" => self.y},
+        )
+    }
+}
+
+// `w:h:x:y`, as `cropdetect` prints it.
+impl fmt::Display for Crop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}:{}", self.width, self.height, self.x, self.y)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Volume {
+    pub n_samples: u64,
+    // dB.
+    pub mean_volume: f64,
+    pub max_volume: f64,
+}
+
+// Integrated loudness, loudness range and true peak per EBU R 128.
+pub fn ebur128(input: &FilterableStream) -> Analysis<Ebur128Summary> {
+    let stream = audio_of(input).filter("ebur128", crate::kwargs! {"peak" => "true"});
+    Analysis::new(&stream, parse_ebur128)
+}
+
+// The measurements of the first pass of two-pass `loudnorm`.
+pub fn loudnorm(input: &FilterableStream, target: LoudnormTarget) -> Analysis<LoudnormMeasurement> {
+    let stream = audio_of(input).filter(
+        "loudnorm",
+        crate::kwargs! {
+            "I" => target.integrated,
+            "TP" => target.true_peak,
+            "LRA" => target.loudness_range,
+            "print_format" => "json",
+        },
+    );
+    Analysis::new(&stream, parse_loudnorm)
+}
+
+// Stretches quieter than `noise` dB for at least `duration`.
+pub fn silencedetect(
+    input: &FilterableStream,
+    noise: f64,
+    duration: Duration,
+) -> Analysis<Vec<Interval>> {
+    let stream = audio_of(input).filter(
+        "silencedetect",
+        crate::kwargs! {"noise" => format!("{}dB", noise), "duration" => duration.format_seconds()},
+    );
+    Analysis::new(&stream, parse_silencedetect)
+}
+
+// Black stretches of at least `duration`; `pixel_threshold` (0 to 1) is how
+// dark a pixel must be to count as black.
+pub fn blackdetect(
+    input: &FilterableStream,
+    duration: Duration,
+    pixel_threshold: f64,
+) -> Analysis<Vec<Interval>> {
+    let stream = video_of(input).filter(
+        "blackdetect",
+        crate::kwargs! {"d" => duration.format_seconds(), "pix_th" => pixel_threshold},
+    );
+    Analysis::new(&stream, parse_blackdetect)
+}
+
+// Timestamps of frames whose scene change score exceeds `threshold` (0 to 1;
+// 0.3 to 0.4 finds most cuts).
+pub fn scene_changes(input: &FilterableStream, threshold: f64) -> Analysis<Vec<Timestamp>> {
+    let stream = video_of(input)
+        .filter("select", crate::kwargs! {"expr" => SCENE.gt(threshold)})
+        .filter("showinfo", crate::kwargs! {});
+    Analysis::new(&stream, parse_scene_changes)
+}
+
+// The crop most frames agree on.
+pub fn cropdetect(input: &FilterableStream) -> Analysis<Crop> {
+    let stream = video_of(input).filter("cropdetect", crate::kwargs! {});
+    Analysis::new(&stream, parse_cropdetect)
+}
+
+pub fn volumedetect(input: &FilterableStream) -> Analysis<Volume> {
+    let stream = audio_of(input).filter("volumedetect", crate::kwargs! {});
+    Analysis::new(&stream, parse_volumedetect)
+}
+
+// The audio of an input, or `stream` itself when it already is one stream, such
+// as a filter output or an input's selected streams.
+pub(crate) fn audio_of(stream: &FilterableStream) -> FilterableStream {
+    select(stream, 'a')
+}
+
+// Like `audio_of`, for video.
+pub(crate) fn video_of(stream: &FilterableStream) -> FilterableStream {
+    select(stream, 'v')
+}
+
+fn select(stream: &FilterableStream, selector: char) -> FilterableStream {
+    if stream.node.node_type() == NodeType::Input && stream.selector.is_none() {
+        stream.get(selector).unwrap_or_else(|_| stream.clone())
+    } else {
+        stream.clone()
+    }
+}
+
+pub fn parse_ebur128(stderr: &str) -> Result<Ebur128Summary, String> {
+    // Every frame is logged with a running `I:` too; only the summary is final.
+    let (_, summary) = stderr
+        .rsplit_once("Summary:")
+        .ok_or("No ebur128 summary in ffmpeg output")?;
+    let mut section = "";
+    let mut values: HashMap<String, f64> = HashMap::new();
+    for line in summary.lines().map(str::trim) {
+        if let Some(name) = line.strip_suffix(':') {
+            section = name;
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.split_whitespace().next().unwrap_or_default();
+        let value: f64 = value
+            .parse()
+            .map_err(|_| format!("Invalid ebur128 value: {}", line))?;
+        values.insert(format!("{}/{}", section, key), value);
+    }
+    let get = |key: &str| {
+        values
+            .get(key)
+            .copied()
+            .ok_or_else(|| format!("No `{}` in ebur128 summary", key))
+    };
+    Ok(Ebur128Summary {
+        integrated: get("Integrated loudness/I")?,
+        integrated_threshold: get("Integrated loudness/Threshold")?,
+        loudness_range: get("Loudness range/LRA")?,
+        lra_threshold: get("Loudness range/Threshold")?,
+        lra_low: get("Loudness range/LRA low")?,
+        lra_high: get("Loudness range/LRA high")?,
+        true_peak: get("True peak/Peak").ok(),
+    })
+}
+
+pub fn parse_loudnorm(stderr: &str) -> Result<LoudnormMeasurement, String> {
+    let (_, rest) = stderr
+        .rsplit_once("[Parsed_loudnorm_")
+        .ok_or("No loudnorm measurement in ffmpeg output")?;
+    let start = rest.find('{').ok_or("No loudnorm JSON in ffmpeg output")?;
+    let end = rest[start..]
+        .find('}')
+        .ok_or("Unterminated loudnorm JSON in ffmpeg output")?;
+    // A flat object of strings: `"key" : "value",` per line.
+    let mut values: HashMap<&str, &str> = HashMap::new();
+    for line in rest[start + 1..start + end].lines() {
+        let line = line.trim().trim_end_matches(',');
+        if let Some((key, value)) = line.split_once(':') {
+            values.insert(key.trim().trim_matches('"'), value.trim().trim_matches('"'));
+        }
+    }
+    let get = |key: &str| {
+        values
+            .get(key)
+            .copied()
+            .ok_or_else(|| format!("No `{}` in loudnorm JSON", key))
+    };
+    let number = |key: &str| {
+        let value = get(key)?;
+        value
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid loudnorm value for `{}`: {}", key, value))
+    };
+    Ok(LoudnormMeasurement {
+        input_i: number("input_i")?,
+        input_tp: number("input_tp")?,
+        input_lra: number("input_lra")?,
+        input_thresh: number("input_thresh")?,
+        output_i: number("output_i")?,
+        output_tp: number("output_tp")?,
+        output_lra: number("output_lra")?,
+        output_thresh: number("output_thresh")?,
+        normalization_type: get("normalization_type")?.to_owned(),
+        target_offset: number("target_offset")?,
+    })
+}
+
+pub fn parse_silencedetect(stderr: &str) -> Result<Vec<Interval>, String> {
+    let mut intervals: Vec<Interval> = Vec::new();
+    for line in filter_lines(stderr, "silencedetect") {
+        if let Some(start) = field(line, "silence_start:") {
+            intervals.push(Interval {
+                start: parse_time(start)?,
+                end: None,
+            });
+        } else if let Some(end) = field(line, "silence_end:") {
+            match intervals.last_mut() {
+                Some(interval) if interval.end.is_none() => interval.end = Some(parse_time(end)?),
+                _ => return Err(format!("silence_end without silence_start: {}", line)),
+            }
+        }
+    }
+    Ok(intervals)
+}
+
+pub fn parse_blackdetect(stderr: &str) -> Result<Vec<Interval>, String> {
+    filter_lines(stderr, "blackdetect")
+        .filter_map(|line| Some((field(line, "black_start:")?, field(line, "black_end:"))))
+        .map(|(start, end)| {
+            Ok(Interval {
+                start: parse_time(start)?,
+                end: end.map(parse_time).transpose()?,
+            })
+        })
+        .collect()
+}
+
+pub fn parse_scene_changes(stderr: &str) -> Result<Vec<Timestamp>, String> {
+    filter_lines(stderr, "showinfo")
+        .filter_map(|line| field(line, "pts_time:"))
+        .map(parse_time)
+        .collect()
+}
+
+pub fn parse_cropdetect(stderr: &str) -> Result<Crop, String> {
+    // Counts of every suggested crop, and the order they were first seen in.
+    let mut counts: Vec<(Crop, usize)> = Vec::new();
+    for line in filter_lines(stderr, "cropdetect") {
+        let Some(value) = field(line, "crop=") else {
+            continue;
+        };
+        let parts: Vec<u32> = value
+            .split(':')
+            .map(|part| part.parse::<u32>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("Invalid crop: {}", value))?;
+        let [width, height, x, y] = parts[..] else {
+            return Err(format!("Invalid crop: {}", value));
+        };
+        let crop = Crop {
+            width,
+            height,
+            x,
+            y,
+        };
+        match counts.iter_mut().find(|(seen, _)| *seen == crop) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((crop, 1)),
+        }
+    }
+    // Ties go to the crop seen first.
+    counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(crop, _)| *crop)
+        .ok_or_else(|| "No cropdetect results in ffmpeg output".to_owned())
+}
+
+pub fn parse_volumedetect(stderr: &str) -> Result<Volume, String> {
+    let mut n_samples = None;
+    let mut mean_volume = None;
+    let mut max_volume = None;
+    for line in filter_lines(stderr, "volumedetect") {
+        if let Some(value) = field(line, "n_samples:") {
+            n_samples = value.parse().ok();
+        } else if let Some(value) = field(line, "mean_volume:") {
+            mean_volume = value.parse().ok();
+        } else if let Some(value) = field(line, "max_volume:") {
+            max_volume = value.parse().ok();
+        }
+    }
+    match (n_samples, mean_volume, max_volume) {
+        (Some(n_samples), Some(mean_volume), Some(max_volume)) => Ok(Volume {
+            n_samples,
+            mean_volume,
+            max_volume,
+        }),
+        _ => Err("No volumedetect results in ffmpeg output".to_owned()),
+    }
+}
+
+// Lines logged by the filter `name`: `[name @ 0x...]` or `[Parsed_name_N @ 0x...]`.
+fn filter_lines<'a>(stderr: &'a str, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    stderr.lines().filter(move |line| {
+        let Some(context) = line
+            .strip_prefix('[')
+            .and_then(|line| line.split(" @ ").next())
+        else {
+            return false;
+        };
+        let context = context
+            .strip_prefix("Parsed_")
+            .and_then(|parsed| parsed.rsplit_once('_'))
+            .map_or(context, |(name, _)| name);
+        context == name
+    })
+}
+
+// The whitespace delimited value after `key`, e.g. `12.5` for `pts_time:`
+// in `pts_time:12.5 duration:...`; ffmpeg pads some values with spaces.
+fn field<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let (_, rest) = line.split_once(key)?;
+    rest.split_whitespace().next()
+}
+
+fn parse_time(seconds: &str) -> Result<Timestamp, String> {
+    seconds.parse()
+}
@@ -0,0 +1,292 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::catalog::{parse_filter_list, FilterCatalog};
+use crate::daq::DagNode;
+use crate::node::{get_stream_spec_node, Node, NodeType, StreamSpec};
+
+/*
+Capabilities of an installed ffmpeg binary.
+
+    ``Capabilities::detect`` runs the binary with ``-version``, ``-encoders``,
+    ``-decoders``, ``-muxers``, ``-demuxers``, ``-filters``, ``-pix_fmts`` and
+    ``-protocols`` and parses each listing.  Results are cached per binary path and
+    modification time, so upgrading ffmpeg in place is picked up.
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CodecType {
+    Video,
+    Audio,
+    Subtitle,
+    Data,
+    Attachment,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Codec {
+    pub name: String,
+    pub codec_type: CodecType,
+    pub experimental: bool,
+    pub description: String,
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Protocols {
+    pub input: BTreeSet<String>,
+    pub output: BTreeSet<String>,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct Capabilities {
+    pub version: String,
+    pub configuration: Vec<String>,
+    pub encoders: BTreeMap<String, Codec>,
+    pub decoders: BTreeMap<String, Codec>,
+    pub muxers: BTreeSet<String>,
+    pub demuxers: BTreeSet<String>,
+    pub filters: FilterCatalog,
+    pub pix_fmts: BTreeSet<String>,
+    pub protocols: Protocols,
+}
+
+type CacheKey = (PathBuf, Option<SystemTime>);
+
+fn cache() -> &'static Mutex<HashMap<CacheKey, Arc<Capabilities>>> {
+    static CACHE: OnceLock<Mutex<HashMap<CacheKey, Arc<Capabilities>>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+impl Capabilities {
+    pub fn detect(ffmpeg_path: impl AsRef<Path>) -> Result<Arc<Capabilities>, Box<dyn Error>> {
+        let path = resolve_binary(ffmpeg_path.as_ref());
+        let mtime = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        let key = (path.clone(), mtime);
+
+        if let Some(capabilities) = cache().lock().unwrap().get(&key) {
+            return Ok(capabilities.clone());
+        }
+
+        let capabilities = Arc::new(Self::from_runner(|flag| {
+            let output = Command::new(&path).arg("-hide_banner").arg(flag).output()?;
+            if !output.status.success() {
+                return Err(
+                    format!("{} {} failed: {}", path.display(), flag, output.status).into(),
+                );
+            }
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        })?);
+        cache().lock().unwrap().insert(key, capabilities.clone());
+        Ok(capabilities)
+    }
+
+    // Builds capabilities from the output of `run(flag)` for every listing flag.
+    pub fn from_runner(
+        mut run: impl FnMut(&str) -> Result<String, Box<dyn Error>>,
+    ) -> Result<Capabilities, Box<dyn Error>> {
+        let (version, configuration) = parse_version(&run("-version")?)?;
+        let mut filters = FilterCatalog::new();
+        for filter in parse_filter_list(&run("-filters")?)? {
+            filters.insert(filter);
+        }
+        Ok(Capabilities {
+            version,
+            configuration,
+            encoders: parse_codecs(&run("-encoders")?)?,
+            decoders: parse_codecs(&run("-decoders")?)?,
+            muxers: parse_formats(&run("-muxers")?, 'E'),
+            demuxers: parse_formats(&run("-demuxers")?, 'D'),
+            filters,
+            pix_fmts: parse_pix_fmts(&run("-pix_fmts")?),
+            protocols: parse_protocols(&run("-protocols")?),
+        })
+    }
+
+    pub fn has_encoder(&self, name: &str) -> bool {
+        self.encoders.contains_key(name)
+    }
+
+    pub fn has_decoder(&self, name: &str) -> bool {
+        self.decoders.contains_key(name)
+    }
+
+    pub fn has_muxer(&self, name: &str) -> bool {
+        self.muxers.contains(name)
+    }
+
+    pub fn has_demuxer(&self, name: &str) -> bool {
+        self.demuxers.contains(name)
+    }
+
+    pub fn has_filter(&self, name: &str) -> bool {
+        self.filters.contains(name)
+    }
+
+    pub fn has_pix_fmt(&self, name: &str) -> bool {
+        self.pix_fmts.contains(name)
+    }
+
+    pub fn has_input_protocol(&self, name: &str) -> bool {
+        self.protocols.input.contains(name)
+    }
+
+    pub fn has_output_protocol(&self, name: &str) -> bool {
+        self.protocols.output.contains(name)
+    }
+
+    pub fn has_configuration(&self, flag: &str) -> bool {
+        self.configuration
+            .iter()
+            .any(|configured| configured == flag)
+    }
+
+    // Picks the first available encoder, e.g. `["libx264", "h264_vaapi", "mpeg4"]`.
+    pub fn first_encoder<'a>(&self, candidates: &[&'a str]) -> Option<&'a str> {
+        candidates
+            .iter()
+            .copied()
+            .find(|name| self.has_encoder(name))
+    }
+
+    // Names of the filters used by the graph that this binary was built without.
+    pub fn missing_filters(&self, stream_spec: impl Into<StreamSpec>) -> Vec<String> {
+        let mut missing: Vec<String> = Vec::new();
+        let mut visited = BTreeSet::new();
+        let mut pending: Vec<Node> = get_stream_spec_node(stream_spec.into());
+        while let Some(node) = pending.pop() {
+            if !visited.insert(node.hash()) {
+                continue;
+            }
+            if node.node_type() == NodeType::Filter
+                && !self.has_filter(node.name())
+                && !missing.iter().any(|name| name == node.name())
+            {
+                missing.push(node.name().to_owned());
+            }
+            pending.extend(
+                node.get_incoming_edges()
+                    .into_iter()
+                    .map(|edge| edge.upstream_node),
+            );
+        }
+        missing.sort();
+        missing
+    }
+}
+
+fn resolve_binary(path: &Path) -> PathBuf {
+    if path.components().count() > 1 {
+        return path.to_owned();
+    }
+    env::var_os("PATH")
+        .and_then(|paths| {
+            env::split_paths(&paths)
+                .map(|dir| dir.join(path))
+                .find(|candidate| candidate.is_file())
+        })
+        .unwrap_or_else(|| path.to_owned())
+}
+
+// ``ffmpeg -version``: the version from the first line and the `configuration:` flags.
+pub fn parse_version(text: &str) -> Result<(String, Vec<String>), Box<dyn Error>> {
+    let version = text
+        .lines()
+        .find_map(|line| line.strip_prefix("ffmpeg version "))
+        .and_then(|rest| rest.split_whitespace().next())
+        .ok_or("Missing `ffmpeg version` line")?
+        .to_owned();
+    let configuration = text
+        .lines()
+        .find_map(|line| line.strip_prefix("configuration:"))
+        .map(|flags| flags.split_whitespace().map(str::to_owned).collect())
+        .unwrap_or_default();
+    Ok((version, configuration))
+}
+
+// ``ffmpeg -encoders`` / ``ffmpeg -decoders``; codecs follow the `------` separator:
+// `` V....D libx264              libx264 H.264 / AVC / MPEG-4 AVC (codec h264)``
+pub fn parse_codecs(text: &str) -> Result<BTreeMap<String, Codec>, Box<dyn Error>> {
+    let mut codecs = BTreeMap::new();
+    for line in text
+        .lines()
+        .skip_while(|line| !line.trim().starts_with("---"))
+        .skip(1)
+    {
+        let mut tokens = line.split_whitespace();
+        let (Some(flags), Some(name)) = (tokens.next(), tokens.next()) else {
+            continue;
+        };
+        let codec_type = match flags.chars().next() {
+            Some('V') => CodecType::Video,
+            Some('A') => CodecType::Audio,
+            Some('S') => CodecType::Subtitle,
+            Some('D') => CodecType::Data,
+            Some('T') => CodecType::Attachment,
+            _ => return Err(format!("Invalid codec line: {}", line).into()),
+        };
+        let description = line.trim_start()[flags.len()..].trim_start()[name.len()..].trim();
+        codecs.insert(
+            name.to_owned(),
+            Codec {
+                name: name.to_owned(),
+                codec_type,
+                experimental: flags.contains('X'),
+                description: description.to_owned(),
+            },
+        );
+    }
+    Ok(codecs)
+}
+
+// ``ffmpeg -muxers`` / ``ffmpeg -demuxers``; entries follow the `--` separator and
+// may list several comma separated names: `` D  mov,mp4,m4a,3gp,3g2,mj2 QuickTime / MOV``
+pub fn parse_formats(text: &str, flag: char) -> BTreeSet<String> {
+    let mut formats = BTreeSet::new();
+    for line in text.lines().skip_while(|line| line.trim() != "--").skip(1) {
+        if line.len() < 4 || !line[..4].contains(flag) {
+            continue;
+        }
+        if let Some(names) = line[4..].split_whitespace().next() {
+            formats.extend(names.split(',').map(str::to_owned));
+        }
+    }
+    formats
+}
+
+// ``ffmpeg -pix_fmts``; formats follow the `-----` separator: ``IO... yuv420p  3  12  8-8-8``
+pub fn parse_pix_fmts(text: &str) -> BTreeSet<String> {
+    text.lines()
+        .skip_while(|line| !line.starts_with("-----"))
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(str::to_owned)
+        .collect()
+}
+
+// ``ffmpeg -protocols``: names listed under `Input:` and `Output:`.
+pub fn parse_protocols(text: &str) -> Protocols {
+    let mut protocols = Protocols::default();
+    let mut section: Option<&mut BTreeSet<String>> = None;
+    for line in text.lines() {
+        match line.trim() {
+            "Input:" => section = Some(&mut protocols.input),
+            "Output:" => section = Some(&mut protocols.output),
+            "" => {}
+            name if line.starts_with(' ') => {
+                if let Some(section) = section.as_mut() {
+                    section.insert(name.to_owned());
+                }
+            }
+            _ => {}
+        }
+    }
+    protocols
+}
@@ -0,0 +1,476 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+
+/*
+Filter catalog.
+
+    The catalog describes the filters a given ffmpeg build provides. It is parsed
+    from two kinds of text dumps:
+     - ``ffmpeg -filters``: one line per filter with its flags, input/output media
+       types and a description.
+     - ``ffmpeg -h filter=NAME``: named input/output pads and the filter's
+       AVOptions (type, range, default and named constants).
+
+    A filter listed by ``-filters`` without a matching help dump is kept with its
+    pads but no options.
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MediaType {
+    Audio,
+    Video,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Pad {
+    pub name: String,
+    pub media_type: MediaType,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Pads {
+    // A fixed list of pads; empty for sources (inputs) and sinks (outputs).
+    Fixed(Vec<Pad>),
+    // The number and type of pads depend on the options, e.g. `split` or `concat`.
+    Dynamic,
+}
+
+impl Pads {
+    pub fn len(&self) -> Option<usize> {
+        match self {
+            Pads::Fixed(pads) => Some(pads.len()),
+            Pads::Dynamic => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum OptionType {
+    Int,
+    Int64,
+    Uint64,
+    Float,
+    Double,
+    Boolean,
+    String,
+    Flags,
+    Duration,
+    Color,
+    ImageSize,
+    VideoRate,
+    Rational,
+    PixFmt,
+    SampleFmt,
+    ChannelLayout,
+    Dictionary,
+    Binary,
+    Other(String),
+}
+
+impl OptionType {
+    fn parse(text: &str) -> OptionType {
+        match text {
+            "int" => OptionType::Int,
+            "int64" => OptionType::Int64,
+            "uint64" => OptionType::Uint64,
+            "float" => OptionType::Float,
+            "double" => OptionType::Double,
+            "boolean" => OptionType::Boolean,
+            "string" => OptionType::String,
+            "flags" => OptionType::Flags,
+            "duration" => OptionType::Duration,
+            "color" => OptionType::Color,
+            "image_size" => OptionType::ImageSize,
+            "video_rate" => OptionType::VideoRate,
+            "rational" => OptionType::Rational,
+            "pix_fmt" => OptionType::PixFmt,
+            "sample_fmt" => OptionType::SampleFmt,
+            "channel_layout" => OptionType::ChannelLayout,
+            "dictionary" => OptionType::Dictionary,
+            "binary" => OptionType::Binary,
+            other => OptionType::Other(other.to_owned()),
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            OptionType::Int
+                | OptionType::Int64
+                | OptionType::Uint64
+                | OptionType::Float
+                | OptionType::Double
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct OptionConstant {
+    pub name: String,
+    pub value: String,
+    pub description: String,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct FilterOption {
+    pub name: String,
+    pub option_type: OptionType,
+    pub description: String,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub default: Option<String>,
+    pub constants: Vec<OptionConstant>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct FilterInfo {
+    pub name: String,
+    pub description: String,
+    pub timeline: bool,
+    pub slice_threads: bool,
+    pub command: bool,
+    pub inputs: Pads,
+    pub outputs: Pads,
+    pub options: Vec<FilterOption>,
+}
+
+impl FilterInfo {
+    pub fn get_option(&self, name: &str) -> Option<&FilterOption> {
+        self.options.iter().find(|option| option.name == name)
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct FilterCatalog {
+    filters: BTreeMap<String, FilterInfo>,
+}
+
+impl FilterCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Builds a catalog from an ``ffmpeg -filters`` dump and any number of
+    // ``ffmpeg -h filter=NAME`` dumps.
+    pub fn from_dumps(filters: &str, helps: &[&str]) -> Result<Self, Box<dyn Error>> {
+        let mut catalog = Self::new();
+        for filter in parse_filter_list(filters)? {
+            catalog.insert(filter);
+        }
+        for help in helps {
+            catalog.add_help(help)?;
+        }
+        Ok(catalog)
+    }
+
+    pub fn insert(&mut self, filter: FilterInfo) {
+        self.filters.insert(filter.name.clone(), filter);
+    }
+
+    // Merges a help dump into the catalog. Pads from the help dump replace the
+    // anonymous ones from the filter list since they carry names.
+    pub fn add_help(&mut self, text: &str) -> Result<(), Box<dyn Error>> {
+        let help = parse_filter_help(text)?;
+        match self.filters.get_mut(&help.name) {
+            Some(filter) => {
+                filter.inputs = help.inputs;
+                filter.outputs = help.outputs;
+                filter.options = help.options;
+                filter.timeline |= help.timeline;
+                filter.slice_threads |= help.slice_threads;
+            }
+            None => {
+                self.filters.insert(help.name.clone(), help);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FilterInfo> {
+        self.filters.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.filters.contains_key(name)
+    }
+
+    pub fn filters(&self) -> impl Iterator<Item = &FilterInfo> {
+        self.filters.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+}
+
+// Parses ``ffmpeg -filters``. Legend lines are skipped; every other line looks like
+// `` TSC overlay           VV->V      Overlay a video source on top of the input.``
+pub fn parse_filter_list(text: &str) -> Result<Vec<FilterInfo>, Box<dyn Error>> {
+    let mut filters = Vec::new();
+    for line in text.lines() {
+        let mut tokens = line.split_whitespace();
+        let (Some(flags), Some(name), Some(io)) = (tokens.next(), tokens.next(), tokens.next())
+        else {
+            continue;
+        };
+        if flags.len() != 3 || !flags.chars().all(|c| "TSC.".contains(c)) || !io.contains("->") {
+            continue;
+        }
+        let (inputs, outputs) = io
+            .split_once("->")
+            .ok_or_else(|| format!("Invalid filter pads: {}", io))?;
+        filters.push(FilterInfo {
+            name: name.to_owned(),
+            description: skip_tokens(line, 3).to_owned(),
+            timeline: flags.contains('T'),
+            slice_threads: flags.contains('S'),
+            command: flags.contains('C'),
+            inputs: parse_pad_types(inputs)?,
+            outputs: parse_pad_types(outputs)?,
+            options: Vec::new(),
+        });
+    }
+    Ok(filters)
+}
+
+fn parse_pad_types(text: &str) -> Result<Pads, Box<dyn Error>> {
+    match text {
+        "|" => Ok(Pads::Fixed(Vec::new())),
+        "N" => Ok(Pads::Dynamic),
+        _ => text
+            .chars()
+            .map(|c| {
+                let media_type = match c {
+                    'A' => MediaType::Audio,
+                    'V' => MediaType::Video,
+                    _ => return Err(format!("Invalid pad type: {}", c).into()),
+                };
+                Ok(Pad {
+                    name: "default".to_owned(),
+                    media_type,
+                })
+            })
+            .collect::<Result<Vec<Pad>, Box<dyn Error>>>()
+            .map(Pads::Fixed),
+    }
+}
+
+#[derive(PartialEq)]
+enum HelpSection {
+    Header,
+    Inputs,
+    Outputs,
+    Options,
+}
+
+// Parses ``ffmpeg -h filter=NAME``.
+pub fn parse_filter_help(text: &str) -> Result<FilterInfo, Box<dyn Error>> {
+    let mut lines = text.lines();
+    let name = lines
+        .by_ref()
+        .find_map(|line| line.strip_prefix("Filter "))
+        .ok_or("Missing `Filter NAME` header")?
+        .trim()
+        .to_owned();
+
+    let mut filter = FilterInfo {
+        name,
+        description: String::new(),
+        timeline: false,
+        slice_threads: false,
+        command: false,
+        inputs: Pads::Fixed(Vec::new()),
+        outputs: Pads::Fixed(Vec::new()),
+        options: Vec::new(),
+    };
+    let mut section = HelpSection::Header;
+
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed.starts_with("This filter has support for timeline") {
+            filter.timeline = true;
+            continue;
+        }
+        if line.ends_with("AVOptions:") {
+            section = HelpSection::Options;
+            continue;
+        }
+        match trimmed {
+            "Inputs:" => section = HelpSection::Inputs,
+            "Outputs:" => section = HelpSection::Outputs,
+            "slice threading supported" => filter.slice_threads = true,
+            _ => match section {
+                HelpSection::Header => {
+                    if filter.description.is_empty() {
+                        filter.description = trimmed.to_owned();
+                    }
+                }
+                HelpSection::Inputs => add_pad(&mut filter.inputs, trimmed)?,
+                HelpSection::Outputs => add_pad(&mut filter.outputs, trimmed)?,
+                HelpSection::Options => add_option_line(&mut filter.options, line)?,
+            },
+        }
+    }
+
+    Ok(filter)
+}
+
+// `#0: main (video)`, `dynamic (depending on the options)` or `none (source filter)`.
+fn add_pad(pads: &mut Pads, line: &str) -> Result<(), Box<dyn Error>> {
+    if line.starts_with("dynamic") {
+        *pads = Pads::Dynamic;
+        return Ok(());
+    }
+    if line.starts_with("none") {
+        return Ok(());
+    }
+    let (_, pad) = line
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid pad: {}", line))?;
+    let (name, media_type) = pad
+        .trim()
+        .split_once(" (")
+        .ok_or_else(|| format!("Invalid pad: {}", line))?;
+    let media_type = match media_type.trim_end_matches(')') {
+        "audio" => MediaType::Audio,
+        "video" => MediaType::Video,
+        other => return Err(format!("Invalid pad type: {}", other).into()),
+    };
+    if let Pads::Fixed(pads) = pads {
+        pads.push(Pad {
+            name: name.to_owned(),
+            media_type,
+        });
+    }
+    Ok(())
+}
+
+// Option lines are indented by three spaces, their named constants by five:
+// `   eval              <int>        ..FV....... specify when to evaluate expressions (from 0 to 1) (default init)`
+// `     init            0            ..FV....... eval expressions once during initialization`
+fn add_option_line(options: &mut Vec<FilterOption>, line: &str) -> Result<(), Box<dyn Error>> {
+    let indent = line.len() - line.trim_start().len();
+    let mut tokens = line.split_whitespace();
+    let name = tokens.next().ok_or("Empty option line")?;
+    let second = tokens.next().unwrap_or_default();
+
+    if indent <= 3 && second.starts_with('<') {
+        // Options shared through a child class (e.g. `framesync`) may repeat ones
+        // already listed by the filter itself.
+        if options.iter().any(|option| option.name == name) {
+            return Ok(());
+        }
+        let (description, min, max, default) =
+            parse_option_description(skip_flags(skip_tokens(line, 2)));
+        options.push(FilterOption {
+            name: name.to_owned(),
+            option_type: OptionType::parse(second.trim_start_matches('<').trim_end_matches('>')),
+            description,
+            min,
+            max,
+            default,
+            constants: Vec::new(),
+        });
+    } else {
+        let option = options
+            .last_mut()
+            .ok_or_else(|| format!("Constant without option: {}", line.trim()))?;
+        // Constants of a repeated shared option are already known.
+        if option
+            .constants
+            .iter()
+            .any(|constant| constant.name == name)
+        {
+            return Ok(());
+        }
+        option.constants.push(OptionConstant {
+            name: name.to_owned(),
+            value: second.to_owned(),
+            description: skip_flags(skip_tokens(line, 2)).to_owned(),
+        });
+    }
+    Ok(())
+}
+
+// Returns the text following the first `count` whitespace separated tokens.
+fn skip_tokens(line: &str, count: usize) -> &str {
+    let mut rest = line.trim_start();
+    for _ in 0..count {
+        rest = match rest.find(char::is_whitespace) {
+            Some(index) => rest[index..].trim_start(),
+            None => "",
+        };
+    }
+    rest.trim_end()
+}
+
+fn skip_flags(text: &str) -> &str {
+    let text = text.trim_start();
+    match text.split_once(' ') {
+        Some((flags, rest)) if is_flags(flags) => rest.trim(),
+        None if is_flags(text) => "",
+        _ => text,
+    }
+}
+
+fn is_flags(token: &str) -> bool {
+    !token.is_empty() && token.chars().all(|c| ".EDFVASXRBTP".contains(c))
+}
+
+fn parse_option_description(text: &str) -> (String, Option<f64>, Option<f64>, Option<String>) {
+    let mut description = text.trim();
+    let mut default = None;
+    let mut min = None;
+    let mut max = None;
+
+    if let Some(index) = description.rfind("(default ") {
+        if description.ends_with(')') {
+            let value = &description[index + "(default ".len()..description.len() - 1];
+            default = Some(value.trim_matches('"').to_owned());
+            description = description[..index].trim_end();
+        }
+    }
+    if let Some(index) = description.rfind("(from ") {
+        if description.ends_with(')') {
+            let range = &description[index + "(from ".len()..description.len() - 1];
+            if let Some((from, to)) = range.split_once(" to ") {
+                min = parse_limit(from);
+                max = parse_limit(to);
+            }
+            description = description[..index].trim_end();
+        }
+    }
+
+    (description.to_owned(), min, max, default)
+}
+
+fn parse_limit(text: &str) -> Option<f64> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+    let value = match text {
+        "INT_MAX" => i32::MAX as f64,
+        "INT_MIN" => i32::MIN as f64,
+        "UINT32_MAX" => u32::MAX as f64,
+        "I64_MAX" => i64::MAX as f64,
+        "I64_MIN" => i64::MIN as f64,
+        "FLT_MAX" => f32::MAX as f64,
+        "FLT_MIN" => f32::MIN_POSITIVE as f64,
+        "DBL_MAX" => f64::MAX,
+        "DBL_MIN" => f64::MIN_POSITIVE,
+        _ => text.parse().ok()?,
+    };
+    Some(if negative { -value } else { value })
+}
@@ -0,0 +1,289 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::catalog::{FilterCatalog, FilterInfo, FilterOption, OptionType, Pads};
+
+/*
+Filter builder code generator.
+
+    For every filter in a catalog this emits a constructor function named after the
+    filter and a builder struct with one setter per option:
+
+        filters::scale(&input).w("1280").h("-2").eval(ScaleEval::Frame).stream()
+
+    Numeric and boolean options take Rust values, options with named constants take
+    a generated enum (a slice of it for flags), everything else takes a string.
+*/
+
+const HEADER: &str = "\
+// @generated by `cargo run --features cli -- codegen FILTERS_DUMP HELP_DUMP...` from
+// `ffmpeg -filters` and `ffmpeg -h filter=NAME` dumps. Do not edit by hand.
+#![allow(clippy::should_implement_trait, clippy::wrong_self_convention)]
+";
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+// Identifiers that cannot be raw, plus the builder's own methods.
+const RESERVED: &[&str] = &[
+    "self", "Self", "super", "crate", "new", "arg", "stream", "node",
+];
+
+pub fn generate(catalog: &FilterCatalog) -> String {
+    let mut code = String::from(HEADER);
+    for filter in catalog.filters() {
+        code.push('\n');
+        generate_filter(&mut code, filter);
+    }
+    code
+}
+
+fn generate_filter(code: &mut String, filter: &FilterInfo) {
+    let struct_name = to_camel_case(&filter.name);
+    let fn_name = to_ident(&filter.name);
+
+    let _ = writeln!(code, "/// {}", doc(&filter.description, &filter.name));
+    match &filter.inputs {
+        Pads::Fixed(pads) if pads.is_empty() => {
+            let _ = writeln!(code, "pub fn {}() -> {} {{", fn_name, struct_name);
+            let _ = writeln!(
+                code,
+                "    {}::new(crate::node::StreamSpec::Multiple(Vec::new()))",
+                struct_name
+            );
+        }
+        Pads::Fixed(pads) if pads.len() > 1 => {
+            let mut seen = HashSet::new();
+            let params: Vec<String> = pads
+                .iter()
+                .enumerate()
+                .map(|(i, pad)| {
+                    let name = to_ident(&pad.name);
+                    if pad.name == "default" || !seen.insert(name.clone()) {
+                        format!("input{}", i)
+                    } else {
+                        name
+                    }
+                })
+                .collect();
+            let args: Vec<String> = params
+                .iter()
+                .map(|param| format!("{}: &crate::node::FilterableStream", param))
+                .collect();
+            let _ = writeln!(
+                code,
+                "pub fn {}({}) -> {} {{",
+                fn_name,
+                args.join(", "),
+                struct_name
+            );
+            let _ = writeln!(
+                code,
+                "    {}::new(crate::node::StreamSpec::from([{}]))",
+                struct_name,
+                params.join(", ")
+            );
+        }
+        _ => {
+            let _ = writeln!(
+                code,
+                "pub fn {}(stream_spec: impl Into<crate::node::StreamSpec>) -> {} {{",
+                fn_name, struct_name
+            );
+            let _ = writeln!(code, "    {}::new(stream_spec.into())", struct_name);
+        }
+    }
+    code.push_str("}\n\n");
+
+    let _ = writeln!(code, "pub struct {} {{", struct_name);
+    code.push_str("    stream_spec: crate::node::StreamSpec,\n");
+    code.push_str("    kwargs: crate::daq::Kwargs,\n");
+    code.push_str("}\n\n");
+
+    let _ = writeln!(code, "impl {} {{", struct_name);
+    code.push_str("    fn new(stream_spec: crate::node::StreamSpec) -> Self {\n");
+    code.push_str("        Self {\n");
+    code.push_str("            stream_spec,\n");
+    code.push_str("            kwargs: crate::daq::Kwargs::new(),\n");
+    code.push_str("        }\n");
+    code.push_str("    }\n\n");
+    code.push_str("    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {\n");
+    code.push_str("        self.kwargs.push((key.to_owned(), value.to_string()));\n");
+    code.push_str("        self\n");
+    code.push_str("    }\n");
+
+    let mut enums = String::new();
+    let mut methods: HashSet<String> = HashSet::new();
+    for option in &filter.options {
+        let method = to_ident(&option.name);
+        if !methods.insert(method.clone()) {
+            continue;
+        }
+        code.push('\n');
+        let _ = writeln!(code, "    /// {}", option_doc(option));
+        let enum_name = format!("{}{}", struct_name, to_camel_case(&option.name));
+        let (value_type, value_expr) = if !option.constants.is_empty() {
+            generate_enum(&mut enums, &enum_name, option);
+            if option.option_type == OptionType::Flags {
+                (
+                    format!("&[{}]", enum_name),
+                    "value.iter().map(|flag| flag.as_str()).collect::<Vec<_>>().join(\"+\")"
+                        .to_owned(),
+                )
+            } else {
+                (enum_name, "value.as_str()".to_owned())
+            }
+        } else {
+            let value_type = match option.option_type {
+                OptionType::Int | OptionType::Int64 => "i64",
+                OptionType::Uint64 => "u64",
+                OptionType::Float | OptionType::Double => "f64",
+                OptionType::Boolean => "bool",
+                _ => "&str",
+            };
+            (value_type.to_owned(), "value".to_owned())
+        };
+        let _ = writeln!(
+            code,
+            "    pub fn {}(self, value: {}) -> Self {{",
+            method, value_type
+        );
+        let _ = writeln!(code, "        self.arg({:?}, {})", option.name, value_expr);
+        code.push_str("    }\n");
+    }
+
+    if filter.timeline && methods.insert("enable".to_owned()) {
+        code.push('\n');
+        code.push_str(
+            "    /// Timeline editing: expression enabling the filter, e.g. `between(t,10,20)`\n",
+        );
+        code.push_str("    pub fn enable(self, value: &str) -> Self {\n");
+        code.push_str("        self.arg(\"enable\", value)\n");
+        code.push_str("    }\n");
+    }
+
+    if filter.outputs.len() == Some(1) {
+        code.push('\n');
+        code.push_str("    pub fn stream(self) -> crate::node::FilterableStream {\n");
+        let _ = writeln!(
+            code,
+            "        crate::ffmpeg::filter(self.stream_spec, {:?}, self.kwargs)",
+            filter.name
+        );
+        code.push_str("    }\n");
+    }
+
+    code.push('\n');
+    code.push_str("    pub fn node(self) -> crate::node::FilterNode {\n");
+    let _ = writeln!(
+        code,
+        "        crate::ffmpeg::filter_multi_output(self.stream_spec, {:?}, self.kwargs)",
+        filter.name
+    );
+    code.push_str("    }\n");
+    code.push_str("}\n");
+    code.push_str(&enums);
+}
+
+fn generate_enum(code: &mut String, enum_name: &str, option: &FilterOption) {
+    let mut variants: Vec<(String, &str)> = Vec::new();
+    for constant in &option.constants {
+        let mut variant = to_camel_case(&constant.name);
+        if variant.starts_with(|c: char| c.is_ascii_digit()) {
+            variant = format!("V{}", variant);
+        } else if variant == "Self" {
+            variant.push('_');
+        }
+        if !variants.iter().any(|(existing, _)| *existing == variant) {
+            variants.push((variant, &constant.name));
+        }
+    }
+
+    code.push('\n');
+    code.push_str("#[derive(Clone, Copy, PartialEq, Eq, Debug)]\n");
+    let _ = writeln!(code, "pub enum {} {{", enum_name);
+    for (variant, _) in &variants {
+        let _ = writeln!(code, "    {},", variant);
+    }
+    code.push_str("}\n\n");
+    let _ = writeln!(code, "impl {} {{", enum_name);
+    code.push_str("    pub fn as_str(&self) -> &'static str {\n");
+    code.push_str("        match self {\n");
+    for (variant, name) in &variants {
+        let _ = writeln!(
+            code,
+            "            {}::{} => {:?},",
+            enum_name, variant, name
+        );
+    }
+    code.push_str("        }\n");
+    code.push_str("    }\n");
+    code.push_str("}\n");
+}
+
+fn option_doc(option: &FilterOption) -> String {
+    let mut text = doc(&option.description, &option.name);
+    if let (Some(min), Some(max)) = (option.min, option.max) {
+        let _ = write!(text, " (from {} to {})", min, max);
+    }
+    match &option.default {
+        Some(default) if option.option_type == OptionType::String => {
+            let _ = write!(text, " (default {:?})", default);
+        }
+        Some(default) => {
+            let _ = write!(text, " (default {})", default);
+        }
+        None => {}
+    }
+    text
+}
+
+fn doc(description: &str, fallback: &str) -> String {
+    let description = description.trim();
+    if description.is_empty() {
+        format!("`{}`", fallback)
+    } else {
+        description.to_owned()
+    }
+}
+
+fn to_camel_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn to_ident(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if KEYWORDS.contains(&ident.as_str()) {
+        format!("r#{}", ident)
+    } else if RESERVED.contains(&ident.as_str()) {
+        format!("{}_", ident)
+    } else {
+        ident
+    }
+}
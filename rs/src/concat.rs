@@ -0,0 +1,146 @@
+use std::env;
+use std::io;
+use std::path::Path;
+
+use crate::daq::Kwargs;
+use crate::node::InputNode;
+use crate::time::{Duration, Timestamp};
+use crate::utils::{quote, TempFile};
+
+/*
+Concat demuxer list files.
+
+    ``ConcatList`` renders an ``ffconcat version 1.0`` script:
+
+        file '/media/intro.mp4'
+        outpoint 5
+        file '/media/it'\''s here.mp4'
+        inpoint 1.5
+
+    ``ConcatList::input`` writes it to a temporary file and returns an input
+    reading it with ``-f concat -safe 0``.  The file is removed once the input
+    node, the streams built from it and any ``CompiledCommand`` referring to it
+    are all dropped.
+*/
+
+#[derive(Clone, Debug)]
+pub struct ConcatFile {
+    path: String,
+    inpoint: Option<Timestamp>,
+    outpoint: Option<Timestamp>,
+    duration: Option<Duration>,
+    metadata: Kwargs,
+}
+
+impl ConcatFile {
+    pub fn new(path: &str) -> ConcatFile {
+        ConcatFile {
+            path: path.to_owned(),
+            inpoint: None,
+            outpoint: None,
+            duration: None,
+            metadata: Kwargs::new(),
+        }
+    }
+
+    pub fn inpoint(mut self, position: Timestamp) -> Self {
+        self.inpoint = Some(position);
+        self
+    }
+
+    pub fn outpoint(mut self, position: Timestamp) -> Self {
+        self.outpoint = Some(position);
+        self
+    }
+
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    // Metadata attached to every packet of the file (`file_packet_meta`).
+    pub fn metadata(mut self, key: &str, value: &str) -> Self {
+        self.metadata.push((key.to_owned(), value.to_owned()));
+        self
+    }
+}
+
+impl From<&str> for ConcatFile {
+    fn from(path: &str) -> Self {
+        ConcatFile::new(path)
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct ConcatList {
+    files: Vec<ConcatFile>,
+}
+
+impl ConcatList {
+    pub fn new() -> ConcatList {
+        ConcatList::default()
+    }
+
+    pub fn file(mut self, file: impl Into<ConcatFile>) -> Self {
+        self.files.push(file.into());
+        self
+    }
+
+    // The script, with paths as given.  Relative paths are resolved by ffmpeg
+    // against the directory of the list file.
+    pub fn render(&self) -> String {
+        self.render_with(|path| path.to_owned())
+    }
+
+    // Writes the script to a temporary file and reads it with the concat demuxer.
+    // Relative paths are made absolute first, since the list lives elsewhere.
+    pub fn input(&self, mut kwargs: Kwargs) -> io::Result<InputNode> {
+        if self.files.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Concat list needs at least one file",
+            ));
+        }
+        let cwd = env::current_dir()?;
+        let script = self.render_with(|path| {
+            if path.contains("://") || Path::new(path).is_absolute() {
+                path.to_owned()
+            } else {
+                cwd.join(path).to_string_lossy().into_owned()
+            }
+        });
+        let temp_file = TempFile::create(".ffconcat", &script)?;
+
+        let mut input_kwargs = crate::kwargs! {"f" => "concat", "safe" => 0};
+        input_kwargs.append(&mut kwargs);
+        input_kwargs.push((
+            "filename".to_owned(),
+            temp_file.path().to_string_lossy().into_owned(),
+        ));
+        Ok(InputNode::with_temp_file("input", input_kwargs, temp_file))
+    }
+
+    fn render_with(&self, resolve: impl Fn(&str) -> String) -> String {
+        let mut script = String::from("ffconcat version 1.0\n");
+        for file in &self.files {
+            script.push_str(&format!("file {}\n", quote(&resolve(&file.path))));
+            if let Some(inpoint) = file.inpoint {
+                script.push_str(&format!("inpoint {}\n", inpoint.format_seconds()));
+            }
+            if let Some(outpoint) = file.outpoint {
+                script.push_str(&format!("outpoint {}\n", outpoint.format_seconds()));
+            }
+            if let Some(duration) = file.duration {
+                script.push_str(&format!("duration {}\n", duration.format_seconds()));
+            }
+            for (key, value) in &file.metadata {
+                script.push_str(&format!(
+                    "file_packet_meta {} {}\n",
+                    quote(key),
+                    quote(value)
+                ));
+            }
+        }
+        script
+    }
+}
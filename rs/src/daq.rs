@@ -1,182 +1,246 @@
+use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::hash::{Hash, Hasher};
 
-fn get_hash_code(obj: &impl std::hash::Hash) -> u64 {
-    let mut hasher = std::collections::hash_map::DefaultHasher::new();
-    obj.hash(&mut hasher);
-    hasher.finish()
-}
+/*
+Node in a directed-acyclic graph (DAG).
+
+Edges:
+    DagNodes are connected by edges.  An edge connects two nodes with a label for
+    each side:
+     - ``upstream_node``: upstream/parent node
+     - ``upstream_label``: label on the outgoing side of the upstream node
+     - ``downstream_node``: downstream/child node
+     - ``downstream_label``: label on the incoming side of the downstream node
+
+    For example, DagNode A may be connected to DagNode B with an edge labelled
+    "foo" on A's side, and "bar" on B's side:
+
+       _____               _____
+      |     |             |     |
+      |  A  >[foo]---[bar]>  B  |
+      |_____|             |_____|
+
+    Nodes cannot have more than one incoming edge with the same label.
 
-type Label = String;
-type Selector = Option<char>;
-type IncomingEdgeMap = HashMap<Label, (Box<dyn DagNode>, Label, Selector)>;
-type OutgoingEdgeMap = HashMap<Label, Vec<(Box<dyn DagNode>, Label, Selector)>>;
+    DagNodes may have any number of incoming edges and any number of outgoing
+    edges.  DagNodes keep track only of their incoming edges, but the entire graph
+    structure can be inferred by looking at the furthest downstream nodes and
+    working backwards.
 
-trait DagNode {
+Hashing:
+    DagNodes must be hashable, and two nodes are considered to be equivalent if
+    they have the same hash value.
+
+    Nodes are immutable, and the hash should remain constant as a result.  If a
+    node with new contents is required, create a new node and throw the old one
+    away.
+*/
+pub trait DagNode: Clone {
+    // hash of the node.
     fn hash(&self) -> u64;
-    fn eq(&self, other: &dyn DagNode) -> bool;
-    fn get_incoming_edge_map(&self) -> IncomingEdgeMap;
-    fn get_incoming_edges(&self) -> Vec<DagEdge>;
+
+    // Compare two nodes; implementations should return true if hashes match.
+    fn eq(&self, other: &Self) -> bool {
+        self.hash() == other.hash()
+    }
+
+    // Provides information about all incoming edges that connect to this node.
+    //
+    // The edge map maps an ``incoming_label`` to ``(outgoing_node, outgoing_label)``.
+    // Note that implicitly, ``incoming_node`` is ``self``.  See "Edges" section above.
+    fn get_incoming_edge_map(&self) -> &IncomingEdgeMap<Self>;
+
+    fn get_incoming_edges(&self) -> Vec<DagEdge<Self>> {
+        get_incoming_edges(self, self.get_incoming_edge_map())
+    }
+}
+
+pub type Label = String;
+pub type Selector = Option<char>;
+pub type Kwargs = Vec<(String, String)>;
+
+#[derive(Clone)]
+pub struct IncomingEdge<N> {
+    pub upstream_node: N,
+    pub upstream_label: Label,
+    pub upstream_selector: Selector,
+}
+
+#[derive(Clone)]
+pub struct OutgoingEdge<N> {
+    pub downstream_node: N,
+    pub downstream_label: Label,
+    pub downstream_selector: Selector,
+}
+
+// Edge maps keep their insertion order, which is also the order streams appear in
+// the compiled command.
+pub type IncomingEdgeMap<N> = Vec<(Label, IncomingEdge<N>)>;
+pub type OutgoingEdgeMap<N> = Vec<(Label, Vec<OutgoingEdge<N>>)>;
+
+#[derive(Clone)]
+pub struct DagEdge<N> {
+    pub downstream_node: N,
+    pub downstream_label: Label,
+    pub upstream_node: N,
+    pub upstream_label: Label,
+    pub upstream_selector: Selector,
+}
+
+pub fn get_hash_code(obj: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    obj.hash(&mut hasher);
+    hasher.finish()
 }
 
-struct KwargReprNode {
-    name: String,
-    incoming_edge_map: IncomingEdgeMap,
-    kwargs: HashMap<String, String>,
+// A DagNode that can be represented as a set of args+kwargs.
+#[derive(Clone)]
+pub struct KwargReprNode<N> {
+    pub name: String,
+    pub kwargs: Kwargs,
+    incoming_edge_map: IncomingEdgeMap<N>,
     hash: u64,
-    incoming_edges: Vec<DagEdge>,
 }
 
-impl KwargReprNode {
-    fn new(incoming_edge_map: IncomingEdgeMap, name: String, kwargs: HashMap<String, String>) -> Self {
+impl<N: DagNode> KwargReprNode<N> {
+    pub fn new(incoming_edge_map: IncomingEdgeMap<N>, name: String, kwargs: Kwargs) -> Self {
         let hash = Self::cal_hash(&name, &kwargs, &incoming_edge_map);
-        let incoming_edges = Self::set_incoming_edges(&incoming_edge_map);
         Self {
             name,
-            incoming_edge_map,
             kwargs,
+            incoming_edge_map,
             hash,
-            incoming_edges,
         }
     }
 
-    fn cal_hash(name: &str, kwargs: &HashMap<String, String>, incoming_edge_map: &IncomingEdgeMap) -> u64 {
-        Self::get_inner_hash(name, kwargs) + Self::get_upstream_hash(incoming_edge_map)
+    pub fn cal_hash(name: &str, kwargs: &Kwargs, incoming_edge_map: &IncomingEdgeMap<N>) -> u64 {
+        Self::get_inner_hash(name, kwargs).wrapping_add(Self::get_upstream_hash(incoming_edge_map))
     }
 
-    fn get_inner_hash(name: &str, kwargs: &HashMap<String, String>) -> u64 {
-        let obj = format!("{}{:?}", name, kwargs);
-        get_hash_code(&obj)
+    fn get_inner_hash(name: &str, kwargs: &Kwargs) -> u64 {
+        get_hash_code(&(name, kwargs))
     }
 
-    fn get_upstream_hash(incoming_edge_map: &IncomingEdgeMap) -> u64 {
+    fn get_upstream_hash(incoming_edge_map: &IncomingEdgeMap<N>) -> u64 {
         incoming_edge_map
             .iter()
-            .map(|(downstream_label, (upstream_node, upstream_label, upstream_selector))| {
-                let values = format!(
-                    "{:?}{:?}{:?}{:?}",
-                    downstream_label, upstream_node.hash(), upstream_label, upstream_selector
-                );
-                get_hash_code(&values)
+            .map(|(downstream_label, edge)| {
+                get_hash_code(&(
+                    downstream_label,
+                    edge.upstream_node.hash(),
+                    &edge.upstream_label,
+                    edge.upstream_selector,
+                ))
             })
-            .sum()
+            .fold(0, u64::wrapping_add)
     }
 
-    fn set_incoming_edges(incoming_edge_map: &IncomingEdgeMap) -> Vec<DagEdge> {
-        incoming_edge_map
-            .iter()
-            .map(|(downstream_label, (upstream_node, upstream_label, upstream_selector))| {
-                DagEdge {
-                    downstream_node: Box::new(Self {
-                        name: "".to_string(),
-                        incoming_edge_map: IncomingEdgeMap::new(),
-                        kwargs: HashMap::new(),
-                        hash: upstream_node.hash(),
-                        incoming_edges: vec![],
-                    }),
-                    downstream_label: downstream_label.clone(),
-                    upstream_node: upstream_node.clone(),
-                    upstream_label: upstream_label.clone(),
-                    upstream_selector: upstream_selector.clone(),
-                }
-            })
-            .collect()
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn get_incoming_edge_map(&self) -> &IncomingEdgeMap<N> {
+        &self.incoming_edge_map
     }
 }
 
-fn get_incoming_edges<'a>(downstream_node: &'a DagNode, incoming_edge_map: &IncomingEdgeMap<'a>) -> Vec<&'a DagEdge<'a>> {
-    let mut edges = Vec::new();
-    for (key, value) in incoming_edge_map {
-        let downstream_label = key;
-        let upstream_node = value.upstream_node;
-        let upstream_label = value.upstream_label;
-        let upstream_selector = value.upstream_selector.clone();
-
-        let edge = DagEdge {
-            downstream_node,
+pub fn get_incoming_edges<N: DagNode>(
+    downstream_node: &N,
+    incoming_edge_map: &IncomingEdgeMap<N>,
+) -> Vec<DagEdge<N>> {
+    incoming_edge_map
+        .iter()
+        .map(|(downstream_label, edge)| DagEdge {
+            downstream_node: downstream_node.clone(),
             downstream_label: downstream_label.clone(),
-            upstream_node,
-            upstream_label: upstream_label.clone(),
-            upstream_selector,
-        };
-
-        edges.push(edge);
-    }
-    edges
+            upstream_node: edge.upstream_node.clone(),
+            upstream_label: edge.upstream_label.clone(),
+            upstream_selector: edge.upstream_selector,
+        })
+        .collect()
 }
 
-fn get_outgoing_edges<'a>(upstream_node: &'a DagNode, outgoing_edge_map: &OutgoingEdgeMap<'a>) -> Vec<&'a DagEdge<'a>> {
+pub fn get_outgoing_edges<N: DagNode>(
+    upstream_node: &N,
+    outgoing_edge_map: &OutgoingEdgeMap<N>,
+) -> Vec<DagEdge<N>> {
     let mut edges = Vec::new();
-    for (key, value) in outgoing_edge_map {
-        let upstream_label = key;
-        let downstream_infos = value;
+    for (upstream_label, downstream_infos) in outgoing_edge_map {
         for downstream_info in downstream_infos {
-            let downstream_node = downstream_info.downstream_node;
-            let downstream_label = downstream_info.downstream_label.clone();
-            let downstream_selector = downstream_info.downstream_selector.clone();
-
-            let edge = DagEdge {
-                downstream_node,
-                downstream_label,
-                upstream_node,
+            edges.push(DagEdge {
+                downstream_node: downstream_info.downstream_node.clone(),
+                downstream_label: downstream_info.downstream_label.clone(),
+                upstream_node: upstream_node.clone(),
                 upstream_label: upstream_label.clone(),
-                upstream_selector: downstream_selector,
-            };
-
-            edges.push(edge);
+                upstream_selector: downstream_info.downstream_selector,
+            });
         }
     }
     edges
 }
 
-pub fn topo_sort(downstream_nodes: &[DagNode]) -> Result<(Vec<DagNode>, HashMap<&DagNode, HashMap<&str, Vec<DagEdge>>>>, Box<dyn Error>> {
-    let mut marked_nodes: HashSet<&DagNode> = HashSet::new();
-    let mut sorted_nodes: Vec<DagNode> = Vec::new();
-    let mut outgoing_edge_maps: HashMap<&DagNode, HashMap<&str, Vec<DagEdge>>> = HashMap::new();
+// Outgoing edge maps are keyed by node hash, since equal hashes mean equal nodes.
+pub type OutgoingEdgeMaps<N> = HashMap<u64, OutgoingEdgeMap<N>>;
+
+pub fn topo_sort<N: DagNode>(
+    downstream_nodes: &[N],
+) -> Result<(Vec<N>, OutgoingEdgeMaps<N>), Box<dyn Error>> {
+    let mut marked_nodes: HashSet<u64> = HashSet::new();
+    let mut sorted_nodes: Vec<N> = Vec::new();
+    let mut outgoing_edge_maps: OutgoingEdgeMaps<N> = HashMap::new();
 
-    fn visit<'a>(
-        upstream_node: &'a DagNode,
+    fn visit<N: DagNode>(
+        upstream_node: &N,
         upstream_label: &str,
-        downstream_node: Option<&'a DagNode>,
-        downstream_label: Option<&str>,
-        downstream_selector: Option<&Selector>,
-        marked_nodes: &mut HashSet<&'a DagNode>,
-        sorted_nodes: &mut Vec<DagNode>,
-        outgoing_edge_maps: &mut HashMap<&'a DagNode, HashMap<&str, Vec<DagEdge>>>,
+        downstream: Option<(&N, &str, Selector)>,
+        marked_nodes: &mut HashSet<u64>,
+        sorted_nodes: &mut Vec<N>,
+        outgoing_edge_maps: &mut OutgoingEdgeMaps<N>,
     ) -> Result<(), Box<dyn Error>> {
-        if marked_nodes.contains(upstream_node) {
+        if marked_nodes.contains(&upstream_node.hash()) {
             return Err("Graph is not a DAG".into());
         }
 
-        if let (Some(downstream_node), Some(downstream_label)) = (downstream_node, downstream_label) {
-            let outgoing_edge_map = outgoing_edge_maps.entry(upstream_node).or_insert(HashMap::new());
-            let outgoing_edge_infos = outgoing_edge_map.entry(downstream_label).or_insert(Vec::new());
-            outgoing_edge_infos.push(DagEdge {
+        if let Some((downstream_node, downstream_label, downstream_selector)) = downstream {
+            let outgoing_edge_map = outgoing_edge_maps.entry(upstream_node.hash()).or_default();
+            let position = match outgoing_edge_map
+                .iter()
+                .position(|(label, _)| label == upstream_label)
+            {
+                Some(position) => position,
+                None => {
+                    outgoing_edge_map.push((upstream_label.to_owned(), Vec::new()));
+                    outgoing_edge_map.len() - 1
+                }
+            };
+            outgoing_edge_map[position].1.push(OutgoingEdge {
                 downstream_node: downstream_node.clone(),
                 downstream_label: downstream_label.to_owned(),
-                upstream_node: upstream_node.clone(),
-                upstream_label: upstream_label.to_owned(),
-                upstream_selector: downstream_selector.cloned(),
+                downstream_selector,
             });
         }
 
-        if !sorted_nodes.contains(upstream_node) {
-            marked_nodes.insert(upstream_node);
+        if !sorted_nodes.iter().any(|node| node.eq(upstream_node)) {
+            marked_nodes.insert(upstream_node.hash());
 
             for edge in upstream_node.get_incoming_edges() {
                 visit(
                     &edge.upstream_node,
                     &edge.upstream_label,
-                    Some(&edge.downstream_node),
-                    Some(&edge.upstream_label),
-                    edge.upstream_selector.as_ref(),
+                    Some((
+                        &edge.downstream_node,
+                        &edge.downstream_label,
+                        edge.upstream_selector,
+                    )),
                     marked_nodes,
                     sorted_nodes,
                     outgoing_edge_maps,
                 )?;
             }
 
-            marked_nodes.remove(upstream_node);
+            marked_nodes.remove(&upstream_node.hash());
             sorted_nodes.push(upstream_node.clone());
         }
 
@@ -188,8 +252,6 @@ pub fn topo_sort(downstream_nodes: &[DagNode]) -> Result<(Vec<DagNode>, HashMap<
             upstream_node,
             "",
             None,
-            None,
-            None,
             &mut marked_nodes,
             &mut sorted_nodes,
             &mut outgoing_edge_maps,
@@ -197,4 +259,4 @@ pub fn topo_sort(downstream_nodes: &[DagNode]) -> Result<(Vec<DagNode>, HashMap<
     }
 
     Ok((sorted_nodes, outgoing_edge_maps))
-}
\ No newline at end of file
+}
@@ -0,0 +1,210 @@
+use crate::catalog::MediaType;
+use crate::daq::Kwargs;
+use crate::encoder::Encoder;
+use crate::ffmpeg::output_node;
+use crate::node::{OutputNode, OutputStream, Stream};
+use crate::time::Duration;
+use crate::utils::SharedStreams;
+
+/*
+DASH output with adaptation sets.
+
+    Each ``AdaptationSet`` groups representations of the same content, e.g. the
+    video ladder or the audio languages.  Representations are mapped to the
+    output in order, so ``-adaptation_sets`` lists their output stream indices
+    and each representation's encoder applies to its own index:
+
+        DashOutput::new("manifest.mpd")
+            .adaptation_set(AdaptationSet::new().representation(hd).representation(sd))
+            .adaptation_set(AdaptationSet::new().representation(audio))
+
+        -map [s2] -map [s3] -map 0:a -f dash ... -adaptation_sets id=0,streams=0,1 id=1,streams=2
+
+    Filter outputs can only be mapped once, so one shared by several
+    representations (e.g. one scaled stream at two bitrates) goes through a
+    ``split``, or an ``asplit`` when the representations' encoders are for audio.
+*/
+
+#[derive(Clone)]
+pub struct Representation {
+    stream: Stream,
+    encoder: Option<Encoder>,
+}
+
+impl Representation {
+    pub fn new(stream: &Stream) -> Representation {
+        Representation {
+            stream: stream.clone(),
+            encoder: None,
+        }
+    }
+
+    pub fn encoder(mut self, encoder: impl Into<Encoder>) -> Self {
+        self.encoder = Some(encoder.into());
+        self
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct AdaptationSet {
+    representations: Vec<Representation>,
+}
+
+impl AdaptationSet {
+    pub fn new() -> AdaptationSet {
+        AdaptationSet::default()
+    }
+
+    pub fn representation(mut self, representation: Representation) -> Self {
+        self.representations.push(representation);
+        self
+    }
+}
+
+#[derive(Clone)]
+pub struct DashOutput {
+    filename: String,
+    adaptation_sets: Vec<AdaptationSet>,
+    seg_duration: Option<Duration>,
+    use_template: Option<bool>,
+    use_timeline: Option<bool>,
+    init_seg_name: Option<String>,
+    media_seg_name: Option<String>,
+    kwargs: Kwargs,
+}
+
+impl DashOutput {
+    // `filename` is the manifest, e.g. `manifest.mpd`.
+    pub fn new(filename: &str) -> DashOutput {
+        DashOutput {
+            filename: filename.to_owned(),
+            adaptation_sets: Vec::new(),
+            seg_duration: None,
+            use_template: None,
+            use_timeline: None,
+            init_seg_name: None,
+            media_seg_name: None,
+            kwargs: Kwargs::new(),
+        }
+    }
+
+    pub fn adaptation_set(mut self, adaptation_set: AdaptationSet) -> Self {
+        self.adaptation_sets.push(adaptation_set);
+        self
+    }
+
+    pub fn seg_duration(mut self, duration: Duration) -> Self {
+        self.seg_duration = Some(duration);
+        self
+    }
+
+    pub fn use_template(mut self, value: bool) -> Self {
+        self.use_template = Some(value);
+        self
+    }
+
+    pub fn use_timeline(mut self, value: bool) -> Self {
+        self.use_timeline = Some(value);
+        self
+    }
+
+    // Templates may use `$RepresentationID$`, `$Number$`, `$Bandwidth$`, `$Time$`
+    // and `$ext$`.
+    pub fn init_seg_name(mut self, template: &str) -> Self {
+        self.init_seg_name = Some(template.to_owned());
+        self
+    }
+
+    pub fn media_seg_name(mut self, template: &str) -> Self {
+        self.media_seg_name = Some(template.to_owned());
+        self
+    }
+
+    // Any other muxer option, e.g. `streaming`.
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn node(&self) -> Result<OutputNode, String> {
+        if self.adaptation_sets.is_empty() {
+            return Err("DASH output needs at least one adaptation set".to_owned());
+        }
+
+        let mut shared = SharedStreams::new(
+            self.adaptation_sets
+                .iter()
+                .flat_map(|adaptation_set| &adaptation_set.representations)
+                .map(|representation| &representation.stream),
+        );
+        let mut streams: Vec<Stream> = Vec::new();
+        let mut adaptation_sets: Vec<String> = Vec::new();
+        let mut encoders: Vec<(String, &Encoder)> = Vec::new();
+        for (id, adaptation_set) in self.adaptation_sets.iter().enumerate() {
+            if adaptation_set.representations.is_empty() {
+                return Err(format!(
+                    "DASH adaptation set #{} has no representations",
+                    id
+                ));
+            }
+            let mut indices: Vec<String> = Vec::new();
+            for representation in &adaptation_set.representations {
+                let index = streams.len().to_string();
+                if let Some(encoder) = &representation.encoder {
+                    encoders.push((index.clone(), encoder));
+                }
+                let mut stream = representation.stream.clone();
+                if shared.is_shared(&stream) {
+                    let media_type = representation
+                        .encoder
+                        .as_ref()
+                        .and_then(Encoder::media_type);
+                    let split_filter = match media_type {
+                        Some(MediaType::Video) => "split",
+                        Some(MediaType::Audio) => "asplit",
+                        None => {
+                            return Err(format!(
+                                "DASH representation #{} shares a filtered stream; \
+                                 give it an audio or video encoder to split it",
+                                index
+                            ))
+                        }
+                    };
+                    stream = shared.take(&stream, split_filter);
+                }
+                streams.push(stream);
+                indices.push(index);
+            }
+            adaptation_sets.push(format!("id={},streams={}", id, indices.join(",")));
+        }
+
+        let mut kwargs: Kwargs = crate::kwargs! {"f" => "dash"};
+        if let Some(seg_duration) = self.seg_duration {
+            kwargs.push(("seg_duration".to_owned(), seg_duration.format_seconds()));
+        }
+        if let Some(use_template) = self.use_template {
+            kwargs.push(("use_template".to_owned(), (use_template as u8).to_string()));
+        }
+        if let Some(use_timeline) = self.use_timeline {
+            kwargs.push(("use_timeline".to_owned(), (use_timeline as u8).to_string()));
+        }
+        if let Some(template) = &self.init_seg_name {
+            kwargs.push(("init_seg_name".to_owned(), template.clone()));
+        }
+        if let Some(template) = &self.media_seg_name {
+            kwargs.push(("media_seg_name".to_owned(), template.clone()));
+        }
+        kwargs.push(("adaptation_sets".to_owned(), adaptation_sets.join(" ")));
+        kwargs.extend(self.kwargs.iter().cloned());
+
+        let mut node = output_node(streams, &self.filename, kwargs);
+        for (specifier, encoder) in encoders {
+            node = node.encoder(&specifier, encoder.clone())?;
+        }
+        Ok(node)
+    }
+
+    pub fn stream(&self) -> Result<OutputStream, String> {
+        Ok(self.node()?.stream("", None))
+    }
+}
@@ -0,0 +1,471 @@
+use crate::catalog::MediaType;
+use crate::daq::Kwargs;
+use crate::utils::escape_chars;
+
+/*
+Typed encoder configurations.
+
+    Each struct lists the options we set for one encoder; ``Encoder::get_args``
+    renders them as output kwargs for a stream specifier, so ``X264`` for stream
+    ``v`` becomes ``-c:v libx264 -crf:v 23 -preset:v slow``.  Rate control modes
+    that ffmpeg would silently resolve one way or another (``crf`` together with a
+    target bitrate for x264, ``lossless`` together with ``crf`` for VP9, ...) are
+    rejected instead.
+
+        output_node(&input, "out.mp4", kwargs! {})
+            .encoder("v", X264 { crf: Some(20.0), ..Default::default() })?
+            .encoder("a", Aac { bitrate: Some(128_000), ..Default::default() })?
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Preset {
+    Ultrafast,
+    Superfast,
+    Veryfast,
+    Faster,
+    Fast,
+    Medium,
+    Slow,
+    Slower,
+    Veryslow,
+    Placebo,
+}
+
+impl Preset {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Preset::Ultrafast => "ultrafast",
+            Preset::Superfast => "superfast",
+            Preset::Veryfast => "veryfast",
+            Preset::Faster => "faster",
+            Preset::Fast => "fast",
+            Preset::Medium => "medium",
+            Preset::Slow => "slow",
+            Preset::Slower => "slower",
+            Preset::Veryslow => "veryslow",
+            Preset::Placebo => "placebo",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Deadline {
+    Best,
+    Good,
+    Realtime,
+}
+
+impl Deadline {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Deadline::Best => "best",
+            Deadline::Good => "good",
+            Deadline::Realtime => "realtime",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OpusVbr {
+    Off,
+    On,
+    Constrained,
+}
+
+impl OpusVbr {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OpusVbr::Off => "off",
+            OpusVbr::On => "on",
+            OpusVbr::Constrained => "constrained",
+        }
+    }
+}
+
+// Bitrates are in bits per second.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct X264 {
+    pub crf: Option<f64>,
+    pub qp: Option<u32>,
+    pub bitrate: Option<u64>,
+    pub maxrate: Option<u64>,
+    pub bufsize: Option<u64>,
+    pub preset: Option<Preset>,
+    pub tune: Option<String>,
+    pub profile: Option<String>,
+    pub level: Option<String>,
+    pub x264_params: Kwargs,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct X265 {
+    pub crf: Option<f64>,
+    pub qp: Option<u32>,
+    pub bitrate: Option<u64>,
+    pub maxrate: Option<u64>,
+    pub bufsize: Option<u64>,
+    pub preset: Option<Preset>,
+    pub tune: Option<String>,
+    pub profile: Option<String>,
+    pub x265_params: Kwargs,
+}
+
+// `crf` alone is constant quality (rendered with `-b:v 0`), `crf` with `bitrate`
+// is constrained quality and `bitrate` alone is VBR.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct LibVpxVp9 {
+    pub crf: Option<u32>,
+    pub bitrate: Option<u64>,
+    pub lossless: bool,
+    pub deadline: Option<Deadline>,
+    pub cpu_used: Option<i32>,
+    pub row_mt: bool,
+}
+
+// Rate control works like `LibVpxVp9`.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct LibAom {
+    pub crf: Option<u32>,
+    pub bitrate: Option<u64>,
+    pub cpu_used: Option<u32>,
+    pub row_mt: bool,
+}
+
+// The native AAC encoder; `quality` is its experimental VBR mode (`-q:a`).
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Aac {
+    pub bitrate: Option<u64>,
+    pub quality: Option<f64>,
+    pub profile: Option<String>,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct LibOpus {
+    pub bitrate: Option<u64>,
+    pub vbr: Option<OpusVbr>,
+    pub compression_level: Option<u32>,
+    pub application: Option<String>,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Flac {
+    pub compression_level: Option<u32>,
+}
+
+// Stream copy, no re-encoding.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Copy;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Encoder {
+    X264(X264),
+    X265(X265),
+    LibVpxVp9(LibVpxVp9),
+    LibAom(LibAom),
+    Aac(Aac),
+    LibOpus(LibOpus),
+    Flac(Flac),
+    Copy,
+}
+
+macro_rules! impl_encoder_from {
+    ($($config:ident),*) => {
+        $(
+            impl From<$config> for Encoder {
+                fn from(config: $config) -> Self {
+                    Encoder::$config(config)
+                }
+            }
+        )*
+    };
+}
+
+impl_encoder_from!(X264, X265, LibVpxVp9, LibAom, Aac, LibOpus, Flac);
+
+impl From<Copy> for Encoder {
+    fn from(_: Copy) -> Self {
+        Encoder::Copy
+    }
+}
+
+impl Encoder {
+    pub fn codec(&self) -> &'static str {
+        match self {
+            Encoder::X264(_) => "libx264",
+            Encoder::X265(_) => "libx265",
+            Encoder::LibVpxVp9(_) => "libvpx-vp9",
+            Encoder::LibAom(_) => "libaom-av1",
+            Encoder::Aac(_) => "aac",
+            Encoder::LibOpus(_) => "libopus",
+            Encoder::Flac(_) => "flac",
+            Encoder::Copy => "copy",
+        }
+    }
+
+    // The type of stream the encoder takes; `None` for `Copy`, which takes any.
+    pub fn media_type(&self) -> Option<MediaType> {
+        match self {
+            Encoder::X264(_) | Encoder::X265(_) | Encoder::LibVpxVp9(_) | Encoder::LibAom(_) => {
+                Some(MediaType::Video)
+            }
+            Encoder::Aac(_) | Encoder::LibOpus(_) | Encoder::Flac(_) => Some(MediaType::Audio),
+            Encoder::Copy => None,
+        }
+    }
+
+    // Keys of every option an encoder config may set for `stream_specifier`, so a
+    // new config can clear what the previous one set.
+    pub fn option_keys(stream_specifier: &str) -> Vec<String> {
+        OPTION_KEYS
+            .iter()
+            .map(|key| stream_key(key, stream_specifier))
+            .collect()
+    }
+
+    // Output kwargs for the streams matched by `stream_specifier` (e.g. `v`, `a:1`,
+    // or `""` for all streams), or an error for conflicting or out of range options.
+    pub fn get_args(&self, stream_specifier: &str) -> Result<Kwargs, String> {
+        let mut args = EncoderArgs {
+            stream_specifier,
+            kwargs: Kwargs::new(),
+        };
+        args.push("c", self.codec());
+        match self {
+            Encoder::X264(config) => {
+                exclusive(
+                    "libx264",
+                    &[
+                        ("crf", config.crf.is_some()),
+                        ("qp", config.qp.is_some()),
+                        ("bitrate", config.bitrate.is_some()),
+                    ],
+                )?;
+                check_range("libx264", "crf", config.crf, 0.0, 51.0)?;
+                check_range("libx264", "qp", config.qp, 0, 69)?;
+                args.push_opt("crf", config.crf);
+                args.push_opt("qp", config.qp);
+                args.push_bitrate("b", config.bitrate);
+                args.push_bitrate("maxrate", config.maxrate);
+                args.push_bitrate("bufsize", config.bufsize);
+                args.push_opt("preset", config.preset.map(|preset| preset.as_str()));
+                args.push_opt("tune", config.tune.as_ref());
+                args.push_opt("profile", config.profile.as_ref());
+                args.push_opt("level", config.level.as_ref());
+                args.push_params("x264-params", &config.x264_params);
+            }
+            Encoder::X265(config) => {
+                exclusive(
+                    "libx265",
+                    &[
+                        ("crf", config.crf.is_some()),
+                        ("qp", config.qp.is_some()),
+                        ("bitrate", config.bitrate.is_some()),
+                    ],
+                )?;
+                check_range("libx265", "crf", config.crf, 0.0, 51.0)?;
+                check_range("libx265", "qp", config.qp, 0, 51)?;
+                args.push_opt("crf", config.crf);
+                args.push_opt("qp", config.qp);
+                args.push_bitrate("b", config.bitrate);
+                args.push_bitrate("maxrate", config.maxrate);
+                args.push_bitrate("bufsize", config.bufsize);
+                args.push_opt("preset", config.preset.map(|preset| preset.as_str()));
+                args.push_opt("tune", config.tune.as_ref());
+                args.push_opt("profile", config.profile.as_ref());
+                args.push_params("x265-params", &config.x265_params);
+            }
+            Encoder::LibVpxVp9(config) => {
+                exclusive(
+                    "libvpx-vp9",
+                    &[("lossless", config.lossless), ("crf", config.crf.is_some())],
+                )?;
+                exclusive(
+                    "libvpx-vp9",
+                    &[
+                        ("lossless", config.lossless),
+                        ("bitrate", config.bitrate.is_some()),
+                    ],
+                )?;
+                check_range("libvpx-vp9", "crf", config.crf, 0, 63)?;
+                check_range("libvpx-vp9", "cpu_used", config.cpu_used, -8, 8)?;
+                args.push_quality(config.crf, config.bitrate);
+                args.push_flag("lossless", config.lossless);
+                args.push_opt(
+                    "deadline",
+                    config.deadline.map(|deadline| deadline.as_str()),
+                );
+                args.push_opt("cpu-used", config.cpu_used);
+                args.push_flag("row-mt", config.row_mt);
+            }
+            Encoder::LibAom(config) => {
+                check_range("libaom-av1", "crf", config.crf, 0, 63)?;
+                check_range("libaom-av1", "cpu_used", config.cpu_used, 0, 8)?;
+                args.push_quality(config.crf, config.bitrate);
+                args.push_opt("cpu-used", config.cpu_used);
+                args.push_flag("row-mt", config.row_mt);
+            }
+            Encoder::Aac(config) => {
+                exclusive(
+                    "aac",
+                    &[
+                        ("bitrate", config.bitrate.is_some()),
+                        ("quality", config.quality.is_some()),
+                    ],
+                )?;
+                check_range("aac", "quality", config.quality, 0.1, 2.0)?;
+                args.push_bitrate("b", config.bitrate);
+                args.push_opt("q", config.quality);
+                args.push_opt("profile", config.profile.as_ref());
+            }
+            Encoder::LibOpus(config) => {
+                check_range("libopus", "bitrate", config.bitrate, 500, 512_000)?;
+                check_range(
+                    "libopus",
+                    "compression_level",
+                    config.compression_level,
+                    0,
+                    10,
+                )?;
+                args.push_bitrate("b", config.bitrate);
+                args.push_opt("vbr", config.vbr.map(|vbr| vbr.as_str()));
+                args.push_opt("compression_level", config.compression_level);
+                args.push_opt("application", config.application.as_ref());
+            }
+            Encoder::Flac(config) => {
+                check_range("flac", "compression_level", config.compression_level, 0, 12)?;
+                args.push_opt("compression_level", config.compression_level);
+            }
+            Encoder::Copy => {}
+        }
+        Ok(args.kwargs)
+    }
+}
+
+// Everything `Encoder::get_args` may set, before the stream specifier is added.
+const OPTION_KEYS: &[&str] = &[
+    "c",
+    "crf",
+    "qp",
+    "b",
+    "maxrate",
+    "bufsize",
+    "preset",
+    "tune",
+    "profile",
+    "level",
+    "x264-params",
+    "x265-params",
+    "lossless",
+    "deadline",
+    "cpu-used",
+    "row-mt",
+    "q",
+    "vbr",
+    "compression_level",
+    "application",
+];
+
+fn stream_key(key: &str, stream_specifier: &str) -> String {
+    if stream_specifier.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{}:{}", key, stream_specifier)
+    }
+}
+
+struct EncoderArgs<'a> {
+    stream_specifier: &'a str,
+    kwargs: Kwargs,
+}
+
+impl EncoderArgs<'_> {
+    fn push(&mut self, key: &str, value: impl ToString) {
+        self.kwargs
+            .push((stream_key(key, self.stream_specifier), value.to_string()));
+    }
+
+    fn push_opt(&mut self, key: &str, value: Option<impl ToString>) {
+        if let Some(value) = value {
+            self.push(key, value);
+        }
+    }
+
+    fn push_flag(&mut self, key: &str, value: bool) {
+        if value {
+            self.push(key, 1);
+        }
+    }
+
+    fn push_bitrate(&mut self, key: &str, bitrate: Option<u64>) {
+        self.push_opt(key, bitrate.map(format_bitrate));
+    }
+
+    // `key=value:key=value`, as parsed by `av_dict_parse_string`, which reads
+    // backslash escapes and single quotes.
+    fn push_params(&mut self, key: &str, params: &Kwargs) {
+        if !params.is_empty() {
+            let params: Vec<String> = params
+                .iter()
+                .map(|(key, value)| {
+                    format!(
+                        "{}={}",
+                        escape_chars(key, "':="),
+                        escape_chars(value, "':=")
+                    )
+                })
+                .collect();
+            self.push(key, params.join(":"));
+        }
+    }
+
+    // libvpx and libaom only run in constant quality mode when `crf` comes with a
+    // zero target bitrate.
+    fn push_quality(&mut self, crf: Option<u32>, bitrate: Option<u64>) {
+        self.push_opt("crf", crf);
+        match (crf, bitrate) {
+            (Some(_), None) => self.push("b", 0),
+            (_, bitrate) => self.push_bitrate("b", bitrate),
+        }
+    }
+}
+
+// `128000` as `128k`; ffmpeg reads both.
+fn format_bitrate(bitrate: u64) -> String {
+    if bitrate != 0 && bitrate.is_multiple_of(1000) {
+        format!("{}k", bitrate / 1000)
+    } else {
+        bitrate.to_string()
+    }
+}
+
+fn exclusive(codec: &str, modes: &[(&str, bool)]) -> Result<(), String> {
+    let set: Vec<&str> = modes
+        .iter()
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| *name)
+        .collect();
+    if set.len() > 1 {
+        return Err(format!(
+            "{}: rate control options {} are mutually exclusive",
+            codec,
+            set.join(" and ")
+        ));
+    }
+    Ok(())
+}
+
+fn check_range<T: PartialOrd + std::fmt::Display>(
+    codec: &str,
+    option: &str,
+    value: Option<T>,
+    min: T,
+    max: T,
+) -> Result<(), String> {
+    match value {
+        Some(value) if value < min || value > max => Err(format!(
+            "{}: value {} for option `{}` is out of range [{}, {}]",
+            codec, value, option, min, max
+        )),
+        _ => Ok(()),
+    }
+}
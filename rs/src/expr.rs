@@ -0,0 +1,289 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/*
+Expressions in ffmpeg's expression language (``libavutil/eval``).
+
+    Options such as overlay's ``x``, ``setpts``, ``select`` or ``geq`` take an
+    expression evaluated per frame.  ``Expr`` builds them with Rust operators and
+    renders the text ffmpeg expects, adding parentheses only where precedence needs
+    them:
+
+        (T - 2).gte(0) * OVERLAY_W  =>  gte(t-2,0)*overlay_w
+
+    Rendered expressions may contain `,` and `:`; ``FilterNode::get_filter`` escapes
+    them when the expression is used as a filter option.
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+impl BinaryOp {
+    fn symbol(&self) -> char {
+        match self {
+            BinaryOp::Add => '+',
+            BinaryOp::Sub => '-',
+            BinaryOp::Mul => '*',
+            BinaryOp::Div => '/',
+            BinaryOp::Pow => '^',
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Add | BinaryOp::Sub => 1,
+            BinaryOp::Mul | BinaryOp::Div => 2,
+            BinaryOp::Pow => 3,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Expr {
+    Number(f64),
+    Var(Cow<'static, str>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Cow<'static, str>, Vec<Expr>),
+}
+
+pub const fn var(name: &'static str) -> Expr {
+    Expr::Var(Cow::Borrowed(name))
+}
+
+// Timestamp in seconds.
+pub const T: Expr = var("t");
+// Sequential frame number, starting from 0.
+pub const N: Expr = var("n");
+// Presentation timestamp in time base units.
+pub const PTS: Expr = var("PTS");
+// Time base of the input timestamps.
+pub const TB: Expr = var("TB");
+// PTS of the first frame.
+pub const STARTPTS: Expr = var("STARTPTS");
+// Frame width and height, as seen by the filter (e.g. the overlaid frame for overlay).
+pub const W: Expr = var("w");
+pub const H: Expr = var("h");
+// Input and output dimensions of scale, crop and pad.
+pub const IW: Expr = var("iw");
+pub const IH: Expr = var("ih");
+pub const OW: Expr = var("ow");
+pub const OH: Expr = var("oh");
+// Main and overlaid frame dimensions of overlay.
+pub const MAIN_W: Expr = var("main_w");
+pub const MAIN_H: Expr = var("main_h");
+pub const OVERLAY_W: Expr = var("overlay_w");
+pub const OVERLAY_H: Expr = var("overlay_h");
+// Pixel coordinates, e.g. for geq.
+pub const X: Expr = var("X");
+pub const Y: Expr = var("Y");
+// Scene change score of select.
+pub const SCENE: Expr = var("scene");
+pub const PI: Expr = var("PI");
+
+impl Expr {
+    pub fn var(name: impl Into<String>) -> Expr {
+        Expr::Var(Cow::Owned(name.into()))
+    }
+
+    pub fn call(name: impl Into<String>, args: Vec<Expr>) -> Expr {
+        Expr::Call(Cow::Owned(name.into()), args)
+    }
+
+    fn function<const N: usize>(name: &'static str, args: [Expr; N]) -> Expr {
+        Expr::Call(Cow::Borrowed(name), args.into())
+    }
+
+    pub fn pow(self, exponent: impl Into<Expr>) -> Expr {
+        Expr::Binary(BinaryOp::Pow, Box::new(self), Box::new(exponent.into()))
+    }
+
+    pub fn gt(self, other: impl Into<Expr>) -> Expr {
+        Self::function("gt", [self, other.into()])
+    }
+
+    pub fn gte(self, other: impl Into<Expr>) -> Expr {
+        Self::function("gte", [self, other.into()])
+    }
+
+    pub fn lt(self, other: impl Into<Expr>) -> Expr {
+        Self::function("lt", [self, other.into()])
+    }
+
+    pub fn lte(self, other: impl Into<Expr>) -> Expr {
+        Self::function("lte", [self, other.into()])
+    }
+
+    // `eq(x,y)`; named `equals` so it does not shadow `PartialEq::eq`.
+    pub fn equals(self, other: impl Into<Expr>) -> Expr {
+        Self::function("eq", [self, other.into()])
+    }
+
+    fn render(&self, f: &mut fmt::Formatter<'_>, parent: u8, wrap_equal: bool) -> fmt::Result {
+        match self {
+            Expr::Number(value) if *value < 0.0 && parent > 0 => write!(f, "({})", value),
+            Expr::Number(value) => write!(f, "{}", value),
+            Expr::Var(name) => f.write_str(name),
+            // ffmpeg takes one sign per factor, rejecting `t--t`, and applies it
+            // after `^`, so `-x^2` is `-(x^2)`.
+            Expr::Neg(expr) if parent > 0 => {
+                f.write_str("(-")?;
+                expr.render(f, u8::MAX, false)?;
+                f.write_str(")")
+            }
+            Expr::Neg(expr) => {
+                f.write_str("-")?;
+                expr.render(f, u8::MAX, false)
+            }
+            Expr::Binary(op, left, right_expr) => {
+                let precedence = op.precedence();
+                let wrap = precedence < parent || (precedence == parent && wrap_equal);
+                if wrap {
+                    f.write_str("(")?;
+                }
+                left.render(f, precedence, false)?;
+                write!(f, "{}", op.symbol())?;
+                // Only + and * are associative; ffmpeg evaluates ^ left to right.
+                let associative = matches!(op, BinaryOp::Add | BinaryOp::Mul);
+                right_expr.render(f, precedence, !associative)?;
+                if wrap {
+                    f.write_str(")")?;
+                }
+                Ok(())
+            }
+            Expr::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    arg.render(f, 0, false)?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render(f, 0, false)
+    }
+}
+
+pub fn if_then(condition: impl Into<Expr>, then: impl Into<Expr>) -> Expr {
+    Expr::function("if", [condition.into(), then.into()])
+}
+
+pub fn if_then_else(
+    condition: impl Into<Expr>,
+    then: impl Into<Expr>,
+    otherwise: impl Into<Expr>,
+) -> Expr {
+    Expr::function("if", [condition.into(), then.into(), otherwise.into()])
+}
+
+pub fn between(x: impl Into<Expr>, min: impl Into<Expr>, max: impl Into<Expr>) -> Expr {
+    Expr::function("between", [x.into(), min.into(), max.into()])
+}
+
+pub fn clip(x: impl Into<Expr>, min: impl Into<Expr>, max: impl Into<Expr>) -> Expr {
+    Expr::function("clip", [x.into(), min.into(), max.into()])
+}
+
+pub fn min(x: impl Into<Expr>, y: impl Into<Expr>) -> Expr {
+    Expr::function("min", [x.into(), y.into()])
+}
+
+pub fn max(x: impl Into<Expr>, y: impl Into<Expr>) -> Expr {
+    Expr::function("max", [x.into(), y.into()])
+}
+
+pub fn abs(x: impl Into<Expr>) -> Expr {
+    Expr::function("abs", [x.into()])
+}
+
+pub fn modulo(x: impl Into<Expr>, y: impl Into<Expr>) -> Expr {
+    Expr::function("mod", [x.into(), y.into()])
+}
+
+pub fn floor(x: impl Into<Expr>) -> Expr {
+    Expr::function("floor", [x.into()])
+}
+
+pub fn not(x: impl Into<Expr>) -> Expr {
+    Expr::function("not", [x.into()])
+}
+
+// Stores `value` in internal variable `var` (0 to 9) and returns it.
+pub fn st(var: u8, value: impl Into<Expr>) -> Expr {
+    Expr::function("st", [Expr::from(var), value.into()])
+}
+
+// Loads internal variable `var` (0 to 9).
+pub fn ld(var: u8) -> Expr {
+    Expr::function("ld", [Expr::from(var)])
+}
+
+macro_rules! impl_from_number {
+    ($($number:ty),*) => {
+        $(
+            impl From<$number> for Expr {
+                fn from(value: $number) -> Self {
+                    Expr::Number(value as f64)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_number!(u8, i32, i64, u32, u64, f32, f64);
+
+impl Neg for Expr {
+    type Output = Expr;
+
+    fn neg(self) -> Expr {
+        Expr::Neg(Box::new(self))
+    }
+}
+
+macro_rules! impl_binary_op {
+    ($($trait:ident :: $method:ident => $op:expr),*) => {
+        $(
+            impl<T: Into<Expr>> $trait<T> for Expr {
+                type Output = Expr;
+
+                fn $method(self, other: T) -> Expr {
+                    Expr::Binary($op, Box::new(self), Box::new(other.into()))
+                }
+            }
+
+            impl_binary_op!(@number $trait :: $method => $op; i32, f64);
+        )*
+    };
+    (@number $trait:ident :: $method:ident => $op:expr; $($number:ty),*) => {
+        $(
+            impl $trait<Expr> for $number {
+                type Output = Expr;
+
+                fn $method(self, other: Expr) -> Expr {
+                    Expr::Binary($op, Box::new(self.into()), Box::new(other))
+                }
+            }
+        )*
+    };
+}
+
+impl_binary_op!(
+    Add::add => BinaryOp::Add,
+    Sub::sub => BinaryOp::Sub,
+    Mul::mul => BinaryOp::Mul,
+    Div::div => BinaryOp::Div
+);
@@ -1,178 +1,550 @@
-use crate::daq::{
-  DagEdge, DagNode, get_outgoing_edges, get_stream_spec_node, KwargReprNode, OutgoingEdgeMap,
-  topo_sort,
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use std::process::Command;
+use std::rc::Rc;
+
+use crate::catalog::{FilterCatalog, MediaType};
+use crate::daq::{topo_sort, DagEdge, DagNode, Kwargs, Label, OutgoingEdgeMap, OutgoingEdgeMaps};
+use crate::node::{
+    get_stream_spec_node, FilterNode, FilterableStream, InputNode, Node, NodeType, OutputNode,
+    OutputStream, StreamSpec, METADATA_LABEL,
 };
-use crate::node::{FilterNode, InputNode, OutputNode, Stream};
-use crate::utils::filter_undefined;
+use crate::pipe::{describe_fd, pipe_fd, PipeEnd};
+use crate::utils::{shell_quote, TempFile};
+use crate::validate::{validate_filters, Diagnostic, ValidationError};
+
+type StreamNameMap = HashMap<String, String>;
 
-pub fn input(filename: &str, kwargs: &mut std::collections::HashMap<String, String>) -> Stream {
-  kwargs.insert(String::from("filename"), String::from(filename));
-  InputNode::new(String::from("input"), vec![], kwargs.clone()).stream()
+pub fn input(filename: &str, kwargs: Kwargs) -> FilterableStream {
+    input_node(filename, kwargs).stream("", None)
 }
 
-pub fn output(stream: Stream, filename: &str, kwargs: &mut std::collections::HashMap<String, String>) -> Stream {
-  kwargs.insert(String::from("filename"), String::from(filename));
-  OutputNode::new(stream, String::from("output"), kwargs.clone()).stream()
+// The node behind `input`, for typed options such as `InputNode::seek`.
+pub fn input_node(filename: &str, mut kwargs: Kwargs) -> InputNode {
+    kwargs.push(("filename".to_owned(), filename.to_owned()));
+    InputNode::new("input", kwargs)
 }
 
-pub fn compile(stream_spec: &Stream, cmd: &str, overwrite_output: bool) -> Vec<String> {
-  let mut cmds: Vec<String> = vec![String::from(cmd)];
-  cmds.append(&mut get_args(stream_spec, overwrite_output));
-  if overwrite_output {
-      cmds.push(String::from("-y"));
-  }
-  cmds
+pub fn output(stream_spec: impl Into<StreamSpec>, filename: &str, kwargs: Kwargs) -> OutputStream {
+    output_node(stream_spec, filename, kwargs).stream("", None)
 }
 
-pub fn filter(stream_spec: &Stream, filter_name: &str, kwargs: &mut std::collections::HashMap<String, String>) -> Stream {
-  FilterNode::new(stream_spec.clone(), String::from(filter_name), kwargs.clone()).stream()
+// The node behind `output`, for typed options such as `OutputNode::seek`.
+pub fn output_node(
+    stream_spec: impl Into<StreamSpec>,
+    filename: &str,
+    mut kwargs: Kwargs,
+) -> OutputNode {
+    kwargs.push(("filename".to_owned(), filename.to_owned()));
+    OutputNode::new(stream_spec.into(), "output", kwargs)
 }
 
-fn get_args(stream_spec: &Stream, overwrite_output: bool) -> Vec<String> {
-  let nodes = get_stream_spec_node(stream_spec);
-  let mut args: Vec<String> = vec![];
+pub fn compile(
+    stream_spec: impl Into<StreamSpec>,
+    cmd: &str,
+    overwrite_output: bool,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut cmds = vec![cmd.to_owned(), "-hide_banner".to_owned()];
+    cmds.extend(
+        get_args(stream_spec)?
+            .into_iter()
+            .filter(|spec| !spec.is_empty()),
+    );
+    if overwrite_output {
+        cmds.push("-y".to_owned());
+    }
+    Ok(cmds)
+}
 
-  let (sorted_nodes, outgoing_edge_maps) = topo_sort(&nodes);
-  let input_nodes: Vec<&DagNode> = sorted_nodes.iter().filter(|node| node.is::<InputNode>()).collect();
-  let output_nodes: Vec<&DagNode> = sorted_nodes.iter().filter(|node| node.is::<OutputNode>()).collect();
-  let filter_nodes: Vec<&DagNode> = sorted_nodes.iter().filter(|node| node.is::<FilterNode>()).collect();
+// Arguments of a compiled command, together with the generated files (such as
+// concat lists) they refer to.  The files are removed when this is dropped.
+pub struct CompiledCommand {
+    pub args: Vec<String>,
+    temp_files: Vec<Rc<TempFile>>,
+    pipes: Vec<Rc<PipeEnd>>,
+}
 
-  for node in input_nodes {
-      if let Some(kwarg_repr_node) = node.downcast_ref::<KwargReprNode>() {
-          let input_args = get_input_args(kwarg_repr_node);
-          args.extend(input_args);
-      }
-  }
+impl CompiledCommand {
+    pub fn temp_files(&self) -> Vec<&Path> {
+        self.temp_files.iter().map(|file| file.path()).collect()
+    }
 
-  let mut stream_name_map: std::collections::HashMap<String, String> = std::collections::HashMap::new();
-  for (i, input_node) in input_nodes.iter().enumerate() {
-      let key = format!("{}_{}", input_node.hash(), "");
-      stream_name_map.insert(key, format!("s{}", i));
-  }
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.args[0]);
+        command.args(&self.args[1..]);
+        command
+    }
 
-  let filter_args = get_filter_args(&filter_nodes, &outgoing_edge_maps, &mut stream_name_map);
-  if !filter_args.is_empty() {
-      args.push(String::from("-filter_complex"));
-      args.push(filter_args);
-  }
+    // Readers and writers attached to the graph's `pipe:N` inputs and outputs.
+    pub(crate) fn pipes(&self) -> &[Rc<PipeEnd>] {
+        &self.pipes
+    }
+}
 
-  for node in output_nodes {
-      if let Some(kwarg_repr_node) = node.downcast_ref::<KwargReprNode>() {
-          let output_args = get_output_args(kwarg_repr_node, &stream_name_map);
-          args.extend(output_args);
-      }
-  }
+// `args` as one shell command line, quoted for POSIX shells.
+pub fn shell_command(args: &[String]) -> String {
+    let quoted: Vec<String> = args.iter().map(|arg| shell_quote(arg)).collect();
+    quoted.join(" ")
+}
+
+// Like `compile`, but keeps the graph's generated files alive for as long as the
+// command is.
+pub fn compile_command(
+    stream_spec: impl Into<StreamSpec>,
+    cmd: &str,
+    overwrite_output: bool,
+) -> Result<CompiledCommand, Box<dyn Error>> {
+    let stream_spec = stream_spec.into();
+    let (sorted_nodes, _) = topo_sort(&get_stream_spec_node(stream_spec.clone()))?;
+    Ok(CompiledCommand {
+        args: compile(stream_spec, cmd, overwrite_output)?,
+        temp_files: sorted_nodes
+            .iter()
+            .filter_map(|node| node.temp_file().cloned())
+            .collect(),
+        pipes: sorted_nodes
+            .iter()
+            .filter_map(|node| node.pipe().cloned())
+            .collect(),
+    })
+}
+
+// Like `compile`, but first validates every filter node against `catalog` and fails
+// with a `ValidationError` listing all diagnostics.
+pub fn compile_validated(
+    stream_spec: impl Into<StreamSpec>,
+    cmd: &str,
+    overwrite_output: bool,
+    catalog: &FilterCatalog,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let stream_spec = stream_spec.into();
+    let diagnostics = validate(stream_spec.clone(), catalog)?;
+    if !diagnostics.is_empty() {
+        return Err(Box::new(ValidationError { diagnostics }));
+    }
+    compile(stream_spec, cmd, overwrite_output)
+}
 
-  if overwrite_output && output_nodes.is_empty() {
-      args.push(String::from("-y"));
-  }
+pub fn validate(
+    stream_spec: impl Into<StreamSpec>,
+    catalog: &FilterCatalog,
+) -> Result<Vec<Diagnostic>, Box<dyn Error>> {
+    let nodes = get_stream_spec_node(stream_spec.into());
+    let (sorted_nodes, outgoing_edge_maps) = topo_sort(&nodes)?;
+    let filter_nodes: Vec<FilterNode> = sorted_nodes.iter().filter_map(Node::as_filter).collect();
+    Ok(validate_filters(
+        &filter_nodes,
+        &outgoing_edge_maps,
+        catalog,
+    ))
+}
 
-  args
+pub fn filter(
+    stream_spec: impl Into<StreamSpec>,
+    filter_name: &str,
+    kwargs: Kwargs,
+) -> FilterableStream {
+    filter_multi_output(stream_spec, filter_name, kwargs).stream("", None)
 }
 
-fn allocate_filter_stream_name(filter_nodes: &[FilterNode], outgoing_edge_maps: &HashMap<DagNode, OutgoingEdgeMap>, stream_name_map: &mut HashMap<String, String>) {
+// Filters with several outputs (e.g. `split`) are consumed through `FilterNode::stream`.
+pub fn filter_multi_output(
+    stream_spec: impl Into<StreamSpec>,
+    filter_name: &str,
+    kwargs: Kwargs,
+) -> FilterNode {
+    FilterNode::new(stream_spec.into(), filter_name, kwargs)
+}
+
+// Joins `(video, audio)` segments with the `concat` filter, interleaving its
+// inputs as `[v0][a0][v1][a1]...`, and returns the joined video and audio.
+pub fn concat(
+    segments: &[(FilterableStream, FilterableStream)],
+) -> Result<(FilterableStream, FilterableStream), Box<dyn Error>> {
+    if segments.is_empty() {
+        return Err("Concat needs at least one segment".into());
+    }
+    let streams: Vec<FilterableStream> = segments
+        .iter()
+        .flat_map(|(video, audio)| [video.clone(), audio.clone()])
+        .collect();
+    let node = filter_multi_output(
+        streams,
+        "concat",
+        crate::kwargs! {"n" => segments.len(), "v" => 1, "a" => 1},
+    );
+    Ok((node.stream("0", None), node.stream("1", None)))
+}
+
+// Like `concat`, but first scales and pads every video to `width`x`height` and
+// resamples every audio to `sample_rate`, which `concat` requires to match.
+pub fn concat_normalized(
+    segments: &[(FilterableStream, FilterableStream)],
+    width: u32,
+    height: u32,
+    sample_rate: u32,
+) -> Result<(FilterableStream, FilterableStream), Box<dyn Error>> {
+    let normalized: Vec<(FilterableStream, FilterableStream)> = segments
+        .iter()
+        .map(|(video, audio)| {
+            let video = video
+                .filter(
+                    "scale",
+                    crate::kwargs! {
+                        "w" => width,
+                        "h" => height,
+                        "force_original_aspect_ratio" => "decrease",
+                    },
+                )
+                .filter(
+                    "pad",
+                    crate::kwargs! {
+                        "w" => width,
+                        "h" => height,
+                        "x" => "(ow-iw)/2",
+                        "y" => "(oh-ih)/2",
+                    },
+                )
+                .filter("setsar", crate::kwargs! {"r" => 1});
+            let audio = audio.filter("aresample", crate::kwargs! {"osr" => sample_rate});
+            (video, audio)
+        })
+        .collect();
+    concat(&normalized)
+}
+
+pub fn get_args(stream_spec: impl Into<StreamSpec>) -> Result<Vec<String>, Box<dyn Error>> {
+    let nodes = get_stream_spec_node(stream_spec.into());
+    let mut args: Vec<String> = Vec::new();
+
+    let (sorted_nodes, outgoing_edge_maps) = topo_sort(&nodes)?;
+    let input_nodes: Vec<&Node> = of_type(&sorted_nodes, NodeType::Input);
+    let output_nodes: Vec<&Node> = of_type(&sorted_nodes, NodeType::Output);
+    let filter_nodes: Vec<FilterNode> = sorted_nodes.iter().filter_map(Node::as_filter).collect();
+    check_pipes(&input_nodes, &output_nodes)?;
+
+    for node in &input_nodes {
+        args.extend(get_input_args(node));
+    }
+
+    let mut stream_name_map = StreamNameMap::new();
+    for (i, node) in input_nodes.iter().enumerate() {
+        stream_name_map.insert(format!("{}_{}", node.hash(), ""), i.to_string());
+    }
+
+    let filter_args = get_filter_args(&filter_nodes, &outgoing_edge_maps, &mut stream_name_map)?;
+    if !filter_args.is_empty() {
+        args.push("-filter_complex".to_owned());
+        args.push(filter_args);
+    }
+
+    for node in &output_nodes {
+        args.extend(get_output_args(node, &stream_name_map));
+    }
+
+    Ok(args)
+}
+
+// Every pipe can only be read by one input or written by one output.
+fn check_pipes(input_nodes: &[&Node], output_nodes: &[&Node]) -> Result<(), Box<dyn Error>> {
+    let mut used: HashMap<u32, String> = HashMap::new();
+    let nodes = input_nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (format!("input #{}", i), true, node))
+        .chain(
+            output_nodes
+                .iter()
+                .enumerate()
+                .map(|(i, node)| (format!("output #{}", i), false, node)),
+        );
+    for (name, is_input, node) in nodes {
+        let Some(fd) = pipe_fd(&get_filename(node.kwargs()), is_input) else {
+            continue;
+        };
+        if let Some(other) = used.insert(fd, name.clone()) {
+            return Err(format!("Both {} and {} use {}", other, name, describe_fd(fd)).into());
+        }
+    }
+    Ok(())
+}
+
+fn of_type(nodes: &[Node], node_type: NodeType) -> Vec<&Node> {
+    nodes
+        .iter()
+        .filter(|node| node.node_type() == node_type)
+        .collect()
+}
+
+// Labels of a filter's output pads, keyed by node hash, in pad order.
+type OutputPadMap = HashMap<u64, Vec<OutputPad>>;
+
+struct OutputPad {
+    label: Label,
+    consumed: bool,
+}
+
+// Names every output pad of every filter in pad order, so `[s0][s1]` follow the
+// filter's pads whichever one is consumed first.  Pads nothing consumes still need
+// a name, and a sink when their media type is known, or ffmpeg would connect them
+// to the first output.
+fn allocate_filter_stream_name(
+    filter_nodes: &[FilterNode],
+    outgoing_edge_maps: &OutgoingEdgeMaps<Node>,
+    stream_name_map: &mut StreamNameMap,
+) -> Result<(OutputPadMap, Vec<String>), Box<dyn Error>> {
+    let mut output_pad_map = OutputPadMap::new();
+    let mut sinks = Vec::new();
     let mut stream_count = 0;
     for upstream_node in filter_nodes {
-        let outgoing_edge_map = outgoing_edge_maps.get(upstream_node).unwrap_or(&OutgoingEdgeMap::new());
-        for (key, value) in outgoing_edge_map.iter() {
-            let upstream_label = key;
-            let downstreams = value;
+        let Some(outgoing_edge_map) = outgoing_edge_maps.get(&upstream_node.node().hash()) else {
+            continue;
+        };
+        for (upstream_label, downstreams) in outgoing_edge_map {
             if downstreams.len() > 1 {
-                panic!("error1");
+                return Err(format!(
+                    "Encountered {} with multiple outgoing edges with same upstream label {:?}; a `split` filter is probably required",
+                    upstream_node.node().name(),
+                    upstream_label
+                )
+                .into());
             }
-            let name_map_key = format!("{}_{}", upstream_node.hash, upstream_label);
-            stream_name_map.insert(name_map_key, format!("s{}", stream_count));
+        }
+        let pad_types = output_pad_types(upstream_node);
+        let pads = get_output_pads(upstream_node, outgoing_edge_map, pad_types.as_deref())?;
+        for (i, pad) in pads.iter().enumerate() {
+            let stream_name = format!("s{}", stream_count);
             stream_count += 1;
+            if !pad.consumed {
+                if let Some(media_type) = pad_types.as_ref().and_then(|types| types.get(i)) {
+                    let sink = match media_type {
+                        MediaType::Video => "nullsink",
+                        MediaType::Audio => "anullsink",
+                    };
+                    sinks.push(format!("[{}]{}", stream_name, sink));
+                }
+            }
+            let name_map_key = format!("{}_{}", upstream_node.node().hash(), pad.label);
+            stream_name_map.insert(name_map_key, stream_name);
         }
+        output_pad_map.insert(upstream_node.node().hash(), pads);
     }
+    Ok((output_pad_map, sinks))
 }
 
-fn get_filter_spec(node: &FilterNode, outgoing_edge_map: &OutgoingEdgeMap, stream_name_map: &HashMap<String, String>) -> String {
-    let incoming_edges = node.get_incoming_edges();
-    let outgoing_edges = get_outgoing_edges(node, outgoing_edge_map);
-    let inputs: Vec<String> = incoming_edges.iter().map(|edge| {
-        format_input_stream_name(stream_name_map, edge)
-    }).collect();
-    let outputs: Vec<String> = outgoing_edges.iter().map(|edge| {
-        format_output_stream_name(stream_name_map, edge)
-    }).collect();
-    let filter_spec = format!("{}{}{}", inputs.join(""), node.get_filter(), outputs.join(""));
-    filter_spec
+// The consumed outputs of `node` ordered by pad, where `""` is the first pad and
+// `"N"` the pad with index N, with the unconsumed pads before the last consumed one
+// and, when `pad_types` lists them, after it.  Other labels keep their order.
+fn get_output_pads(
+    node: &FilterNode,
+    outgoing_edge_map: &OutgoingEdgeMap<Node>,
+    pad_types: Option<&[MediaType]>,
+) -> Result<Vec<OutputPad>, Box<dyn Error>> {
+    let labels: Vec<&Label> = outgoing_edge_map.iter().map(|(label, _)| label).collect();
+    let indices: Option<Vec<usize>> = labels
+        .iter()
+        .map(|label| match label.as_str() {
+            "" => Some(0),
+            label => label.parse().ok(),
+        })
+        .collect();
+    let Some(indices) = indices else {
+        return Ok(labels
+            .into_iter()
+            .map(|label| OutputPad {
+                label: label.clone(),
+                consumed: true,
+            })
+            .collect());
+    };
+    let pad_count = indices
+        .iter()
+        .map(|index| index + 1)
+        .chain(pad_types.map(<[MediaType]>::len))
+        .max()
+        .unwrap_or_default();
+    let mut pads = Vec::with_capacity(pad_count);
+    for index in 0..pad_count {
+        let mut consumed = labels
+            .iter()
+            .zip(&indices)
+            .filter(|(_, consumed_index)| **consumed_index == index);
+        pads.push(match (consumed.next(), consumed.next()) {
+            (Some((label, _)), None) => OutputPad {
+                label: (*label).clone(),
+                consumed: true,
+            },
+            (None, _) => OutputPad {
+                label: index.to_string(),
+                consumed: false,
+            },
+            (Some(_), Some(_)) => {
+                return Err(format!(
+                    "Encountered {} with output #{} consumed under two labels; a `split` filter is probably required",
+                    node.node().name(),
+                    index
+                )
+                .into())
+            }
+        });
+    }
+    Ok(pads)
 }
 
-fn get_filter_args(nodes: &[DagNode], outgoing_edge_maps: &HashMap<DagNode, OutgoingEdgeMap>, stream_name_map: &mut HashMap<String, String>) -> String {
-    let filter_nodes: Vec<&FilterNode> = nodes.iter().filter_map(|node| {
-        if let Some(filter_node) = node.downcast_ref::<FilterNode>() {
-            Some(filter_node)
-        } else {
-            None
+// Media types of the output pads of filters whose outputs depend on their options.
+fn output_pad_types(node: &FilterNode) -> Option<Vec<MediaType>> {
+    let count = |key: &str, default: usize| {
+        node.node()
+            .kwargs()
+            .iter()
+            .find(|(existing, _)| existing == key)
+            .map_or(Some(default), |(_, value)| value.parse().ok())
+    };
+    match node.node().name() {
+        "concat" => {
+            let mut types = vec![MediaType::Video; count("v", 1)?];
+            types.extend(vec![MediaType::Audio; count("a", 0)?]);
+            Some(types)
         }
-    }).collect();
-    allocate_filter_stream_name(&filter_nodes, outgoing_edge_maps, stream_name_map);
-    let filter_spec: Vec<String> = filter_nodes.iter().map(|node| {
-        let outgoing_edge_map = outgoing_edge_maps.get(node).unwrap_or(&OutgoingEdgeMap::new());
-        get_filter_spec(node, outgoing_edge_map, stream_name_map)
-    }).collect();
-    filter_spec.join(";")
-}
-
-fn format_input_stream_name(stream_name_map: &HashMap<String, String>, edge: &DagEdge, is_final_arg: bool) -> String {
-    let key = format!("{}_{}", edge.upstream_node.hash, edge.upstream_label);
-    let prefix = stream_name_map.get(&key).unwrap_or(&"".to_string()).clone();
-    let mut suffix = "".to_string();
-    if let Some(upstream_selector) = &edge.upstream_selector {
+        "split" => Some(vec![MediaType::Video; count("outputs", 2)?]),
+        "asplit" => Some(vec![MediaType::Audio; count("outputs", 2)?]),
+        _ => None,
+    }
+}
+
+fn get_filter_spec(
+    node: &FilterNode,
+    output_pads: &[OutputPad],
+    stream_name_map: &StreamNameMap,
+) -> String {
+    let incoming_edges = node.node().get_incoming_edges();
+    let inputs: Vec<String> = incoming_edges
+        .iter()
+        .map(|edge| format_input_stream_name(stream_name_map, edge, false))
+        .collect();
+    let outputs: Vec<String> = output_pads
+        .iter()
+        .map(|pad| format_output_stream_name(stream_name_map, node.node(), &pad.label))
+        .collect();
+    format!(
+        "{}{}{}",
+        inputs.join(""),
+        node.get_filter(),
+        outputs.join("")
+    )
+}
+
+fn get_filter_args(
+    filter_nodes: &[FilterNode],
+    outgoing_edge_maps: &OutgoingEdgeMaps<Node>,
+    stream_name_map: &mut StreamNameMap,
+) -> Result<String, Box<dyn Error>> {
+    let (output_pad_map, sinks) =
+        allocate_filter_stream_name(filter_nodes, outgoing_edge_maps, stream_name_map)?;
+    let mut filter_spec: Vec<String> = filter_nodes
+        .iter()
+        .map(|node| {
+            let output_pads = output_pad_map
+                .get(&node.node().hash())
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            get_filter_spec(node, output_pads, stream_name_map)
+        })
+        .collect();
+    filter_spec.extend(sinks);
+    Ok(filter_spec.join(";"))
+}
+
+fn format_input_stream_name(
+    stream_name_map: &StreamNameMap,
+    edge: &DagEdge<Node>,
+    is_final_arg: bool,
+) -> String {
+    let key = format!("{}_{}", edge.upstream_node.hash(), edge.upstream_label);
+    let prefix = stream_name_map.get(&key);
+    let mut suffix = String::new();
+    if let (Some(_), Some(upstream_selector)) = (prefix, edge.upstream_selector) {
         suffix = format!(":{}", upstream_selector);
     }
-    if is_final_arg && edge.upstream_node.downcast_ref::<InputNode>().is_some() {
+    let prefix = prefix.map(String::as_str).unwrap_or_default();
+
+    if is_final_arg && edge.upstream_node.node_type() == NodeType::Input {
         format!("{}{}", prefix, suffix)
     } else {
         format!("[{}{}]", prefix, suffix)
     }
 }
 
-fn format_output_stream_name(stream_name_map: &HashMap<String, String>, edge: &DagEdge) -> String {
-    let key = format!("{}_{}", edge.upstream_node.hash, edge.upstream_label);
-    format!("[{}]", stream_name_map.get(&key).unwrap_or(&"".to_string()))
+fn format_output_stream_name(
+    stream_name_map: &StreamNameMap,
+    upstream_node: &Node,
+    upstream_label: &str,
+) -> String {
+    let key = format!("{}_{}", upstream_node.hash(), upstream_label);
+    format!(
+        "[{}]",
+        stream_name_map
+            .get(&key)
+            .map(String::as_str)
+            .unwrap_or_default()
+    )
 }
 
-fn get_output_args(output_node: &KwargReprNode, stream_name_map: &HashMap<String, String>) -> Vec<String> {
-  let mut args: Vec<String> = Vec::new();
+fn get_output_args(output_node: &Node, stream_name_map: &StreamNameMap) -> Vec<String> {
+    let mut args: Vec<String> = Vec::new();
 
-  let incoming_edges = output_node.get_incoming_edges();
-  for edge in incoming_edges {
-      let stream_name = format_input_stream_name(stream_name_map, &edge, true);
-      if stream_name != "0" || incoming_edges.len() > 1 {
-          args.push("-map".to_owned());
-          args.push(stream_name);
-      }
-  }
+    let (metadata_edges, incoming_edges): (Vec<_>, Vec<_>) = output_node
+        .get_incoming_edges()
+        .into_iter()
+        .partition(|edge| edge.downstream_label == METADATA_LABEL);
+    for edge in &incoming_edges {
+        let stream_name = format_input_stream_name(stream_name_map, edge, true);
+        if stream_name != "0" || incoming_edges.len() > 1 {
+            args.push("-map".to_owned());
+            args.push(stream_name);
+        }
+    }
+    // `map_metadata` or `map_chapters` set on the output win over the metadata
+    // input, which then only provides the other one.
+    for edge in &metadata_edges {
+        let input_index = format_input_stream_name(stream_name_map, edge, true);
+        for option in ["map_metadata", "map_chapters"] {
+            if !output_node.kwargs().iter().any(|(key, _)| key == option) {
+                args.push(format!("-{}", option));
+                args.push(input_index.clone());
+            }
+        }
+    }
 
-  let kwargs = &output_node.kwargs;
-  let filename = &kwargs["filename"];
-  let mut output_args = args;
-  output_args.append(&mut convert_kwargs_to_cmd_args(kwargs));
-  output_args.push(filename.to_owned());
+    args.extend(convert_kwargs_to_cmd_args(output_node.kwargs()));
+    args.push(get_filename(output_node.kwargs()));
+    args
+}
 
-  return output_args;
+fn get_input_args(input_node: &Node) -> Vec<String> {
+    let mut args = convert_kwargs_to_cmd_args(input_node.kwargs());
+    args.push("-i".to_owned());
+    args.push(get_filename(input_node.kwargs()));
+    args
 }
 
-fn get_input_args(input_node: &KwargReprNode) -> Vec<String> {
-  let kwargs = &input_node.kwargs;
-  let filename = &kwargs["filename"];
-  let mut args = convert_kwargs_to_cmd_args(kwargs);
-  args.push("-i".to_owned());
-  args.push(filename.to_owned());
-  return args;
+fn get_filename(kwargs: &Kwargs) -> String {
+    kwargs
+        .iter()
+        .find(|(key, _)| key == "filename")
+        .map(|(_, value)| value.clone())
+        .unwrap_or_default()
 }
 
-fn convert_kwargs_to_cmd_args(kwargs: &HashMap<String, String>) -> Vec<String> {
-  let mut args: Vec<String> = Vec::new();
-  for (key, value) in kwargs {
-      if key == "filename" {
-          continue;
-      }
-      args.push(format!("-{}", key));
-      args.push(value.to_owned());
-  }
-  return args;
-}
\ No newline at end of file
+pub(crate) fn convert_kwargs_to_cmd_args(kwargs: &Kwargs) -> Vec<String> {
+    let mut args: Vec<String> = Vec::new();
+    for (key, value) in kwargs {
+        if key == "filename" {
+            continue;
+        }
+        args.push(format!("-{}", key));
+        args.push(value.to_owned());
+    }
+    args
+}
@@ -0,0 +1,1540 @@
+// @generated by `cargo run --features cli -- codegen FILTERS_DUMP HELP_DUMP...` from
+// `ffmpeg -filters` and `ffmpeg -h filter=NAME` dumps. Do not edit by hand.
+#![allow(clippy::should_implement_trait, clippy::wrong_self_convention)]
+
+/// Cross fade two input audio streams.
+pub fn acrossfade(input0: &crate::node::FilterableStream, input1: &crate::node::FilterableStream) -> Acrossfade {
+    Acrossfade::new(crate::node::StreamSpec::from([input0, input1]))
+}
+
+pub struct Acrossfade {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Acrossfade {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "acrossfade", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "acrossfade", self.kwargs)
+    }
+}
+
+/// Fade in/out input audio.
+pub fn afade(stream_spec: impl Into<crate::node::StreamSpec>) -> Afade {
+    Afade::new(stream_spec.into())
+}
+
+pub struct Afade {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Afade {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// set the fade direction (from 0 to 1) (default in)
+    pub fn r#type(self, value: AfadeType) -> Self {
+        self.arg("type", value.as_str())
+    }
+
+    /// set the fade direction (from 0 to 1) (default in)
+    pub fn t(self, value: AfadeT) -> Self {
+        self.arg("t", value.as_str())
+    }
+
+    /// set number of first sample to start fading (from 0 to 9223372036854776000) (default 0)
+    pub fn start_sample(self, value: i64) -> Self {
+        self.arg("start_sample", value)
+    }
+
+    /// set number of first sample to start fading (from 0 to 9223372036854776000) (default 0)
+    pub fn ss(self, value: i64) -> Self {
+        self.arg("ss", value)
+    }
+
+    /// set number of samples for fade duration (from 1 to 9223372036854776000) (default 44100)
+    pub fn nb_samples(self, value: i64) -> Self {
+        self.arg("nb_samples", value)
+    }
+
+    /// set number of samples for fade duration (from 1 to 9223372036854776000) (default 44100)
+    pub fn ns(self, value: i64) -> Self {
+        self.arg("ns", value)
+    }
+
+    /// set time to start fading (default 0)
+    pub fn start_time(self, value: &str) -> Self {
+        self.arg("start_time", value)
+    }
+
+    /// set time to start fading (default 0)
+    pub fn st(self, value: &str) -> Self {
+        self.arg("st", value)
+    }
+
+    /// set fade duration (default 0)
+    pub fn duration(self, value: &str) -> Self {
+        self.arg("duration", value)
+    }
+
+    /// set fade duration (default 0)
+    pub fn d(self, value: &str) -> Self {
+        self.arg("d", value)
+    }
+
+    /// set the silence gain (from 0 to 1) (default 0)
+    pub fn silence(self, value: f64) -> Self {
+        self.arg("silence", value)
+    }
+
+    /// set the unity gain (from 0 to 1) (default 1)
+    pub fn unity(self, value: f64) -> Self {
+        self.arg("unity", value)
+    }
+
+    /// Timeline editing: expression enabling the filter, e.g. `between(t,10,20)`
+    pub fn enable(self, value: &str) -> Self {
+        self.arg("enable", value)
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "afade", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "afade", self.kwargs)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AfadeType {
+    In,
+    Out,
+}
+
+impl AfadeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AfadeType::In => "in",
+            AfadeType::Out => "out",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AfadeT {
+    In,
+    Out,
+}
+
+impl AfadeT {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AfadeT::In => "in",
+            AfadeT::Out => "out",
+        }
+    }
+}
+
+/// Convert the input audio to one of the specified formats.
+pub fn aformat(stream_spec: impl Into<crate::node::StreamSpec>) -> Aformat {
+    Aformat::new(stream_spec.into())
+}
+
+pub struct Aformat {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Aformat {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "aformat", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "aformat", self.kwargs)
+    }
+}
+
+/// Audio mixing.
+pub fn amix(stream_spec: impl Into<crate::node::StreamSpec>) -> Amix {
+    Amix::new(stream_spec.into())
+}
+
+pub struct Amix {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Amix {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// Number of inputs. (from 1 to 32767) (default 2)
+    pub fn inputs(self, value: i64) -> Self {
+        self.arg("inputs", value)
+    }
+
+    /// How to determine the end-of-stream. (from 0 to 2) (default longest)
+    pub fn duration(self, value: AmixDuration) -> Self {
+        self.arg("duration", value.as_str())
+    }
+
+    /// Transition time, in seconds, for volume renormalization when an input stream ends. (from 0 to 2147483647) (default 2)
+    pub fn dropout_transition(self, value: f64) -> Self {
+        self.arg("dropout_transition", value)
+    }
+
+    /// Set weight for each input. (default "1 1")
+    pub fn weights(self, value: &str) -> Self {
+        self.arg("weights", value)
+    }
+
+    /// Scale inputs (default true)
+    pub fn normalize(self, value: bool) -> Self {
+        self.arg("normalize", value)
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "amix", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "amix", self.kwargs)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AmixDuration {
+    Longest,
+    Shortest,
+    First,
+}
+
+impl AmixDuration {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AmixDuration::Longest => "longest",
+            AmixDuration::Shortest => "shortest",
+            AmixDuration::First => "first",
+        }
+    }
+}
+
+/// Pass the source unchanged to the output.
+pub fn anull(stream_spec: impl Into<crate::node::StreamSpec>) -> Anull {
+    Anull::new(stream_spec.into())
+}
+
+pub struct Anull {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Anull {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "anull", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "anull", self.kwargs)
+    }
+}
+
+/// Null audio source, return unprocessed audio frames.
+pub fn anullsrc() -> Anullsrc {
+    Anullsrc::new(crate::node::StreamSpec::Multiple(Vec::new()))
+}
+
+pub struct Anullsrc {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Anullsrc {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// set channel_layout (default stereo)
+    pub fn channel_layout(self, value: &str) -> Self {
+        self.arg("channel_layout", value)
+    }
+
+    /// set channel_layout (default stereo)
+    pub fn cl(self, value: &str) -> Self {
+        self.arg("cl", value)
+    }
+
+    /// set sample rate (from 1 to 2147483647) (default 44100)
+    pub fn sample_rate(self, value: i64) -> Self {
+        self.arg("sample_rate", value)
+    }
+
+    /// set sample rate (from 1 to 2147483647) (default 44100)
+    pub fn r(self, value: i64) -> Self {
+        self.arg("r", value)
+    }
+
+    /// set the number of samples per requested frame (from 1 to 65535) (default 1024)
+    pub fn nb_samples(self, value: i64) -> Self {
+        self.arg("nb_samples", value)
+    }
+
+    /// set the number of samples per requested frame (from 1 to 65535) (default 1024)
+    pub fn n(self, value: i64) -> Self {
+        self.arg("n", value)
+    }
+
+    /// set the audio duration (default -0.000001)
+    pub fn duration(self, value: &str) -> Self {
+        self.arg("duration", value)
+    }
+
+    /// set the audio duration (default -0.000001)
+    pub fn d(self, value: &str) -> Self {
+        self.arg("d", value)
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "anullsrc", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "anullsrc", self.kwargs)
+    }
+}
+
+/// Resample audio data.
+pub fn aresample(stream_spec: impl Into<crate::node::StreamSpec>) -> Aresample {
+    Aresample::new(stream_spec.into())
+}
+
+pub struct Aresample {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Aresample {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "aresample", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "aresample", self.kwargs)
+    }
+}
+
+/// Set PTS for the output audio frame.
+pub fn asetpts(stream_spec: impl Into<crate::node::StreamSpec>) -> Asetpts {
+    Asetpts::new(stream_spec.into())
+}
+
+pub struct Asetpts {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Asetpts {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "asetpts", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "asetpts", self.kwargs)
+    }
+}
+
+/// Pass on the audio input to N audio outputs.
+pub fn asplit(stream_spec: impl Into<crate::node::StreamSpec>) -> Asplit {
+    Asplit::new(stream_spec.into())
+}
+
+pub struct Asplit {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Asplit {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "asplit", self.kwargs)
+    }
+}
+
+/// Pick one continuous section from the input, drop the rest.
+pub fn atrim(stream_spec: impl Into<crate::node::StreamSpec>) -> Atrim {
+    Atrim::new(stream_spec.into())
+}
+
+pub struct Atrim {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Atrim {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "atrim", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "atrim", self.kwargs)
+    }
+}
+
+/// Concatenate audio and video streams.
+pub fn concat(stream_spec: impl Into<crate::node::StreamSpec>) -> Concat {
+    Concat::new(stream_spec.into())
+}
+
+pub struct Concat {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Concat {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// specify the number of segments (from 1 to 2147483647) (default 2)
+    pub fn n(self, value: i64) -> Self {
+        self.arg("n", value)
+    }
+
+    /// specify the number of video streams (from 0 to 2147483647) (default 1)
+    pub fn v(self, value: i64) -> Self {
+        self.arg("v", value)
+    }
+
+    /// specify the number of audio streams (from 0 to 2147483647) (default 0)
+    pub fn a(self, value: i64) -> Self {
+        self.arg("a", value)
+    }
+
+    /// enable unsafe mode (default false)
+    pub fn r#unsafe(self, value: bool) -> Self {
+        self.arg("unsafe", value)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "concat", self.kwargs)
+    }
+}
+
+/// Crop the input video.
+pub fn crop(stream_spec: impl Into<crate::node::StreamSpec>) -> Crop {
+    Crop::new(stream_spec.into())
+}
+
+pub struct Crop {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Crop {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// Timeline editing: expression enabling the filter, e.g. `between(t,10,20)`
+    pub fn enable(self, value: &str) -> Self {
+        self.arg("enable", value)
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "crop", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "crop", self.kwargs)
+    }
+}
+
+/// Draw text on top of video frames using libfreetype library.
+pub fn drawtext(stream_spec: impl Into<crate::node::StreamSpec>) -> Drawtext {
+    Drawtext::new(stream_spec.into())
+}
+
+pub struct Drawtext {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Drawtext {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// Timeline editing: expression enabling the filter, e.g. `between(t,10,20)`
+    pub fn enable(self, value: &str) -> Self {
+        self.arg("enable", value)
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "drawtext", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "drawtext", self.kwargs)
+    }
+}
+
+/// EBU R128 scanner.
+pub fn ebur128(stream_spec: impl Into<crate::node::StreamSpec>) -> Ebur128 {
+    Ebur128::new(stream_spec.into())
+}
+
+pub struct Ebur128 {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Ebur128 {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// Timeline editing: expression enabling the filter, e.g. `between(t,10,20)`
+    pub fn enable(self, value: &str) -> Self {
+        self.arg("enable", value)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "ebur128", self.kwargs)
+    }
+}
+
+/// Convert the input video to one of the specified pixel formats.
+pub fn format(stream_spec: impl Into<crate::node::StreamSpec>) -> Format {
+    Format::new(stream_spec.into())
+}
+
+pub struct Format {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Format {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "format", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "format", self.kwargs)
+    }
+}
+
+/// Force constant framerate.
+pub fn fps(stream_spec: impl Into<crate::node::StreamSpec>) -> Fps {
+    Fps::new(stream_spec.into())
+}
+
+pub struct Fps {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Fps {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// A string describing desired output framerate (default "25")
+    pub fn fps(self, value: &str) -> Self {
+        self.arg("fps", value)
+    }
+
+    /// Assume the first PTS should be this value. (from -179769313486231570000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000 to 179769313486231570000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000) (default DBL_MAX)
+    pub fn start_time(self, value: f64) -> Self {
+        self.arg("start_time", value)
+    }
+
+    /// set rounding method for timestamps (from 0 to 5) (default near)
+    pub fn round(self, value: FpsRound) -> Self {
+        self.arg("round", value.as_str())
+    }
+
+    /// action performed for last frame (from 0 to 1) (default round)
+    pub fn eof_action(self, value: FpsEofAction) -> Self {
+        self.arg("eof_action", value.as_str())
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "fps", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "fps", self.kwargs)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FpsRound {
+    Zero,
+    Inf,
+    Down,
+    Up,
+    Near,
+}
+
+impl FpsRound {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FpsRound::Zero => "zero",
+            FpsRound::Inf => "inf",
+            FpsRound::Down => "down",
+            FpsRound::Up => "up",
+            FpsRound::Near => "near",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FpsEofAction {
+    Round,
+    Pass,
+}
+
+impl FpsEofAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FpsEofAction::Round => "round",
+            FpsEofAction::Pass => "pass",
+        }
+    }
+}
+
+/// Horizontally flip the input video.
+pub fn hflip(stream_spec: impl Into<crate::node::StreamSpec>) -> Hflip {
+    Hflip::new(stream_spec.into())
+}
+
+pub struct Hflip {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Hflip {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// Timeline editing: expression enabling the filter, e.g. `between(t,10,20)`
+    pub fn enable(self, value: &str) -> Self {
+        self.arg("enable", value)
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "hflip", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "hflip", self.kwargs)
+    }
+}
+
+/// Loop video frames.
+pub fn r#loop(stream_spec: impl Into<crate::node::StreamSpec>) -> Loop {
+    Loop::new(stream_spec.into())
+}
+
+pub struct Loop {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Loop {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "loop", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "loop", self.kwargs)
+    }
+}
+
+/// Pass the source unchanged to the output.
+pub fn null(stream_spec: impl Into<crate::node::StreamSpec>) -> Null {
+    Null::new(stream_spec.into())
+}
+
+pub struct Null {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Null {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "null", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "null", self.kwargs)
+    }
+}
+
+/// Do absolutely nothing with the input video.
+pub fn nullsink(stream_spec: impl Into<crate::node::StreamSpec>) -> Nullsink {
+    Nullsink::new(stream_spec.into())
+}
+
+pub struct Nullsink {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Nullsink {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "nullsink", self.kwargs)
+    }
+}
+
+/// Overlay a video source on top of the input.
+pub fn overlay(main: &crate::node::FilterableStream, overlay: &crate::node::FilterableStream) -> Overlay {
+    Overlay::new(crate::node::StreamSpec::from([main, overlay]))
+}
+
+pub struct Overlay {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Overlay {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// set the x expression (default "0")
+    pub fn x(self, value: &str) -> Self {
+        self.arg("x", value)
+    }
+
+    /// set the y expression (default "0")
+    pub fn y(self, value: &str) -> Self {
+        self.arg("y", value)
+    }
+
+    /// Action to take when encountering EOF from secondary input (from 0 to 2) (default repeat)
+    pub fn eof_action(self, value: OverlayEofAction) -> Self {
+        self.arg("eof_action", value.as_str())
+    }
+
+    /// specify when to evaluate expressions (from 0 to 1) (default frame)
+    pub fn eval(self, value: OverlayEval) -> Self {
+        self.arg("eval", value.as_str())
+    }
+
+    /// force termination when the shortest input terminates (default false)
+    pub fn shortest(self, value: bool) -> Self {
+        self.arg("shortest", value)
+    }
+
+    /// set output format (from 0 to 7) (default yuv420)
+    pub fn format(self, value: OverlayFormat) -> Self {
+        self.arg("format", value.as_str())
+    }
+
+    /// repeat overlay of the last overlay frame (default true)
+    pub fn repeatlast(self, value: bool) -> Self {
+        self.arg("repeatlast", value)
+    }
+
+    /// alpha format (from 0 to 1) (default straight)
+    pub fn alpha(self, value: OverlayAlpha) -> Self {
+        self.arg("alpha", value.as_str())
+    }
+
+    /// How strictly to sync streams based on secondary input timestamps (from 0 to 1) (default default)
+    pub fn ts_sync_mode(self, value: OverlayTsSyncMode) -> Self {
+        self.arg("ts_sync_mode", value.as_str())
+    }
+
+    /// Timeline editing: expression enabling the filter, e.g. `between(t,10,20)`
+    pub fn enable(self, value: &str) -> Self {
+        self.arg("enable", value)
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "overlay", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "overlay", self.kwargs)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OverlayEofAction {
+    Repeat,
+    Endall,
+    Pass,
+}
+
+impl OverlayEofAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverlayEofAction::Repeat => "repeat",
+            OverlayEofAction::Endall => "endall",
+            OverlayEofAction::Pass => "pass",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OverlayEval {
+    Init,
+    Frame,
+}
+
+impl OverlayEval {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverlayEval::Init => "init",
+            OverlayEval::Frame => "frame",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OverlayFormat {
+    Yuv420,
+    Yuv420p10,
+    Yuv422,
+    Yuv422p10,
+    Yuv444,
+    Rgb,
+    Gbrp,
+    Auto,
+}
+
+impl OverlayFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverlayFormat::Yuv420 => "yuv420",
+            OverlayFormat::Yuv420p10 => "yuv420p10",
+            OverlayFormat::Yuv422 => "yuv422",
+            OverlayFormat::Yuv422p10 => "yuv422p10",
+            OverlayFormat::Yuv444 => "yuv444",
+            OverlayFormat::Rgb => "rgb",
+            OverlayFormat::Gbrp => "gbrp",
+            OverlayFormat::Auto => "auto",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OverlayAlpha {
+    Straight,
+    Premultiplied,
+    Repeat,
+    Endall,
+    Pass,
+}
+
+impl OverlayAlpha {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverlayAlpha::Straight => "straight",
+            OverlayAlpha::Premultiplied => "premultiplied",
+            OverlayAlpha::Repeat => "repeat",
+            OverlayAlpha::Endall => "endall",
+            OverlayAlpha::Pass => "pass",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OverlayTsSyncMode {
+    Default,
+    Nearest,
+}
+
+impl OverlayTsSyncMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverlayTsSyncMode::Default => "default",
+            OverlayTsSyncMode::Nearest => "nearest",
+        }
+    }
+}
+
+/// Scale the input video size and/or convert the image format.
+pub fn scale(stream_spec: impl Into<crate::node::StreamSpec>) -> Scale {
+    Scale::new(stream_spec.into())
+}
+
+pub struct Scale {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Scale {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// Output video width
+    pub fn w(self, value: &str) -> Self {
+        self.arg("w", value)
+    }
+
+    /// Output video width
+    pub fn width(self, value: &str) -> Self {
+        self.arg("width", value)
+    }
+
+    /// Output video height
+    pub fn h(self, value: &str) -> Self {
+        self.arg("h", value)
+    }
+
+    /// Output video height
+    pub fn height(self, value: &str) -> Self {
+        self.arg("height", value)
+    }
+
+    /// Flags to pass to libswscale (default "")
+    pub fn flags(self, value: &str) -> Self {
+        self.arg("flags", value)
+    }
+
+    /// set interlacing (default false)
+    pub fn interl(self, value: bool) -> Self {
+        self.arg("interl", value)
+    }
+
+    /// set video size
+    pub fn size(self, value: &str) -> Self {
+        self.arg("size", value)
+    }
+
+    /// set video size
+    pub fn s(self, value: &str) -> Self {
+        self.arg("s", value)
+    }
+
+    /// set input color range (from 0 to 2) (default auto)
+    pub fn in_range(self, value: ScaleInRange) -> Self {
+        self.arg("in_range", value.as_str())
+    }
+
+    /// decrease or increase w/h if necessary to keep the original AR (from 0 to 2) (default disable)
+    pub fn force_original_aspect_ratio(self, value: ScaleForceOriginalAspectRatio) -> Self {
+        self.arg("force_original_aspect_ratio", value.as_str())
+    }
+
+    /// enforce that the output resolution is divisible by a defined integer when force_original_aspect_ratio is used (from 1 to 256) (default 1)
+    pub fn force_divisible_by(self, value: i64) -> Self {
+        self.arg("force_divisible_by", value)
+    }
+
+    /// Scaler param 0 (from -179769313486231570000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000 to 179769313486231570000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000) (default DBL_MAX)
+    pub fn param0(self, value: f64) -> Self {
+        self.arg("param0", value)
+    }
+
+    /// Scaler param 1 (from -179769313486231570000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000 to 179769313486231570000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000) (default DBL_MAX)
+    pub fn param1(self, value: f64) -> Self {
+        self.arg("param1", value)
+    }
+
+    /// specify when to evaluate expressions (from 0 to 1) (default init)
+    pub fn eval(self, value: ScaleEval) -> Self {
+        self.arg("eval", value.as_str())
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "scale", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "scale", self.kwargs)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScaleInRange {
+    Auto,
+    Unknown,
+    Full,
+    Limited,
+    Jpeg,
+    Mpeg,
+    Tv,
+    Pc,
+}
+
+impl ScaleInRange {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScaleInRange::Auto => "auto",
+            ScaleInRange::Unknown => "unknown",
+            ScaleInRange::Full => "full",
+            ScaleInRange::Limited => "limited",
+            ScaleInRange::Jpeg => "jpeg",
+            ScaleInRange::Mpeg => "mpeg",
+            ScaleInRange::Tv => "tv",
+            ScaleInRange::Pc => "pc",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScaleForceOriginalAspectRatio {
+    Disable,
+    Decrease,
+    Increase,
+}
+
+impl ScaleForceOriginalAspectRatio {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScaleForceOriginalAspectRatio::Disable => "disable",
+            ScaleForceOriginalAspectRatio::Decrease => "decrease",
+            ScaleForceOriginalAspectRatio::Increase => "increase",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScaleEval {
+    Init,
+    Frame,
+}
+
+impl ScaleEval {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScaleEval::Init => "init",
+            ScaleEval::Frame => "frame",
+        }
+    }
+}
+
+/// Set PTS for the output video frame.
+pub fn setpts(stream_spec: impl Into<crate::node::StreamSpec>) -> Setpts {
+    Setpts::new(stream_spec.into())
+}
+
+pub struct Setpts {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Setpts {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// Expression determining the frame timestamp (default "PTS")
+    pub fn expr(self, value: &str) -> Self {
+        self.arg("expr", value)
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "setpts", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "setpts", self.kwargs)
+    }
+}
+
+/// Pass on the input to N video outputs.
+pub fn split(stream_spec: impl Into<crate::node::StreamSpec>) -> Split {
+    Split::new(stream_spec.into())
+}
+
+pub struct Split {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Split {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// set number of outputs (from 1 to 2147483647) (default 2)
+    pub fn outputs(self, value: i64) -> Self {
+        self.arg("outputs", value)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "split", self.kwargs)
+    }
+}
+
+/// Pick one continuous section from the input, drop the rest.
+pub fn trim(stream_spec: impl Into<crate::node::StreamSpec>) -> Trim {
+    Trim::new(stream_spec.into())
+}
+
+pub struct Trim {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Trim {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// Timestamp of the first frame that should be passed (default INT64_MAX)
+    pub fn start(self, value: &str) -> Self {
+        self.arg("start", value)
+    }
+
+    /// Timestamp of the first frame that should be passed (default INT64_MAX)
+    pub fn starti(self, value: &str) -> Self {
+        self.arg("starti", value)
+    }
+
+    /// Timestamp of the first frame that should be dropped again (default INT64_MAX)
+    pub fn end(self, value: &str) -> Self {
+        self.arg("end", value)
+    }
+
+    /// Timestamp of the first frame that should be dropped again (default INT64_MAX)
+    pub fn endi(self, value: &str) -> Self {
+        self.arg("endi", value)
+    }
+
+    /// Timestamp of the first frame that should be  passed (from -9223372036854776000 to 9223372036854776000) (default I64_MIN)
+    pub fn start_pts(self, value: i64) -> Self {
+        self.arg("start_pts", value)
+    }
+
+    /// Timestamp of the first frame that should be dropped again (from -9223372036854776000 to 9223372036854776000) (default I64_MIN)
+    pub fn end_pts(self, value: i64) -> Self {
+        self.arg("end_pts", value)
+    }
+
+    /// Maximum duration of the output (default 0)
+    pub fn duration(self, value: &str) -> Self {
+        self.arg("duration", value)
+    }
+
+    /// Maximum duration of the output (default 0)
+    pub fn durationi(self, value: &str) -> Self {
+        self.arg("durationi", value)
+    }
+
+    /// Number of the first frame that should be passed to the output (from -1 to 9223372036854776000) (default -1)
+    pub fn start_frame(self, value: i64) -> Self {
+        self.arg("start_frame", value)
+    }
+
+    /// Number of the first frame that should be dropped again (from 0 to 9223372036854776000) (default I64_MAX)
+    pub fn end_frame(self, value: i64) -> Self {
+        self.arg("end_frame", value)
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "trim", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "trim", self.kwargs)
+    }
+}
+
+/// Change input volume.
+pub fn volume(stream_spec: impl Into<crate::node::StreamSpec>) -> Volume {
+    Volume::new(stream_spec.into())
+}
+
+pub struct Volume {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Volume {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    /// set volume adjustment expression (default "1.0")
+    pub fn volume(self, value: &str) -> Self {
+        self.arg("volume", value)
+    }
+
+    /// select mathematical precision (from 0 to 2) (default float)
+    pub fn precision(self, value: VolumePrecision) -> Self {
+        self.arg("precision", value.as_str())
+    }
+
+    /// specify when to evaluate expressions (from 0 to 1) (default once)
+    pub fn eval(self, value: VolumeEval) -> Self {
+        self.arg("eval", value.as_str())
+    }
+
+    /// Apply replaygain side data when present (from 0 to 3) (default drop)
+    pub fn replaygain(self, value: VolumeReplaygain) -> Self {
+        self.arg("replaygain", value.as_str())
+    }
+
+    /// Apply replaygain pre-amplification (from -15 to 15) (default 0)
+    pub fn replaygain_preamp(self, value: f64) -> Self {
+        self.arg("replaygain_preamp", value)
+    }
+
+    /// Apply replaygain clipping prevention (default true)
+    pub fn replaygain_noclip(self, value: bool) -> Self {
+        self.arg("replaygain_noclip", value)
+    }
+
+    /// Timeline editing: expression enabling the filter, e.g. `between(t,10,20)`
+    pub fn enable(self, value: &str) -> Self {
+        self.arg("enable", value)
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "volume", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "volume", self.kwargs)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VolumePrecision {
+    Fixed,
+    Float,
+    Double,
+}
+
+impl VolumePrecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VolumePrecision::Fixed => "fixed",
+            VolumePrecision::Float => "float",
+            VolumePrecision::Double => "double",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VolumeEval {
+    Once,
+    Frame,
+}
+
+impl VolumeEval {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VolumeEval::Once => "once",
+            VolumeEval::Frame => "frame",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VolumeReplaygain {
+    Drop,
+    Ignore,
+    Track,
+    Album,
+}
+
+impl VolumeReplaygain {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VolumeReplaygain::Drop => "drop",
+            VolumeReplaygain::Ignore => "ignore",
+            VolumeReplaygain::Track => "track",
+            VolumeReplaygain::Album => "album",
+        }
+    }
+}
+
+/// Cross fade one video with another video.
+pub fn xfade(input0: &crate::node::FilterableStream, input1: &crate::node::FilterableStream) -> Xfade {
+    Xfade::new(crate::node::StreamSpec::from([input0, input1]))
+}
+
+pub struct Xfade {
+    stream_spec: crate::node::StreamSpec,
+    kwargs: crate::daq::Kwargs,
+}
+
+impl Xfade {
+    fn new(stream_spec: crate::node::StreamSpec) -> Self {
+        Self {
+            stream_spec,
+            kwargs: crate::daq::Kwargs::new(),
+        }
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn stream(self) -> crate::node::FilterableStream {
+        crate::ffmpeg::filter(self.stream_spec, "xfade", self.kwargs)
+    }
+
+    pub fn node(self) -> crate::node::FilterNode {
+        crate::ffmpeg::filter_multi_output(self.stream_spec, "xfade", self.kwargs)
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Write;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::daq::{topo_sort, DagNode, IncomingEdge, Kwargs, Label, Selector};
+use crate::ffmpeg::{compile, convert_kwargs_to_cmd_args};
+use crate::node::{Node, NodeType, Stream, StreamSpec};
+
+/*
+A plain-data copy of a graph, for storing and reloading pipelines.
+
+    ``Graph::new`` flattens the nodes behind a stream spec in ``topo_sort`` order;
+    edges refer to nodes by their index in ``nodes``, so every edge points back
+    to an earlier node.  ``Graph::stream_spec`` rebuilds the nodes, after which
+    the graph compiles like the original.  ``to_dot`` and ``to_mermaid`` draw it
+    in the same order, e.g. for docs or to spot mislabelled links.
+
+    With the ``serde`` feature the graph (de)serializes into a stable schema.
+    Kwargs stay lists of pairs since their order and repeated keys (such as
+    ``metadata``) matter:
+
+        {
+          "version": 1,
+          "globals": [["loglevel", "error"]],
+          "nodes": [
+            {"type": "input", "name": "input", "kwargs": [["filename", "in.mp4"]]},
+            {"type": "filter", "name": "hflip", "kwargs": []},
+            {"type": "output", "name": "output", "kwargs": [["filename", "out.mp4"]]}
+          ],
+          "edges": [
+            {"from": 0, "from_label": "", "selector": "v", "to": 1, "to_label": ""},
+            {"from": 1, "from_label": "", "selector": null, "to": 2, "to_label": ""}
+          ],
+          "streams": [{"node": 2, "label": "", "selector": null}]
+        }
+
+    ``version`` changes whenever a stored graph would no longer load or compile
+    the same way.  Nodes with a pipe or a generated file (concat lists,
+    ffmetadata files) cannot be stored, since those do not outlive the process.
+*/
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Graph {
+    pub version: u32,
+    // Global options such as `loglevel`, placed before the inputs by `compile`.
+    // Options without a value are passed with an empty one, e.g. `("nostdin", "")`.
+    pub globals: Kwargs,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    // The streams the graph was built from, usually its outputs.
+    pub streams: Vec<GraphStream>,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GraphNode {
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub node_type: NodeType,
+    pub name: String,
+    pub kwargs: Kwargs,
+}
+
+// An incoming edge of `to`, in the order the node lists them.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub from_label: Label,
+    pub selector: Selector,
+    pub to: usize,
+    pub to_label: Label,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GraphStream {
+    pub node: usize,
+    pub label: Label,
+    pub selector: Selector,
+}
+
+impl Graph {
+    pub fn new(stream_spec: impl Into<StreamSpec>) -> Result<Graph, Box<dyn Error>> {
+        let streams = match stream_spec.into() {
+            StreamSpec::Single(stream) => vec![stream],
+            StreamSpec::Multiple(streams) => streams,
+        };
+        let downstream_nodes: Vec<Node> =
+            streams.iter().map(|stream| stream.node.clone()).collect();
+        let (sorted_nodes, _) = topo_sort(&downstream_nodes)?;
+        let indices: HashMap<u64, usize> = sorted_nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.hash(), i))
+            .collect();
+
+        let mut graph = Graph {
+            version: SCHEMA_VERSION,
+            globals: Kwargs::new(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            streams: streams
+                .iter()
+                .map(|stream| GraphStream {
+                    node: indices[&stream.node.hash()],
+                    label: stream.label.clone(),
+                    selector: stream.selector,
+                })
+                .collect(),
+        };
+        for (i, node) in sorted_nodes.iter().enumerate() {
+            if node.pipe().is_some() || node.temp_file().is_some() {
+                return Err(format!(
+                    "Node #{} `{}` uses a pipe or generated file, which cannot be stored",
+                    i,
+                    node.name()
+                )
+                .into());
+            }
+            graph.nodes.push(GraphNode {
+                node_type: node.node_type(),
+                name: node.name().to_owned(),
+                kwargs: node.kwargs().clone(),
+            });
+            for edge in node.get_incoming_edges() {
+                graph.edges.push(GraphEdge {
+                    from: indices[&edge.upstream_node.hash()],
+                    from_label: edge.upstream_label,
+                    selector: edge.upstream_selector,
+                    to: i,
+                    to_label: edge.downstream_label,
+                });
+            }
+        }
+        Ok(graph)
+    }
+
+    pub fn global(mut self, key: &str, value: impl ToString) -> Self {
+        self.globals.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    // Rebuilds the graph's nodes, failing on graphs from another schema version
+    // or with edges that do not point back to an earlier node.
+    pub fn stream_spec(&self) -> Result<StreamSpec, String> {
+        if self.version != SCHEMA_VERSION {
+            return Err(format!(
+                "Unsupported graph schema version {}; expected {}",
+                self.version, SCHEMA_VERSION
+            ));
+        }
+        let mut nodes: Vec<Node> = Vec::with_capacity(self.nodes.len());
+        for (i, graph_node) in self.nodes.iter().enumerate() {
+            let mut incoming_edge_map = Vec::new();
+            for edge in self.edges.iter().filter(|edge| edge.to == i) {
+                let Some(upstream_node) = nodes.get(edge.from) else {
+                    return Err(format!(
+                        "Edge from node #{} to node #{} does not point back to an earlier node",
+                        edge.from, edge.to
+                    ));
+                };
+                if incoming_edge_map
+                    .iter()
+                    .any(|(label, _)| *label == edge.to_label)
+                {
+                    return Err(format!(
+                        "Node #{} has more than one incoming edge labelled {:?}",
+                        i, edge.to_label
+                    ));
+                }
+                incoming_edge_map.push((
+                    edge.to_label.clone(),
+                    IncomingEdge {
+                        upstream_node: upstream_node.clone(),
+                        upstream_label: edge.from_label.clone(),
+                        upstream_selector: edge.selector,
+                    },
+                ));
+            }
+            if graph_node.node_type == NodeType::Input && !incoming_edge_map.is_empty() {
+                return Err(format!("Input node #{} has incoming edges", i));
+            }
+            nodes.push(Node::new(
+                incoming_edge_map,
+                &graph_node.name,
+                graph_node.node_type,
+                graph_node.kwargs.clone(),
+            ));
+        }
+        if let Some(edge) = self.edges.iter().find(|edge| edge.to >= nodes.len()) {
+            return Err(format!("Edge into missing node #{}", edge.to));
+        }
+
+        let mut streams = Vec::with_capacity(self.streams.len());
+        for stream in &self.streams {
+            let node = nodes
+                .get(stream.node)
+                .ok_or_else(|| format!("Stream of missing node #{}", stream.node))?;
+            streams.push(Stream::new(
+                node.clone(),
+                stream.label.clone(),
+                stream.selector,
+            ));
+        }
+        match streams.len() {
+            0 => Err("Graph has no streams".to_owned()),
+            1 => Ok(StreamSpec::Single(streams.remove(0))),
+            _ => Ok(StreamSpec::Multiple(streams)),
+        }
+    }
+
+    // Like `ffmpeg::compile`, with the graph's global options after `-hide_banner`.
+    pub fn compile(
+        &self,
+        cmd: &str,
+        overwrite_output: bool,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut args = compile(self.stream_spec()?, cmd, overwrite_output)?;
+        let globals = convert_kwargs_to_cmd_args(&self.globals)
+            .into_iter()
+            .filter(|arg| !arg.is_empty());
+        args.splice(2..2, globals);
+        Ok(args)
+    }
+
+    // Graphviz source with a node per graph node, labelled with its name and
+    // kwargs, and edges labelled with their labels and selectors.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n    rankdir=LR;\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let shape = match node.node_type {
+                NodeType::Filter => "ellipse",
+                NodeType::Input | NodeType::Output => "box",
+            };
+            let lines: Vec<String> = node_label(node)
+                .iter()
+                .map(|line| escape_dot(line))
+                .collect();
+            let label = lines.join("\\n");
+            let _ = writeln!(dot, "    n{} [label=\"{}\", shape={}];", i, label, shape);
+        }
+        for edge in &self.edges {
+            let _ = match self.edge_label(edge) {
+                Some(label) => writeln!(
+                    dot,
+                    "    n{} -> n{} [label=\"{}\"];",
+                    edge.from,
+                    edge.to,
+                    escape_dot(&label)
+                ),
+                None => writeln!(dot, "    n{} -> n{};", edge.from, edge.to),
+            };
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    // The same drawing as `to_dot`, as a Mermaid flowchart.
+    // `upstream → downstream`, where the upstream side is the outgoing label and
+    // selector as in `0:v`; edges out of inputs start with the input's index, as
+    // in the compiled command.  Either side is left out when empty.
+    fn edge_label(&self, edge: &GraphEdge) -> Option<String> {
+        let from_label = if self.nodes[edge.from].node_type == NodeType::Input {
+            let index = self.nodes[..edge.from]
+                .iter()
+                .filter(|node| node.node_type == NodeType::Input)
+                .count();
+            index.to_string()
+        } else {
+            edge.from_label.clone()
+        };
+        let upstream = match edge.selector {
+            Some(selector) if from_label.is_empty() => selector.to_string(),
+            Some(selector) => format!("{}:{}", from_label, selector),
+            None => from_label,
+        };
+        match (upstream.is_empty(), edge.to_label.is_empty()) {
+            (true, true) => None,
+            (false, true) => Some(upstream),
+            (true, false) => Some(format!("→ {}", edge.to_label)),
+            (false, false) => Some(format!("{} → {}", upstream, edge.to_label)),
+        }
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("flowchart LR\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let lines: Vec<String> = node_label(node)
+                .iter()
+                .map(|line| escape_mermaid(line))
+                .collect();
+            let label = lines.join("<br>");
+            let _ = match node.node_type {
+                NodeType::Filter => writeln!(mermaid, "    n{}[\"{}\"]", i, label),
+                NodeType::Input | NodeType::Output => {
+                    writeln!(mermaid, "    n{}([\"{}\"])", i, label)
+                }
+            };
+        }
+        for edge in &self.edges {
+            let _ = match self.edge_label(edge) {
+                Some(label) => writeln!(
+                    mermaid,
+                    "    n{} -->|\"{}\"| n{}",
+                    edge.from,
+                    escape_mermaid(&label),
+                    edge.to
+                ),
+                None => writeln!(mermaid, "    n{} --> n{}", edge.from, edge.to),
+            };
+        }
+        mermaid
+    }
+}
+
+// The node's name, then one `key=value` line per kwarg.
+fn node_label(node: &GraphNode) -> Vec<String> {
+    let mut lines = vec![node.name.clone()];
+    lines.extend(
+        node.kwargs
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value)),
+    );
+    lines
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Mermaid labels are HTML; `#name;` stands for the entity `&name;`.
+fn escape_mermaid(text: &str) -> String {
+    text.replace('&', "#amp;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
@@ -0,0 +1,215 @@
+use crate::daq::Kwargs;
+use crate::encoder::Encoder;
+use crate::ffmpeg::output_node;
+use crate::node::{OutputNode, OutputStream, Stream};
+use crate::time::Duration;
+use crate::utils::SharedStreams;
+
+/*
+HLS output with variant streams.
+
+    Every ``Rendition`` is one variant playlist made of an optional video and an
+    optional audio stream.  The renditions' streams are mapped to the output in
+    order, so ``-var_stream_map`` follows from the per type stream indices:
+
+        HlsOutput::new("out_%v.m3u8")
+            .rendition(Rendition::new().video(&hd).audio(&audio).name("720p"))
+            .rendition(Rendition::new().video(&sd).audio(&audio).name("360p"))
+
+        -map [s0] -map 0:a -map [s1] -map 0:a -f hls ...
+        -var_stream_map v:0,a:0,name:720p v:1,a:1,name:360p
+
+    Audio shared by several renditions is mapped once per rendition, as HLS
+    muxes it into every variant.  Filter outputs can only be mapped once, so a
+    shared one goes through an ``asplit`` (or ``split`` for video) first:
+
+        [0:a]aresample=osr=48000[s0];[s0]asplit=outputs=2[s1][s2]
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HlsPlaylistType {
+    Event,
+    Vod,
+}
+
+impl HlsPlaylistType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HlsPlaylistType::Event => "event",
+            HlsPlaylistType::Vod => "vod",
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Rendition {
+    video: Option<Stream>,
+    audio: Option<Stream>,
+    name: Option<String>,
+    video_encoder: Option<Encoder>,
+    audio_encoder: Option<Encoder>,
+}
+
+impl Rendition {
+    pub fn new() -> Rendition {
+        Rendition::default()
+    }
+
+    pub fn video(mut self, stream: &Stream) -> Self {
+        self.video = Some(stream.clone());
+        self
+    }
+
+    pub fn audio(mut self, stream: &Stream) -> Self {
+        self.audio = Some(stream.clone());
+        self
+    }
+
+    // Replaces `%v` in playlist and segment names instead of the rendition index.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_owned());
+        self
+    }
+
+    pub fn video_encoder(mut self, encoder: impl Into<Encoder>) -> Self {
+        self.video_encoder = Some(encoder.into());
+        self
+    }
+
+    pub fn audio_encoder(mut self, encoder: impl Into<Encoder>) -> Self {
+        self.audio_encoder = Some(encoder.into());
+        self
+    }
+}
+
+#[derive(Clone)]
+pub struct HlsOutput {
+    filename: String,
+    renditions: Vec<Rendition>,
+    hls_time: Option<Duration>,
+    playlist_type: Option<HlsPlaylistType>,
+    segment_filename: Option<String>,
+    master_pl_name: Option<String>,
+    kwargs: Kwargs,
+}
+
+impl HlsOutput {
+    // `filename` is the variant playlist name, usually with a `%v` placeholder.
+    pub fn new(filename: &str) -> HlsOutput {
+        HlsOutput {
+            filename: filename.to_owned(),
+            renditions: Vec::new(),
+            hls_time: None,
+            playlist_type: None,
+            segment_filename: None,
+            master_pl_name: None,
+            kwargs: Kwargs::new(),
+        }
+    }
+
+    pub fn rendition(mut self, rendition: Rendition) -> Self {
+        self.renditions.push(rendition);
+        self
+    }
+
+    // Target segment duration.
+    pub fn hls_time(mut self, duration: Duration) -> Self {
+        self.hls_time = Some(duration);
+        self
+    }
+
+    pub fn playlist_type(mut self, playlist_type: HlsPlaylistType) -> Self {
+        self.playlist_type = Some(playlist_type);
+        self
+    }
+
+    pub fn segment_filename(mut self, template: &str) -> Self {
+        self.segment_filename = Some(template.to_owned());
+        self
+    }
+
+    pub fn master_pl_name(mut self, name: &str) -> Self {
+        self.master_pl_name = Some(name.to_owned());
+        self
+    }
+
+    // Any other muxer option, e.g. `hls_flags`.
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn node(&self) -> Result<OutputNode, String> {
+        if self.renditions.is_empty() {
+            return Err("HLS output needs at least one rendition".to_owned());
+        }
+
+        let mut shared = SharedStreams::new(
+            self.renditions
+                .iter()
+                .flat_map(|rendition| [&rendition.video, &rendition.audio])
+                .flatten(),
+        );
+        let mut streams: Vec<Stream> = Vec::new();
+        let mut var_stream_map: Vec<String> = Vec::new();
+        let mut encoders: Vec<(String, &Encoder)> = Vec::new();
+        let (mut video_count, mut audio_count) = (0, 0);
+        for (i, rendition) in self.renditions.iter().enumerate() {
+            let mut entries: Vec<String> = Vec::new();
+            if let Some(video) = &rendition.video {
+                let specifier = format!("v:{}", video_count);
+                if let Some(encoder) = &rendition.video_encoder {
+                    encoders.push((specifier.clone(), encoder));
+                }
+                streams.push(shared.take(video, "split"));
+                entries.push(specifier);
+                video_count += 1;
+            }
+            if let Some(audio) = &rendition.audio {
+                let specifier = format!("a:{}", audio_count);
+                if let Some(encoder) = &rendition.audio_encoder {
+                    encoders.push((specifier.clone(), encoder));
+                }
+                streams.push(shared.take(audio, "asplit"));
+                entries.push(specifier);
+                audio_count += 1;
+            }
+            if entries.is_empty() {
+                return Err(format!("HLS rendition #{} has no streams", i));
+            }
+            if let Some(name) = &rendition.name {
+                entries.push(format!("name:{}", name));
+            }
+            var_stream_map.push(entries.join(","));
+        }
+
+        let mut kwargs: Kwargs = crate::kwargs! {"f" => "hls"};
+        if let Some(hls_time) = self.hls_time {
+            kwargs.push(("hls_time".to_owned(), hls_time.format_seconds()));
+        }
+        if let Some(playlist_type) = self.playlist_type {
+            kwargs.push((
+                "hls_playlist_type".to_owned(),
+                playlist_type.as_str().to_owned(),
+            ));
+        }
+        if let Some(template) = &self.segment_filename {
+            kwargs.push(("hls_segment_filename".to_owned(), template.clone()));
+        }
+        if let Some(name) = &self.master_pl_name {
+            kwargs.push(("master_pl_name".to_owned(), name.clone()));
+        }
+        kwargs.push(("var_stream_map".to_owned(), var_stream_map.join(" ")));
+        kwargs.extend(self.kwargs.iter().cloned());
+
+        let mut node = output_node(streams, &self.filename, kwargs);
+        for (specifier, encoder) in encoders {
+            node = node.encoder(&specifier, encoder.clone())?;
+        }
+        Ok(node)
+    }
+
+    pub fn stream(&self) -> Result<OutputStream, String> {
+        Ok(self.node()?.stream("", None))
+    }
+}
@@ -0,0 +1,250 @@
+use std::fmt;
+
+use crate::daq::Kwargs;
+use crate::ffmpeg::{input_node, output_node};
+use crate::node::{FilterableStream, InputNode, OutputNode, OutputStream};
+use crate::utils::escape_chars;
+
+/*
+Image sequence inputs and outputs (the ``image2`` demuxer and muxer).
+
+    ``ImagePattern`` builds file patterns from their parts so a literal ``%`` in a
+    directory or prefix cannot be taken for the frame number:
+
+        ImagePattern::numbered("renders/100%", "frame_", 5, "png")  =>  renders/100%%/frame_%05d.png
+        ImagePattern::glob("shots [final]", "shot_*.jpg")            =>  shots \[final\]/shot_*.jpg
+
+    Inputs read a pattern at a frame rate; outputs write one file per frame, at
+    most ``frames`` of them, optionally sampled down with the ``fps`` filter, or
+    keep overwriting a single file with ``-update 1``.
+*/
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ImagePattern {
+    // A `%d` style pattern, read with the default `sequence` pattern type.
+    Sequence(String),
+    // A glob(3) pattern, read with `-pattern_type glob`.
+    Glob(String),
+}
+
+impl ImagePattern {
+    // `{dir}/{prefix}%0{digits}d.{extension}`; `%` in the other parts is escaped.
+    pub fn numbered(dir: &str, prefix: &str, digits: u8, extension: &str) -> ImagePattern {
+        let number = if digits > 1 {
+            format!("%0{}d", digits)
+        } else {
+            "%d".to_owned()
+        };
+        let name = format!(
+            "{}{}.{}",
+            escape_percent(prefix),
+            number,
+            escape_percent(extension)
+        );
+        ImagePattern::Sequence(join(&escape_percent(dir), &name))
+    }
+
+    // `{dir}/{glob}`; glob metacharacters in `dir` are escaped.
+    pub fn glob(dir: &str, glob: &str) -> ImagePattern {
+        ImagePattern::Glob(join(&escape_chars(dir, "*?[]{}"), glob))
+    }
+
+    // A hand written `%d` pattern, rejected unless it has exactly one frame number
+    // and every other `%` is escaped as `%%`.
+    pub fn parse(pattern: &str) -> Result<ImagePattern, String> {
+        let mut numbers = 0;
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                continue;
+            }
+            let mut spec: String = String::new();
+            for c in chars.by_ref() {
+                spec.push(c);
+                if !c.is_ascii_digit() {
+                    break;
+                }
+            }
+            match spec.as_str() {
+                "%" => {}
+                _ if spec.ends_with('d') => numbers += 1,
+                _ => {
+                    return Err(format!(
+                        "Unescaped `%` in image pattern {:?}; use `%%` for a literal `%`",
+                        pattern
+                    ))
+                }
+            }
+        }
+        if numbers != 1 {
+            return Err(format!(
+                "Image pattern {:?} needs exactly one frame number such as `%05d`, found {}",
+                pattern, numbers
+            ));
+        }
+        Ok(ImagePattern::Sequence(pattern.to_owned()))
+    }
+
+    // The file name of frame `number` of a `%d` pattern, e.g. `thumbs/007.jpg`.
+    // Glob patterns have no numbered file names.
+    pub fn filename(&self, number: u64) -> Option<String> {
+        let pattern = match self {
+            ImagePattern::Sequence(pattern) => pattern,
+            ImagePattern::Glob(_) => return None,
+        };
+        let mut filename = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                filename.push(c);
+                continue;
+            }
+            let mut width = String::new();
+            for c in chars.by_ref() {
+                match c {
+                    '%' => filename.push('%'),
+                    'd' => {
+                        let width: usize = width.parse().unwrap_or(0);
+                        filename.push_str(&format!("{:0width$}", number, width = width));
+                    }
+                    _ => {
+                        width.push(c);
+                        continue;
+                    }
+                }
+                break;
+            }
+        }
+        Some(filename)
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ImagePattern::Sequence(pattern) | ImagePattern::Glob(pattern) => pattern,
+        }
+    }
+}
+
+impl fmt::Display for ImagePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone)]
+pub struct ImageSequenceInput {
+    pattern: ImagePattern,
+    kwargs: Kwargs,
+}
+
+impl ImageSequenceInput {
+    pub fn new(pattern: ImagePattern) -> ImageSequenceInput {
+        ImageSequenceInput {
+            pattern,
+            kwargs: Kwargs::new(),
+        }
+    }
+
+    // Frames per second, e.g. `25` or `30000/1001`.
+    pub fn framerate(self, framerate: impl ToString) -> Self {
+        self.arg("framerate", framerate)
+    }
+
+    // The first frame number of a `%d` pattern, if not 0 to 4.
+    pub fn start_number(self, start_number: u32) -> Self {
+        self.arg("start_number", start_number)
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn node(&self) -> InputNode {
+        let mut kwargs = self.kwargs.clone();
+        if let ImagePattern::Glob(_) = self.pattern {
+            kwargs.push(("pattern_type".to_owned(), "glob".to_owned()));
+        }
+        input_node(self.pattern.as_str(), kwargs)
+    }
+
+    pub fn stream(&self) -> FilterableStream {
+        self.node().stream("", None)
+    }
+}
+
+#[derive(Clone)]
+pub struct ImageSequenceOutput {
+    stream: FilterableStream,
+    filename: String,
+    fps: Option<String>,
+    kwargs: Kwargs,
+}
+
+impl ImageSequenceOutput {
+    // One file per frame of `stream`, named after `pattern`.
+    pub fn new(stream: &FilterableStream, pattern: &ImagePattern) -> ImageSequenceOutput {
+        ImageSequenceOutput {
+            stream: stream.clone(),
+            filename: pattern.to_string(),
+            fps: None,
+            kwargs: Kwargs::new(),
+        }
+    }
+
+    // A single file, overwritten with every frame (`-update 1`), e.g. for a live
+    // preview or, with `frames(1)`, a poster frame.
+    pub fn single(stream: &FilterableStream, filename: &str) -> ImageSequenceOutput {
+        ImageSequenceOutput {
+            stream: stream.clone(),
+            filename: filename.to_owned(),
+            fps: None,
+            kwargs: crate::kwargs! {"update" => 1},
+        }
+    }
+
+    // Samples the stream at `fps` frames per second (e.g. `1`, or `1/60` for one
+    // frame a minute) with the `fps` filter.
+    pub fn fps(mut self, fps: impl ToString) -> Self {
+        self.fps = Some(fps.to_string());
+        self
+    }
+
+    // Stops after `frames` frames (`-frames:v`).
+    pub fn frames(self, frames: u64) -> Self {
+        self.arg("frames:v", frames)
+    }
+
+    pub fn start_number(self, start_number: u32) -> Self {
+        self.arg("start_number", start_number)
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn node(&self) -> OutputNode {
+        let stream = match &self.fps {
+            Some(fps) => self.stream.filter("fps", crate::kwargs! {"fps" => fps}),
+            None => self.stream.clone(),
+        };
+        output_node(&stream, &self.filename, self.kwargs.clone())
+    }
+
+    pub fn stream(&self) -> OutputStream {
+        self.node().stream("", None)
+    }
+}
+
+fn join(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}/{}", dir.trim_end_matches('/'), name)
+    }
+}
+
+fn escape_percent(text: &str) -> String {
+    text.replace('%', "%%")
+}
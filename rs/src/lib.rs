@@ -0,0 +1,42 @@
+pub mod analysis;
+pub mod capabilities;
+pub mod catalog;
+pub mod codegen;
+pub mod concat;
+pub mod daq;
+pub mod dash;
+pub mod encoder;
+pub mod expr;
+pub mod ffmpeg;
+#[rustfmt::skip]
+pub mod filters;
+pub mod graph;
+pub mod hls;
+pub mod image_sequence;
+pub mod loudnorm;
+pub mod metadata;
+pub mod node;
+pub mod optimize;
+pub mod pipe;
+#[cfg(feature = "serde")]
+pub mod pipeline;
+pub mod segment;
+pub mod subtitles;
+pub mod tee;
+pub mod thumbnails;
+pub mod time;
+pub mod timeline;
+pub mod twopass;
+mod utils;
+pub mod validate;
+
+// Builds `Kwargs` from `key => value` pairs, keeping their order.
+#[macro_export]
+macro_rules! kwargs {
+    () => {
+        $crate::daq::Kwargs::new()
+    };
+    ($($key:expr => $value:expr),+ $(,)?) => {
+        vec![$(($key.to_string(), $value.to_string())),+]
+    };
+}
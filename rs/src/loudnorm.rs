@@ -0,0 +1,60 @@
+use std::error::Error;
+
+use crate::analysis::{audio_of, loudnorm, LoudnormMeasurement, LoudnormTarget};
+use crate::node::{FilterableStream, OutputStream};
+
+/*
+Two-pass loudness normalization with ``loudnorm``.
+
+    The first pass measures the input (``analysis::loudnorm``); the second pass
+    feeds the measurement back with ``linear=true``, so the whole input gets one
+    gain change instead of dynamic compression:
+
+        let output = normalize("ffmpeg", &input, LoudnormTarget::default(), |audio| {
+            audio.output("episode.m4a", kwargs! {"c:a" => "aac", "ar" => 48000})
+        })?;
+        output.compile_command("ffmpeg", true)?.command().status()?;
+
+    ``loudnorm`` resamples to 192 kHz internally and outputs that rate, so the
+    output usually wants an explicit ``ar``.  When the measurement does not
+    allow a linear gain within the true peak target, ffmpeg itself falls back to
+    dynamic mode.
+*/
+
+// `input` normalized to `target` using a first pass `measurement`; the audio is
+// selected when `input` is a whole input, other streams are taken as they are.
+pub fn second_pass(
+    input: &FilterableStream,
+    target: LoudnormTarget,
+    measurement: &LoudnormMeasurement,
+) -> Result<FilterableStream, String> {
+    if !measurement.input_i.is_finite() || !measurement.input_thresh.is_finite() {
+        return Err("Input is silent; its loudness cannot be normalized".to_owned());
+    }
+    Ok(audio_of(input).filter(
+        "loudnorm",
+        crate::kwargs! {
+            "I" => target.integrated,
+            "TP" => target.true_peak,
+            "LRA" => target.loudness_range,
+            "measured_I" => measurement.input_i,
+            "measured_TP" => measurement.input_tp,
+            "measured_LRA" => measurement.input_lra,
+            "measured_thresh" => measurement.input_thresh,
+            "offset" => measurement.target_offset,
+            "linear" => "true",
+        },
+    ))
+}
+
+// Runs the first pass with `cmd` and returns the second pass, with the
+// normalized audio passed to `output` to build the deliverable.
+pub fn normalize(
+    cmd: &str,
+    input: &FilterableStream,
+    target: LoudnormTarget,
+    output: impl FnOnce(&FilterableStream) -> OutputStream,
+) -> Result<OutputStream, Box<dyn Error>> {
+    let measurement = loudnorm(input, target).run(cmd)?;
+    Ok(output(&second_pass(input, target, &measurement)?))
+}
@@ -1,3 +1,146 @@
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+use std::process::{self, Command};
+
+use ffmpeg_rs::catalog::FilterCatalog;
+use ffmpeg_rs::codegen;
+use ffmpeg_rs::ffmpeg;
+use ffmpeg_rs::graph::Graph;
+use ffmpeg_rs::pipeline::Pipeline;
+
+const USAGE: &str = "usage: ffmpeg-cmd [--ffmpeg PATH] [-y] [--shell | --run] PIPELINE
+       ffmpeg-cmd codegen FILTERS_DUMP [HELP_DUMP...]
+
+Compiles a pipeline file (.yaml, .yml or .toml, or a graph stored as .json) and
+prints the ffmpeg arguments one per line, or as one shell command with --shell.
+With --run, runs ffmpeg instead and exits with its status.  -y overwrites
+existing outputs.
+
+codegen generates Rust filter builders from `ffmpeg -filters` and
+`ffmpeg -h filter=NAME` dumps and prints them to stdout.";
+
+const CODEGEN_USAGE: &str = "usage: ffmpeg-cmd codegen FILTERS_DUMP [HELP_DUMP...]";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Argv,
+    Shell,
+    Run,
+}
+
+struct Options {
+    ffmpeg: String,
+    overwrite: bool,
+    mode: Mode,
+    path: String,
+}
+
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+    let result = match args[0].as_str() {
+        "codegen" if args.len() == 1 => {
+            eprintln!("{}", CODEGEN_USAGE);
+            process::exit(2);
+        }
+        "codegen" => codegen(&args[1], &args[2..]).map(|code| {
+            print!("{}", code);
+            0
+        }),
+        _ => parse_options(&args).and_then(|options| run(&options)),
+    };
+    match result {
+        Ok(code) => process::exit(code),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn parse_options(args: &[String]) -> Result<Options, Box<dyn Error>> {
+    let mut options = Options {
+        ffmpeg: "ffmpeg".to_owned(),
+        overwrite: false,
+        mode: Mode::Argv,
+        path: String::new(),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ffmpeg" => options.ffmpeg = args.next().ok_or("--ffmpeg needs a path")?.clone(),
+            "-y" => options.overwrite = true,
+            "--shell" => options.mode = Mode::Shell,
+            "--run" => options.mode = Mode::Run,
+            _ if arg.starts_with('-') => return Err(format!("Unknown option {}", arg).into()),
+            _ if options.path.is_empty() => options.path = arg.clone(),
+            _ => return Err("Only one pipeline can be compiled at a time".into()),
+        }
+    }
+    if options.path.is_empty() {
+        return Err("No pipeline file given".into());
+    }
+    Ok(options)
+}
+
+fn run(options: &Options) -> Result<i32, Box<dyn Error>> {
+    let args = load(&options.path)?.compile(&options.ffmpeg, options.overwrite)?;
+    match options.mode {
+        Mode::Argv => {
+            for arg in &args {
+                println!("{}", arg);
+            }
+            Ok(0)
+        }
+        Mode::Shell => {
+            println!("{}", ffmpeg::shell_command(&args));
+            Ok(0)
+        }
+        Mode::Run => {
+            let status = Command::new(&args[0])
+                .args(&args[1..])
+                .status()
+                .map_err(|err| format!("{}: {}", args[0], err))?;
+            Ok(status.code().unwrap_or(1))
+        }
+    }
+}
+
+fn load(path: &str) -> Result<Graph, Box<dyn Error>> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let extension = Path::new(path)
+        .extension()
+        .and_then(OsStr::to_str)
+        .unwrap_or_default();
+    let in_file = |err: &dyn Error| format!("{}: {}", path, err);
+    let pipeline: Pipeline = match extension {
+        "yaml" | "yml" => serde_yaml_ng::from_str(&text).map_err(|err| in_file(&err))?,
+        "toml" => toml::from_str(&text).map_err(|err| in_file(&err))?,
+        "json" => return Ok(serde_json::from_str(&text).map_err(|err| in_file(&err))?),
+        _ => {
+            return Err(format!(
+                "{}: unknown pipeline format; expected .yaml, .yml, .toml or .json",
+                path
+            )
+            .into())
+        }
+    };
+    pipeline.graph().map_err(|err| in_file(err.as_ref()).into())
+}
+
+fn codegen(filters_path: &str, help_paths: &[String]) -> Result<String, Box<dyn Error>> {
+    let filters =
+        fs::read_to_string(filters_path).map_err(|err| format!("{}: {}", filters_path, err))?;
+    let helps = help_paths
+        .iter()
+        .map(|path| fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err)))
+        .collect::<Result<Vec<String>, String>>()?;
+    let helps: Vec<&str> = helps.iter().map(String::as_str).collect();
+    let catalog = FilterCatalog::from_dumps(&filters, &helps)?;
+    Ok(codegen::generate(&catalog))
 }
@@ -0,0 +1,125 @@
+use std::io;
+
+use crate::daq::Kwargs;
+use crate::node::InputNode;
+use crate::time::Timestamp;
+use crate::utils::{escape_chars, TempFile};
+
+/*
+FFmetadata files with global tags and chapters.
+
+    ``FfMetadata`` renders the format read by the ``ffmetadata`` demuxer, with
+    chapter times in microseconds:
+
+        ;FFMETADATA1
+        title=Episode 12
+        artist=The Show
+
+        [CHAPTER]
+        TIMEBASE=1/1000000
+        START=0
+        END=90000000
+        title=Intro
+
+    ``FfMetadata::input`` writes it to a temporary file; passing the input to
+    ``OutputNode::metadata_input`` adds it to the command as
+    ``-map_metadata N -map_chapters N`` without mapping any streams.  Tags for
+    single outputs are simpler set with ``OutputNode::metadata``.
+*/
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Chapter {
+    start: Timestamp,
+    end: Timestamp,
+    title: String,
+    tags: Kwargs,
+}
+
+impl Chapter {
+    pub fn new(start: Timestamp, end: Timestamp, title: &str) -> Chapter {
+        Chapter {
+            start,
+            end,
+            title: title.to_owned(),
+            tags: Kwargs::new(),
+        }
+    }
+
+    // Any other chapter tag, e.g. `artist`.
+    pub fn tag(mut self, key: &str, value: &str) -> Self {
+        self.tags.push((key.to_owned(), value.to_owned()));
+        self
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct FfMetadata {
+    tags: Kwargs,
+    chapters: Vec<Chapter>,
+}
+
+impl FfMetadata {
+    pub fn new() -> FfMetadata {
+        FfMetadata::default()
+    }
+
+    // A global tag, e.g. `title`, `artist`, `album` or `comment`.
+    pub fn tag(mut self, key: &str, value: &str) -> Self {
+        self.tags.push((key.to_owned(), value.to_owned()));
+        self
+    }
+
+    pub fn chapter(mut self, chapter: Chapter) -> Self {
+        self.chapters.push(chapter);
+        self
+    }
+
+    // Chapters must be in order and must not overlap.
+    pub fn render(&self) -> Result<String, String> {
+        let mut script = String::from(";FFMETADATA1\n");
+        push_tags(&mut script, &self.tags);
+        for (i, chapter) in self.chapters.iter().enumerate() {
+            if chapter.end <= chapter.start {
+                return Err(format!("Chapter #{} ends before it starts", i));
+            }
+            if i > 0 && chapter.start < self.chapters[i - 1].end {
+                return Err(format!(
+                    "Chapter #{} starts before chapter #{} ends",
+                    i,
+                    i - 1
+                ));
+            }
+            script.push_str("\n[CHAPTER]\nTIMEBASE=1/1000000\n");
+            script.push_str(&format!("START={}\n", chapter.start.as_micros()));
+            script.push_str(&format!("END={}\n", chapter.end.as_micros()));
+            let mut tags = crate::kwargs! {"title" => chapter.title};
+            tags.extend(chapter.tags.iter().cloned());
+            push_tags(&mut script, &tags);
+        }
+        Ok(script)
+    }
+
+    // Writes the file to a temporary file and reads it with `-f ffmetadata`.
+    pub fn input(&self) -> io::Result<InputNode> {
+        let script = self
+            .render()
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        let temp_file = TempFile::create(".ffmeta", &script)?;
+        let kwargs = crate::kwargs! {
+            "f" => "ffmetadata",
+            "filename" => temp_file.path().to_string_lossy(),
+        };
+        Ok(InputNode::with_temp_file("input", kwargs, temp_file))
+    }
+}
+
+// `=`, `;`, `#`, `\` and newlines are special in both keys and values.
+fn push_tags(script: &mut String, tags: &Kwargs) {
+    for (key, value) in tags {
+        script.push_str(&format!(
+            "{}={}\n",
+            escape_chars(key, "=;#\n"),
+            escape_chars(value, "=;#\n")
+        ));
+    }
+}
@@ -1,242 +1,612 @@
-use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
-
-#[derive(PartialEq, Eq, Hash)]
-enum STREAM_TYPE {
-    FILTERABLE_STREAM,
-    OUTPUT_STREAM,
+use std::error::Error;
+use std::ops::Deref;
+use std::rc::Rc;
+
+use crate::daq::{
+    get_hash_code, DagNode, IncomingEdge, IncomingEdgeMap, KwargReprNode, Kwargs, Label, Selector,
+};
+use crate::encoder::Encoder;
+use crate::ffmpeg::{compile, compile_command, filter, output, CompiledCommand};
+use crate::pipe::PipeEnd;
+use crate::time::{Duration, Timestamp};
+use crate::utils::{escape_chars, TempFile};
+
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum NodeType {
+    Input,
+    Filter,
+    Output,
 }
 
-#[derive(PartialEq, Eq, Hash)]
-enum NODE_TYPE {
-    INPUT,
-    FILTER,
-    OUTPUT,
+#[derive(Clone)]
+pub struct Node {
+    repr: Rc<KwargReprNode<Node>>,
+    node_type: NodeType,
+    // A generated file the node refers to (e.g. a concat list), kept alive by
+    // every clone of the node and by commands compiled from it.
+    temp_file: Option<Rc<TempFile>>,
+    // A reader or writer on the other end of the node's `pipe:N`.  Every pipe is
+    // its own node, even with the same options as another.
+    pipe: Option<Rc<PipeEnd>>,
 }
 
-struct Node {
-  incoming_edge_map: IncomingEdgeMap,
-  name: String,
-  incoming_stream_type: Option<STREAM_TYPE>,
-  outgoing_stream_type: STREAM_TYPE,
-  kwargs: HashMap<String, String>,
+impl DagNode for Node {
+    fn hash(&self) -> u64 {
+        match &self.pipe {
+            Some(pipe) => self.repr.hash().wrapping_add(pipe.id()),
+            None => self.repr.hash(),
+        }
+    }
+
+    fn get_incoming_edge_map(&self) -> &IncomingEdgeMap<Self> {
+        self.repr.get_incoming_edge_map()
+    }
 }
 
 impl Node {
-  fn new(
-      incoming_edge_map: IncomingEdgeMap,
-      name: String,
-      incoming_stream_type: Option<STREAM_TYPE>,
-      outgoing_stream_type: STREAM_TYPE,
-      kwargs: HashMap<String, String>,
-  ) -> Node {
-      Node {
-          incoming_edge_map,
-          name,
-          incoming_stream_type,
-          outgoing_stream_type,
-          kwargs,
-      }
-  }
-
-  fn stream(&self, label: Label, upstream_selector: Selector) -> Stream {
-      match self.outgoing_stream_type {
-          STREAM_TYPE::FILTERABLE_STREAM => {
-              Stream::new_filterable_stream(self, label, upstream_selector)
-          }
-          STREAM_TYPE::OUTPUT_STREAM => {
-              Stream::new_output_stream(self, label, upstream_selector)
-          }
-      }
-  }
-}
-
-struct InputNode {
-  node: Node,
+    pub(crate) fn new(
+        incoming_edge_map: IncomingEdgeMap<Node>,
+        name: &str,
+        node_type: NodeType,
+        kwargs: Kwargs,
+    ) -> Node {
+        Node {
+            repr: Rc::new(KwargReprNode::new(
+                incoming_edge_map,
+                name.to_owned(),
+                kwargs,
+            )),
+            node_type,
+            temp_file: None,
+            pipe: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.repr.name
+    }
+
+    pub fn kwargs(&self) -> &Kwargs {
+        &self.repr.kwargs
+    }
+
+    pub fn node_type(&self) -> NodeType {
+        self.node_type
+    }
+
+    pub fn as_input(&self) -> Option<InputNode> {
+        (self.node_type == NodeType::Input).then(|| InputNode { node: self.clone() })
+    }
+
+    pub fn as_filter(&self) -> Option<FilterNode> {
+        (self.node_type == NodeType::Filter).then(|| FilterNode { node: self.clone() })
+    }
+
+    pub fn as_output(&self) -> Option<OutputNode> {
+        (self.node_type == NodeType::Output).then(|| OutputNode { node: self.clone() })
+    }
+
+    pub fn stream(&self, label: &str, upstream_selector: Selector) -> Stream {
+        Stream::new(self.clone(), label.to_owned(), upstream_selector)
+    }
+
+    // Nodes are immutable, so setting options builds a new node with the same
+    // incoming edges.  Existing values for the same keys are replaced in place.
+    fn with_kwargs(&self, new_kwargs: Kwargs) -> Node {
+        let mut kwargs = self.kwargs().clone();
+        for (key, value) in new_kwargs {
+            match kwargs.iter_mut().find(|(existing, _)| *existing == key) {
+                Some((_, existing)) => *existing = value,
+                None => kwargs.push((key, value)),
+            }
+        }
+        self.rebuild(self.get_incoming_edge_map().clone(), kwargs)
+    }
+
+    fn rebuild(&self, incoming_edge_map: IncomingEdgeMap<Node>, kwargs: Kwargs) -> Node {
+        Node {
+            temp_file: self.temp_file.clone(),
+            pipe: self.pipe.clone(),
+            ..Node::new(incoming_edge_map, self.name(), self.node_type, kwargs)
+        }
+    }
+
+    pub(crate) fn temp_file(&self) -> Option<&Rc<TempFile>> {
+        self.temp_file.as_ref()
+    }
+
+    pub(crate) fn pipe(&self) -> Option<&Rc<PipeEnd>> {
+        self.pipe.as_ref()
+    }
+}
+
+#[derive(Clone)]
+pub struct InputNode {
+    node: Node,
 }
 
 impl InputNode {
-  fn new(name: String, args: Vec<String>, kwargs: HashMap<String, String>) -> InputNode {
-      InputNode {
-          node: Node::new(
-              HashMap::new(),
-              name,
-              None,
-              STREAM_TYPE::FILTERABLE_STREAM,
-              kwargs,
-          ),
-      }
-  }
+    pub fn new(name: &str, kwargs: Kwargs) -> InputNode {
+        InputNode {
+            node: Node::new(IncomingEdgeMap::new(), name, NodeType::Input, kwargs),
+        }
+    }
 
-  fn stream(&self, label: Label, upstream_selector: Selector) -> FilterableStream {
-      FilterableStream::new(&self.node, label, upstream_selector)
-  }
+    // An input reading `temp_file`, which is removed once neither the node nor a
+    // command compiled from it is in use.
+    pub(crate) fn with_temp_file(name: &str, kwargs: Kwargs, temp_file: TempFile) -> InputNode {
+        let mut input = InputNode::new(name, kwargs);
+        input.node.temp_file = Some(Rc::new(temp_file));
+        input
+    }
+
+    pub(crate) fn with_pipe(name: &str, kwargs: Kwargs, pipe: PipeEnd) -> InputNode {
+        let mut input = InputNode::new(name, kwargs);
+        input.node.pipe = Some(Rc::new(pipe));
+        input
+    }
+
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    pub fn stream(&self, label: &str, upstream_selector: Selector) -> FilterableStream {
+        FilterableStream(self.node.stream(label, upstream_selector))
+    }
+
+    // `-ss`: seeks the input to `position` before decoding.
+    pub fn seek(&self, position: Timestamp) -> InputNode {
+        InputNode {
+            node: self.node.with_kwargs(crate::kwargs! {"ss" => position}),
+        }
+    }
+
+    // `-t`: reads at most `duration` of the input.
+    pub fn duration(&self, duration: Duration) -> InputNode {
+        InputNode {
+            node: self.node.with_kwargs(crate::kwargs! {"t" => duration}),
+        }
+    }
+
+    // `-to`: stops reading the input at `position`.
+    pub fn to(&self, position: Timestamp) -> InputNode {
+        InputNode {
+            node: self.node.with_kwargs(crate::kwargs! {"to" => position}),
+        }
+    }
 }
 
-struct FilterNode {
-  node: Node,
+#[derive(Clone)]
+pub struct FilterNode {
+    node: Node,
 }
 
 impl FilterNode {
-  fn new(stream_spec: &StreamSpec, name: String, kwargs: HashMap<String, String>) -> FilterNode {
-      FilterNode {
-          node: Node::new(
-              get_incoming_edge_map(get_stream_map(stream_spec)),
-              name,
-              Some(STREAM_TYPE::FILTERABLE_STREAM),
-              STREAM_TYPE::FILTERABLE_STREAM,
-              kwargs,
-          ),
-      }
-  }
-
-  fn stream(&self, label: Label, upstream_selector: Selector) -> FilterableStream {
-      FilterableStream::new(&self.node, label, upstream_selector)
-  }
-
-  fn get_filter(&self) -> String {
-      let filter_name = &self.node.name;
-      let kwargs = &self.node.kwargs;
-      let filter_params: Vec<String> = kwargs
-          .iter()
-          .map(|(key, value)| format!("{}={}", key, value))
-          .collect();
-      if filter_params.is_empty() {
-          filter_name.to_string()
-      } else {
-          format!("{}={}", filter_name, filter_params.join(":"))
-      }
-  }
-}
-
-pub struct OutputNode<'a> {
+    pub fn new(stream_spec: StreamSpec, name: &str, kwargs: Kwargs) -> FilterNode {
+        FilterNode {
+            node: Node::new(
+                get_incoming_edge_map(&get_stream_map(stream_spec)),
+                name,
+                NodeType::Filter,
+                kwargs,
+            ),
+        }
+    }
+
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    pub fn stream(&self, label: &str, upstream_selector: Selector) -> FilterableStream {
+        FilterableStream(self.node.stream(label, upstream_selector))
+    }
+
+    // Option values are escaped twice: once for the filter's option parser and once
+    // for the filtergraph parser, so expressions like `if(gt(t,2),10,20)` survive.
+    pub fn get_filter(&self) -> String {
+        let filter_name = self.node.name();
+        let filter_params: Vec<String> = self
+            .node
+            .kwargs()
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "{}={}",
+                    escape_chars(key, "\'=:"),
+                    escape_chars(value, "\':")
+                )
+            })
+            .collect();
+        if filter_params.is_empty() {
+            filter_name.to_owned()
+        } else {
+            format!(
+                "{}={}",
+                filter_name,
+                escape_chars(&filter_params.join(":"), "\'[],;")
+            )
+        }
+    }
+}
+
+// The downstream label of the edge from an output to its `metadata_input`.
+pub(crate) const METADATA_LABEL: &str = "metadata";
+
+#[derive(Clone)]
+pub struct OutputNode {
     node: Node,
-    pub streams: Vec<OutputStream<'a>>,
 }
 
-impl<'a> OutputNode<'a> {
-    pub fn new(stream: &Stream, name: &str, kwargs: HashMap<String, String>) -> OutputNode {
-        let stream_map = get_stream_map(stream);
-        let incoming_edge_map = get_incoming_edge_map(&stream_map);
-        let node = Node::new(&incoming_edge_map, name, STREAM_TYPE_FILTERABLE_STREAM, STREAM_TYPE_OUTPUT_STREAM, kwargs);
-        let streams = stream_map.iter().map(|(label, stream)| {
-            OutputStream::new(&node, label.clone(), stream.selector.clone())
-        }).collect();
+impl OutputNode {
+    pub fn new(stream_spec: StreamSpec, name: &str, kwargs: Kwargs) -> OutputNode {
+        OutputNode {
+            node: Node::new(
+                get_incoming_edge_map(&get_stream_map(stream_spec)),
+                name,
+                NodeType::Output,
+                kwargs,
+            ),
+        }
+    }
+
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
 
+    pub(crate) fn with_pipe(mut self, pipe: PipeEnd) -> OutputNode {
+        self.node.pipe = Some(Rc::new(pipe));
+        self
+    }
+
+    pub fn stream(&self, label: &str, upstream_selector: Selector) -> OutputStream {
+        OutputStream(self.node.stream(label, upstream_selector))
+    }
+
+    // `-ss` as an output option: decodes and discards everything before `position`.
+    pub fn seek(&self, position: Timestamp) -> OutputNode {
         OutputNode {
-            node,
-            streams,
+            node: self.node.with_kwargs(crate::kwargs! {"ss" => position}),
+        }
+    }
+
+    // A copy of this output with `kwargs` added; existing keys, including
+    // `filename`, are replaced.
+    pub fn with_kwargs(&self, kwargs: Kwargs) -> OutputNode {
+        OutputNode {
+            node: self.node.with_kwargs(kwargs),
+        }
+    }
+
+    // Encodes the streams matched by `stream_specifier` (`v`, `a:0`, ...) with
+    // `encoder`.  Options already set under the same keys are replaced, and the
+    // ones a previous encoder set for the specifier but this one does not (such
+    // as `crf` after switching to a bitrate) are removed.
+    pub fn encoder(
+        &self,
+        stream_specifier: &str,
+        encoder: impl Into<Encoder>,
+    ) -> Result<OutputNode, String> {
+        let new_kwargs = encoder.into().get_args(stream_specifier)?;
+        let stale = Encoder::option_keys(stream_specifier);
+        let mut kwargs = self.node.kwargs().clone();
+        kwargs.retain(|(key, _)| {
+            !stale.contains(key) || new_kwargs.iter().any(|(new_key, _)| new_key == key)
+        });
+        let node = self
+            .node
+            .rebuild(self.node.get_incoming_edge_map().clone(), kwargs);
+        Ok(OutputNode {
+            node: node.with_kwargs(new_kwargs),
+        })
+    }
+
+    // `-metadata key=value`; setting a key again replaces its value.
+    pub fn metadata(&self, key: &str, value: &str) -> OutputNode {
+        self.with_metadata("metadata", key, value)
+    }
+
+    // `-metadata:s:{stream_specifier} key=value`, e.g. `("a:0", "language", "eng")`.
+    pub fn stream_metadata(&self, stream_specifier: &str, key: &str, value: &str) -> OutputNode {
+        self.with_metadata(&format!("metadata:s:{}", stream_specifier), key, value)
+    }
+
+    // `-map_metadata`: where global metadata comes from, e.g. `"1"` for the second
+    // input or `"-1"` to drop it.
+    pub fn map_metadata(&self, source: &str) -> OutputNode {
+        self.with_kwargs(crate::kwargs! {"map_metadata" => source})
+    }
+
+    // `-map_chapters`: the input to copy chapters from, or `-1` to drop them.
+    pub fn map_chapters(&self, input_index: i32) -> OutputNode {
+        self.with_kwargs(crate::kwargs! {"map_chapters" => input_index})
+    }
+
+    // Takes global metadata and chapters from `input`, usually an ffmetadata file
+    // from `metadata::FfMetadata`.  The input is added to the command without
+    // mapping any of its streams, and referred to by its index once compiled.
+    // `map_metadata` or `map_chapters` set on the output take precedence.
+    pub fn metadata_input(&self, input: &InputNode) -> OutputNode {
+        let mut incoming_edge_map = self.node.get_incoming_edge_map().clone();
+        incoming_edge_map.retain(|(label, _)| label != METADATA_LABEL);
+        incoming_edge_map.push((
+            METADATA_LABEL.to_owned(),
+            IncomingEdge {
+                upstream_node: input.node.clone(),
+                upstream_label: String::new(),
+                upstream_selector: None,
+            },
+        ));
+        OutputNode {
+            node: self
+                .node
+                .rebuild(incoming_edge_map, self.node.kwargs().clone()),
         }
     }
 
-    pub fn stream(&self, label: &str, upstream_selector: Option<&Selector>) -> OutputStream {
-        let upstream_selector = upstream_selector.cloned();
-        OutputStream::new(&self.node, label.to_string(), upstream_selector)
+    fn with_metadata(&self, option: &str, key: &str, value: &str) -> OutputNode {
+        let prefix = format!("{}=", key);
+        let entry = format!("{}{}", prefix, value);
+        let mut kwargs = self.node.kwargs().clone();
+        match kwargs
+            .iter_mut()
+            .find(|(existing, value)| existing == option && value.starts_with(&prefix))
+        {
+            Some((_, existing)) => *existing = entry,
+            None => kwargs.push((option.to_owned(), entry)),
+        }
+        OutputNode {
+            node: self
+                .node
+                .rebuild(self.node.get_incoming_edge_map().clone(), kwargs),
+        }
     }
 }
 
-#[derive(PartialEq, Eq, Hash)]
-struct Stream {
-  node: Node,
-  label: Label,
-  selector: Selector,
-  hash: u64,
+#[derive(Clone)]
+pub struct Stream {
+    pub node: Node,
+    pub label: Label,
+    pub selector: Selector,
+    hash: u64,
 }
 
 impl Stream {
     pub fn new(node: Node, label: Label, selector: Selector) -> Self {
-        let mut hash = node.hash();
-        hash += label.as_bytes().iter().fold(0, |acc, x| acc + (*x as u64));
-        Self { node, label, selector, hash }
+        let hash = node.hash().wrapping_add(get_hash_code(&label));
+        Self {
+            node,
+            label,
+            selector,
+            hash,
+        }
     }
 
-    pub fn eq(&self, other: &Self) -> bool {
-        self.hash == other.hash
+    pub fn hash(&self) -> u64 {
+        self.hash
     }
 
-    pub fn get(&self, item: &'static str) -> Result<Stream, String> {
+    pub fn get(&self, item: char) -> Result<Stream, String> {
         if self.selector.is_some() {
-            return Err("Already selected".to_string());
+            return Err("Already selected".to_owned());
         }
 
         match item {
-            "a" => Ok(self.node.stream(&self.label, "a")),
-            "v" => Ok(self.node.stream(&self.label, "v")),
+            'a' | 'v' | 's' => Ok(self.node.stream(&self.label, Some(item))),
             _ => Err(format!("Invalid item: {}", item)),
         }
     }
 
-    pub fn audio(&self) -> Result<Stream, String> {
-        self.get("a")
+    pub fn audio(&self) -> Stream {
+        self.node.stream(&self.label, Some('a'))
+    }
+
+    pub fn video(&self) -> Stream {
+        self.node.stream(&self.label, Some('v'))
     }
 
-    pub fn video(&self) -> Result<Stream, String> {
-        self.get("v")
+    // Subtitle streams can only be mapped to outputs, or burnt in with
+    // `subtitles::BurnIn`.
+    pub fn subtitle(&self) -> Stream {
+        self.node.stream(&self.label, Some('s'))
     }
 }
 
+impl PartialEq for Stream {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+    }
+}
+
+impl Eq for Stream {}
+
+#[derive(Clone)]
 pub struct FilterableStream(Stream);
 
+impl Deref for FilterableStream {
+    type Target = Stream;
+
+    fn deref(&self) -> &Stream {
+        &self.0
+    }
+}
+
 impl FilterableStream {
-    pub fn new(stream: Stream) -> Self {
-        Self(stream)
+    pub fn get(&self, item: char) -> Result<FilterableStream, String> {
+        self.0.get(item).map(FilterableStream)
+    }
+
+    pub fn audio(&self) -> FilterableStream {
+        FilterableStream(self.0.audio())
+    }
+
+    pub fn video(&self) -> FilterableStream {
+        FilterableStream(self.0.video())
+    }
+
+    pub fn subtitle(&self) -> FilterableStream {
+        FilterableStream(self.0.subtitle())
     }
 
-    pub fn output(&self, filename: &str, kwargs: HashMap<String, String>) -> Result<(), String> {
-        output(&self.0, filename, kwargs);
-        Ok(())
+    pub fn output(&self, filename: &str, kwargs: Kwargs) -> OutputStream {
+        output(self, filename, kwargs)
     }
 
-    pub fn filter(&self, filter_name: &str, kwargs: HashMap<String, String>) -> Result<Stream, String> {
-        filter(&self.0, filter_name, kwargs)
+    pub fn filter(&self, filter_name: &str, kwargs: Kwargs) -> FilterableStream {
+        filter(self, filter_name, kwargs)
+    }
+
+    // Keeps the part between `start` and `end`, with `atrim` for audio selected
+    // streams.  Other streams are taken as video; audio filter outputs need
+    // `atrim`.  Times are passed in seconds so they need no filtergraph escaping.
+    pub fn trim(&self, start: Timestamp, end: Timestamp) -> FilterableStream {
+        if self.selector == Some('a') {
+            self.atrim(start, end)
+        } else {
+            self.trim_with("trim", start, end)
+        }
+    }
+
+    // `trim` for audio streams, e.g. the output of `amix`.
+    pub fn atrim(&self, start: Timestamp, end: Timestamp) -> FilterableStream {
+        self.trim_with("atrim", start, end)
+    }
+
+    fn trim_with(&self, filter_name: &str, start: Timestamp, end: Timestamp) -> FilterableStream {
+        filter(
+            self,
+            filter_name,
+            crate::kwargs! {"start" => start.format_seconds(), "end" => end.format_seconds()},
+        )
     }
 }
 
+#[derive(Clone)]
 pub struct OutputStream(Stream);
 
+impl Deref for OutputStream {
+    type Target = Stream;
+
+    fn deref(&self) -> &Stream {
+        &self.0
+    }
+}
+
 impl OutputStream {
-    pub fn new(stream: Stream) -> Self {
-        Self(stream)
+    pub fn compile(
+        &self,
+        cmd: &str,
+        overwrite_output: bool,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        compile(self, cmd, overwrite_output)
     }
 
-    pub fn compile(&self, cmd: &str, overwrite_output: bool) -> Result<(), String> {
-        compile(&self.0, cmd, overwrite_output);
-        Ok(())
+    pub fn compile_command(
+        &self,
+        cmd: &str,
+        overwrite_output: bool,
+    ) -> Result<CompiledCommand, Box<dyn Error>> {
+        compile_command(self, cmd, overwrite_output)
     }
 }
 
-type StreamMap = HashMap<Label, Stream>;
+// One stream, or a list of streams connected to consecutive input labels.
+#[derive(Clone)]
+pub enum StreamSpec {
+    Single(Stream),
+    Multiple(Vec<Stream>),
+}
+
+impl From<Stream> for StreamSpec {
+    fn from(stream: Stream) -> Self {
+        StreamSpec::Single(stream)
+    }
+}
+
+macro_rules! impl_stream_spec_from {
+    ($($stream:ty => $into:expr),* $(,)?) => {
+        $(
+            impl From<$stream> for StreamSpec {
+                fn from(stream: $stream) -> Self {
+                    StreamSpec::Single($into(stream))
+                }
+            }
+
+            impl From<Vec<$stream>> for StreamSpec {
+                fn from(streams: Vec<$stream>) -> Self {
+                    StreamSpec::Multiple(streams.into_iter().map($into).collect())
+                }
+            }
+
+            impl From<&[$stream]> for StreamSpec {
+                fn from(streams: &[$stream]) -> Self {
+                    StreamSpec::Multiple(streams.iter().cloned().map($into).collect())
+                }
+            }
+
+            impl<const N: usize> From<[$stream; N]> for StreamSpec {
+                fn from(streams: [$stream; N]) -> Self {
+                    StreamSpec::Multiple(streams.into_iter().map($into).collect())
+                }
+            }
+        )*
+    };
+}
+
+impl_stream_spec_from! {
+    &Stream => |stream: &Stream| stream.clone(),
+    FilterableStream => |stream: FilterableStream| stream.0,
+    &FilterableStream => |stream: &FilterableStream| stream.0.clone(),
+    OutputStream => |stream: OutputStream| stream.0,
+    &OutputStream => |stream: &OutputStream| stream.0.clone(),
+}
+
+impl From<Vec<Stream>> for StreamSpec {
+    fn from(streams: Vec<Stream>) -> Self {
+        StreamSpec::Multiple(streams)
+    }
+}
+
+type StreamMap = Vec<(Label, Stream)>;
 
 fn get_stream_map_node(stream_map: &StreamMap) -> Vec<Node> {
-    stream_map.values().map(|s| s.node.clone()).collect()
+    stream_map
+        .iter()
+        .map(|(_, stream)| stream.node.clone())
+        .collect()
 }
 
-pub fn get_stream_spec_node(stream_spec: &Stream) -> Vec<Node> {
-    let stream_map = get_stream_map(stream_spec);
-    get_stream_map_node(&stream_map)
+pub fn get_stream_spec_node(stream_spec: StreamSpec) -> Vec<Node> {
+    get_stream_map_node(&get_stream_map(stream_spec))
 }
 
-fn get_stream_map(stream_spec: &Option<Box<[Stream]>>) -> StreamMap {
+fn get_stream_map(stream_spec: StreamSpec) -> StreamMap {
     match stream_spec {
-        None => StreamMap::new(),
-        Some(streams) => streams
-            .iter()
+        StreamSpec::Single(stream) => vec![(String::new(), stream)],
+        StreamSpec::Multiple(streams) => streams
+            .into_iter()
             .enumerate()
-            .map(|(i, s)| (i.to_string(), s.clone()))
+            .map(|(i, stream)| (i.to_string(), stream))
             .collect(),
     }
 }
 
-fn get_incoming_edge_map(stream_map: &StreamMap) -> IncomingEdgeMap {
-  let mut incoming_edge_map: IncomingEdgeMap = HashMap::new();
-  for (key, value) in stream_map.iter() {
-      let downstream_label = key.to_string();
-      incoming_edge_map.insert(downstream_label, IncomingEdge {
-          upstream_node: value.node,
-          upstream_label: value.label.clone(),
-          upstream_selector: value.selector.clone(),
-      });
-  }
-
-  incoming_edge_map
-}
\ No newline at end of file
+fn get_incoming_edge_map(stream_map: &StreamMap) -> IncomingEdgeMap<Node> {
+    stream_map
+        .iter()
+        .map(|(downstream_label, stream)| {
+            (
+                downstream_label.clone(),
+                IncomingEdge {
+                    upstream_node: stream.node.clone(),
+                    upstream_label: stream.label.clone(),
+                    upstream_selector: stream.selector,
+                },
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,538 @@
+use std::error::Error;
+
+use crate::catalog::{FilterCatalog, MediaType, Pads};
+use crate::graph::{Graph, GraphEdge, GraphNode};
+use crate::node::{NodeType, StreamSpec};
+
+/*
+Optimization passes over a graph.
+
+    ``Optimizer`` runs its passes over a ``Graph`` until none of them changes it.
+    Passes edit the graph's nodes and edges in place; after every change the
+    graph is rebuilt through ``Graph::stream_spec``, which puts it back in
+    ``topo_sort`` order, drops nodes nothing refers to any more and collapses
+    nodes with the same ``KwargReprNode::cal_hash``.  The built-in passes:
+
+     - ``RemoveNoOps``: drops ``null``, ``anull``, ``copy``, ``acopy``,
+       ``setpts=PTS``, ``asetpts=PTS`` and ``scale=iw:ih``, unless one fed by an
+       input goes to an output: ``-map 0:a`` would map every audio stream where
+       the filter only took the first one
+     - ``FuseScales``: merges a ``scale`` into the next one, substituting its
+       size for ``iw`` and ``ih``
+     - ``FuseSetpts``: merges a ``setpts`` into a following one that only uses
+       ``PTS``, e.g. ``PTS-STARTPTS`` then ``0.5*PTS`` into ``0.5*(PTS-STARTPTS)``
+     - ``RemoveDeadBranches``: drops the unused outputs of ``split`` and
+       ``asplit``, and the filter itself when one output is left
+     - ``SplitSharedStreams``: feeds a filter output used more than once, such as
+       identical filters on the same input once collapsed, through a ``split`` or
+       ``asplit``; it needs a ``FilterCatalog`` to tell them apart, so only
+       ``Optimizer::with_catalog`` runs it
+
+        let graph = optimize_with_catalog(outputs, &catalog)?;
+        graph.compile("ffmpeg", true)?;
+
+    Passes only rewrite what they can prove equivalent, and leave anything with
+    options they do not know alone.
+*/
+
+pub trait Pass {
+    // Rewrites `graph` and returns whether anything changed.  Nodes left without
+    // consumers are removed afterwards, so passes only need to reconnect edges.
+    fn run(&self, graph: &mut Graph) -> bool;
+}
+
+pub struct Optimizer {
+    passes: Vec<Box<dyn Pass>>,
+}
+
+// Passes changing something every round would never finish.
+const MAX_ROUNDS: usize = 32;
+
+impl Optimizer {
+    // An optimizer without passes; see `Optimizer::default` for the built-in ones.
+    pub fn new() -> Optimizer {
+        Optimizer { passes: Vec::new() }
+    }
+
+    // The built-in passes, with `SplitSharedStreams` taking output types from
+    // `catalog`.
+    pub fn with_catalog(catalog: &FilterCatalog) -> Optimizer {
+        Optimizer::default().pass(SplitSharedStreams::new(catalog.clone()))
+    }
+
+    pub fn pass(mut self, pass: impl Pass + 'static) -> Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    pub fn run(&self, graph: Graph) -> Result<Graph, Box<dyn Error>> {
+        let mut graph = normalize(graph)?;
+        for _ in 0..MAX_ROUNDS {
+            let mut changed = false;
+            for pass in &self.passes {
+                if pass.run(&mut graph) {
+                    graph = normalize(graph)?;
+                    changed = true;
+                }
+            }
+            if !changed {
+                return Ok(graph);
+            }
+        }
+        Err(format!("Optimization did not settle after {} rounds", MAX_ROUNDS).into())
+    }
+}
+
+impl Default for Optimizer {
+    fn default() -> Optimizer {
+        Optimizer::new()
+            .pass(RemoveNoOps)
+            .pass(FuseScales)
+            .pass(FuseSetpts)
+            .pass(RemoveDeadBranches)
+    }
+}
+
+// The graph behind `stream_spec` with the built-in passes applied.
+pub fn optimize(stream_spec: impl Into<StreamSpec>) -> Result<Graph, Box<dyn Error>> {
+    Optimizer::default().run(Graph::new(stream_spec)?)
+}
+
+// Like `optimize`, but also splits shared streams, telling audio from video with
+// `catalog`.
+pub fn optimize_with_catalog(
+    stream_spec: impl Into<StreamSpec>,
+    catalog: &FilterCatalog,
+) -> Result<Graph, Box<dyn Error>> {
+    Optimizer::with_catalog(catalog).run(Graph::new(stream_spec)?)
+}
+
+fn normalize(graph: Graph) -> Result<Graph, Box<dyn Error>> {
+    let mut normalized = Graph::new(graph.stream_spec()?)?;
+    normalized.globals = graph.globals;
+    Ok(normalized)
+}
+
+// Indices of the edges leaving `node`.
+pub fn outgoing_edges(graph: &Graph, node: usize) -> Vec<usize> {
+    (0..graph.edges.len())
+        .filter(|&i| graph.edges[i].from == node)
+        .collect()
+}
+
+// Indices of the edges into `node`, in the node's order.
+pub fn incoming_edges(graph: &Graph, node: usize) -> Vec<usize> {
+    (0..graph.edges.len())
+        .filter(|&i| graph.edges[i].to == node)
+        .collect()
+}
+
+// Connects whatever `node` feeds to the node's only input instead, as if every
+// output of `node` passed its input through unchanged.  Fails on nodes with
+// another number of inputs.
+pub fn bypass(graph: &mut Graph, node: usize) -> bool {
+    let [input] = incoming_edges(graph, node)[..] else {
+        return false;
+    };
+    let source = graph.edges[input].clone();
+    for edge in graph.edges.iter_mut().filter(|edge| edge.from == node) {
+        edge.from = source.from;
+        edge.from_label = source.from_label.clone();
+        edge.selector = source.selector.or(edge.selector);
+    }
+    for stream in graph
+        .streams
+        .iter_mut()
+        .filter(|stream| stream.node == node)
+    {
+        stream.node = source.from;
+        stream.label = source.from_label.clone();
+        stream.selector = source.selector.or(stream.selector);
+    }
+    true
+}
+
+// Inserts `node` at `index`, moving later nodes up by one.
+pub fn insert_node(graph: &mut Graph, index: usize, node: GraphNode) {
+    let shift = |i: &mut usize| {
+        if *i >= index {
+            *i += 1;
+        }
+    };
+    for edge in &mut graph.edges {
+        shift(&mut edge.from);
+        shift(&mut edge.to);
+    }
+    for stream in &mut graph.streams {
+        shift(&mut stream.node);
+    }
+    graph.nodes.insert(index, node);
+}
+
+fn is_filter(graph: &Graph, node: usize, names: &[&str]) -> bool {
+    let node = &graph.nodes[node];
+    node.node_type == NodeType::Filter && names.contains(&node.name.as_str())
+}
+
+fn kwarg<'a>(node: &'a GraphNode, key: &str) -> Option<&'a str> {
+    node.kwargs
+        .iter()
+        .find(|(existing, _)| existing == key)
+        .map(|(_, value)| value.as_str())
+}
+
+// Whether `node` feeds exactly one edge and is not one of the graph's streams.
+fn single_consumer(graph: &Graph, node: usize) -> Option<usize> {
+    if graph.streams.iter().any(|stream| stream.node == node) {
+        return None;
+    }
+    match outgoing_edges(graph, node)[..] {
+        [edge] => Some(edge),
+        _ => None,
+    }
+}
+
+pub struct RemoveNoOps;
+
+impl Pass for RemoveNoOps {
+    fn run(&self, graph: &mut Graph) -> bool {
+        let mut changed = false;
+        for node in 0..graph.nodes.len() {
+            if is_no_op(graph, node) && keeps_streams(graph, node) {
+                changed |= bypass(graph, node);
+            }
+        }
+        changed
+    }
+}
+
+// Whether `node`'s consumers still get the same streams once it is bypassed.  A
+// filter pad takes the first stream an input link matches, but an output maps
+// all of them.
+fn keeps_streams(graph: &Graph, node: usize) -> bool {
+    let [input] = incoming_edges(graph, node)[..] else {
+        return false;
+    };
+    if graph.nodes[graph.edges[input].from].node_type == NodeType::Filter {
+        return true;
+    }
+    !graph.streams.iter().any(|stream| stream.node == node)
+        && outgoing_edges(graph, node)
+            .iter()
+            .all(|&edge| graph.nodes[graph.edges[edge].to].node_type == NodeType::Filter)
+}
+
+fn is_no_op(graph: &Graph, node: usize) -> bool {
+    let filter = &graph.nodes[node];
+    if filter.node_type != NodeType::Filter {
+        return false;
+    }
+    let mut kwargs: Vec<(&str, &str)> = filter
+        .kwargs
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect();
+    kwargs.sort();
+    match filter.name.as_str() {
+        "null" | "anull" | "copy" | "acopy" => kwargs.is_empty(),
+        "setpts" | "asetpts" => kwargs == [("expr", "PTS")],
+        "scale" => {
+            kwargs == [("h", "ih"), ("w", "iw")] || kwargs == [("height", "ih"), ("width", "iw")]
+        }
+        _ => false,
+    }
+}
+
+pub struct FuseScales;
+
+impl Pass for FuseScales {
+    fn run(&self, graph: &mut Graph) -> bool {
+        let mut changed = false;
+        for first in 0..graph.nodes.len() {
+            let Some(edge) = single_consumer(graph, first) else {
+                continue;
+            };
+            let second = graph.edges[edge].to;
+            if !is_filter(graph, first, &["scale"]) || !is_filter(graph, second, &["scale"]) {
+                continue;
+            }
+            let (Some(first_size), Some(second_size)) = (
+                scale_size(&graph.nodes[first]),
+                scale_size(&graph.nodes[second]),
+            ) else {
+                continue;
+            };
+            // The first size may only depend on its input, which both share.
+            let input_only = ["iw", "ih", "in_w", "in_h"];
+            if !uses_only(first_size.0, &input_only) || !uses_only(first_size.1, &input_only) {
+                continue;
+            }
+            // ffmpeg truncates evaluated sizes, and takes 0 to mean the input's.
+            let as_input = |size: &str, input: &str| match size.parse::<u32>() {
+                Ok(0) => input.to_owned(),
+                Ok(_) => size.to_owned(),
+                Err(_) if input_only.contains(&size) => size.to_owned(),
+                Err(_) => format!("trunc({})", size),
+            };
+            let width = as_input(first_size.0, "iw");
+            let height = as_input(first_size.1, "ih");
+            let replacements = [
+                ("iw", width.as_str()),
+                ("in_w", width.as_str()),
+                ("ih", height.as_str()),
+                ("in_h", height.as_str()),
+            ];
+            let allowed = ["iw", "ih", "in_w", "in_h", "ow", "oh", "out_w", "out_h"];
+            let (Some(w), Some(h)) = (
+                substitute(second_size.0, &allowed, &replacements),
+                substitute(second_size.1, &allowed, &replacements),
+            ) else {
+                continue;
+            };
+            graph.nodes[second].kwargs = crate::kwargs! {"w" => w, "h" => h};
+            changed |= bypass(graph, first);
+        }
+        changed
+    }
+}
+
+// The `w` and `h` of a scale with no other options, unless either asks to keep
+// the aspect ratio (negative values), which depends on the input's size.
+fn scale_size(node: &GraphNode) -> Option<(&str, &str)> {
+    let known = ["w", "h", "width", "height"];
+    if node
+        .kwargs
+        .iter()
+        .any(|(key, _)| !known.contains(&key.as_str()))
+    {
+        return None;
+    }
+    let w = kwarg(node, "w").or(kwarg(node, "width"))?;
+    let h = kwarg(node, "h").or(kwarg(node, "height"))?;
+    (!w.starts_with('-') && !h.starts_with('-')).then_some((w, h))
+}
+
+pub struct FuseSetpts;
+
+impl Pass for FuseSetpts {
+    fn run(&self, graph: &mut Graph) -> bool {
+        let mut changed = false;
+        for first in 0..graph.nodes.len() {
+            let Some(edge) = single_consumer(graph, first) else {
+                continue;
+            };
+            let second = graph.edges[edge].to;
+            for name in ["setpts", "asetpts"] {
+                if !is_filter(graph, first, &[name]) || !is_filter(graph, second, &[name]) {
+                    continue;
+                }
+                let (Some(inner), Some(outer)) = (
+                    setpts_expr(&graph.nodes[first]),
+                    setpts_expr(&graph.nodes[second]),
+                ) else {
+                    continue;
+                };
+                // Anything but `PTS`, such as `STARTPTS` or `N`, would refer to the
+                // first filter's output rather than its input.
+                let inner = format!("({})", inner);
+                let Some(expr) = substitute(outer, &["PTS"], &[("PTS", &inner)]) else {
+                    continue;
+                };
+                graph.nodes[second].kwargs = crate::kwargs! {"expr" => expr};
+                changed |= bypass(graph, first);
+            }
+        }
+        changed
+    }
+}
+
+fn setpts_expr(node: &GraphNode) -> Option<&str> {
+    match &node.kwargs[..] {
+        [(key, expr)] if key == "expr" => Some(expr),
+        _ => None,
+    }
+}
+
+pub struct RemoveDeadBranches;
+
+impl Pass for RemoveDeadBranches {
+    fn run(&self, graph: &mut Graph) -> bool {
+        let mut changed = false;
+        for node in 0..graph.nodes.len() {
+            if !is_filter(graph, node, &["split", "asplit"]) {
+                continue;
+            }
+            let edges = outgoing_edges(graph, node);
+            let mut labels: Vec<String> = Vec::new();
+            for &edge in &edges {
+                if !labels.contains(&graph.edges[edge].from_label) {
+                    labels.push(graph.edges[edge].from_label.clone());
+                }
+            }
+            if labels.len() <= 1 {
+                changed |= bypass(graph, node);
+                continue;
+            }
+            let outputs = kwarg(&graph.nodes[node], "outputs").unwrap_or("2");
+            let numbered = labels
+                .iter()
+                .enumerate()
+                .all(|(i, label)| *label == i.to_string());
+            if numbered && outputs == labels.len().to_string() {
+                continue;
+            }
+            for edge in edges {
+                let position = labels
+                    .iter()
+                    .position(|label| *label == graph.edges[edge].from_label);
+                graph.edges[edge].from_label = position.unwrap_or_default().to_string();
+            }
+            set_outputs(&mut graph.nodes[node], labels.len());
+            changed = true;
+        }
+        changed
+    }
+}
+
+// Two outputs are the default, and left implicit.
+fn set_outputs(node: &mut GraphNode, outputs: usize) {
+    node.kwargs.retain(|(key, _)| key != "outputs");
+    if outputs != 2 {
+        node.kwargs
+            .push(("outputs".to_owned(), outputs.to_string()));
+    }
+}
+
+// Shared outputs of filters `catalog` does not know, or whose output pads depend
+// on their options, are left alone.
+pub struct SplitSharedStreams {
+    catalog: FilterCatalog,
+}
+
+impl SplitSharedStreams {
+    pub fn new(catalog: FilterCatalog) -> SplitSharedStreams {
+        SplitSharedStreams { catalog }
+    }
+}
+
+impl Pass for SplitSharedStreams {
+    fn run(&self, graph: &mut Graph) -> bool {
+        for node in 0..graph.nodes.len() {
+            if graph.nodes[node].node_type != NodeType::Filter {
+                continue;
+            }
+            let edges = outgoing_edges(graph, node);
+            for &edge in &edges {
+                let GraphEdge {
+                    from_label,
+                    selector,
+                    ..
+                } = graph.edges[edge].clone();
+                let shared: Vec<usize> = edges
+                    .iter()
+                    .copied()
+                    .filter(|&other| graph.edges[other].from_label == from_label)
+                    .collect();
+                if shared.len() < 2
+                    || shared
+                        .iter()
+                        .any(|&other| graph.edges[other].selector != selector)
+                {
+                    continue;
+                }
+                let name = match output_type(&self.catalog, &graph.nodes[node], &from_label) {
+                    Some(MediaType::Audio) => "asplit",
+                    Some(MediaType::Video) => "split",
+                    None => continue,
+                };
+                let mut split = GraphNode {
+                    node_type: NodeType::Filter,
+                    name: name.to_owned(),
+                    kwargs: Vec::new(),
+                };
+                set_outputs(&mut split, shared.len());
+                insert_node(graph, node + 1, split);
+                for (i, &other) in shared.iter().enumerate() {
+                    let other = &mut graph.edges[other];
+                    other.from = node + 1;
+                    other.from_label = i.to_string();
+                    other.selector = None;
+                }
+                graph.edges.push(GraphEdge {
+                    from: node,
+                    from_label,
+                    selector,
+                    to: node + 1,
+                    to_label: String::new(),
+                });
+                // Indices moved; the optimizer runs the pass again if needed.
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// The media type of `filter`'s output pad `label`, where "" is the first pad.
+fn output_type(catalog: &FilterCatalog, filter: &GraphNode, label: &str) -> Option<MediaType> {
+    let Pads::Fixed(pads) = &catalog.get(&filter.name)?.outputs else {
+        return None;
+    };
+    let index = if label.is_empty() {
+        0
+    } else {
+        label.parse().ok()?
+    };
+    pads.get(index).map(|pad| pad.media_type)
+}
+
+// `expr` with identifiers replaced, or `None` if it uses one that is neither in
+// `allowed` nor a function call.
+fn substitute(expr: &str, allowed: &[&str], replacements: &[(&str, &str)]) -> Option<String> {
+    let mut result = String::new();
+    let mut chars = expr.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_ascii_digit() || c == '.' {
+            // Numbers, including exponents and SI suffixes such as `1e3` or `2K`.
+            result.push(c);
+            let mut previous = c;
+            while let Some(&(_, next)) = chars.peek() {
+                let sign = (next == '+' || next == '-') && (previous == 'e' || previous == 'E');
+                if !(next.is_ascii_alphanumeric() || next == '.' || sign) {
+                    break;
+                }
+                result.push(next);
+                previous = next;
+                chars.next();
+            }
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut end = start + c.len_utf8();
+            while let Some(&(i, next)) = chars.peek() {
+                if !(next.is_ascii_alphanumeric() || next == '_') {
+                    break;
+                }
+                end = i + next.len_utf8();
+                chars.next();
+            }
+            let name = &expr[start..end];
+            let is_call = expr[end..].trim_start().starts_with('(');
+            if is_call {
+                result.push_str(name);
+            } else if !allowed.contains(&name) {
+                return None;
+            } else {
+                match replacements.iter().find(|(from, _)| *from == name) {
+                    Some((_, to)) => result.push_str(to),
+                    None => result.push_str(name),
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    Some(result)
+}
+
+fn uses_only(expr: &str, allowed: &[&str]) -> bool {
+    substitute(expr, allowed, &[]).is_some()
+}
@@ -0,0 +1,599 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+use crate::daq::Kwargs;
+use crate::ffmpeg::{input_node, output_node, CompiledCommand};
+use crate::node::{FilterableStream, InputNode, OutputNode};
+
+/*
+Raw frames over ffmpeg's stdin and stdout.
+
+    ``RawVideo`` and ``RawAudio`` describe headerless media; their ``input`` reads
+    it from ``pipe:0`` and their ``output`` writes it to ``pipe:1``:
+
+        -f rawvideo -pix_fmt rgb24 -s 640x360 -framerate 25 -i pipe:0
+        ... -f rawvideo -pix_fmt rgb24 -s 320x180 pipe:1
+
+    ``PipeRunner`` runs a compiled command with Rust code on both ends.  Writing
+    stdin happens on its own thread while the calling thread reads stdout and a
+    third thread drains stderr, so ffmpeg blocking on a full pipe never stalls
+    the other side:
+
+        PipeRunner::new(&command)
+            .write_frames(frames)
+            .read_frames(&output_format, |frame| { ...; Ok(()) })
+            .run()?;
+
+    ``from_reader`` and ``to_writer`` attach any ``Read`` or ``Write`` to an input
+    or output instead, e.g. to transcode an in-memory buffer or a network body
+    without a temp file.  ``PipeRunner`` copies them to and from ffmpeg; fd 0
+    and 1 are stdin and stdout, and on Linux any fd from 3 up is given to ffmpeg
+    as ``pipe:N`` as well.  Compiling fails when two inputs both read stdin, or
+    any other fd is used twice.
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PixelFormat {
+    Rgb24,
+    Bgr24,
+    Rgba,
+    Bgra,
+    Gray,
+    Yuv420p,
+}
+
+impl PixelFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PixelFormat::Rgb24 => "rgb24",
+            PixelFormat::Bgr24 => "bgr24",
+            PixelFormat::Rgba => "rgba",
+            PixelFormat::Bgra => "bgra",
+            PixelFormat::Gray => "gray",
+            PixelFormat::Yuv420p => "yuv420p",
+        }
+    }
+
+    // Bytes in one `width` by `height` frame.
+    pub fn frame_size(&self, width: u32, height: u32) -> usize {
+        let (width, height) = (width as usize, height as usize);
+        match self {
+            PixelFormat::Rgb24 | PixelFormat::Bgr24 => width * height * 3,
+            PixelFormat::Rgba | PixelFormat::Bgra => width * height * 4,
+            PixelFormat::Gray => width * height,
+            // Chroma planes are subsampled by two in both directions, rounding up.
+            PixelFormat::Yuv420p => width * height + 2 * width.div_ceil(2) * height.div_ceil(2),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SampleFormat {
+    S16le,
+    S32le,
+    F32le,
+}
+
+impl SampleFormat {
+    // The name of both the sample format's muxer and its demuxer.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SampleFormat::S16le => "s16le",
+            SampleFormat::S32le => "s32le",
+            SampleFormat::F32le => "f32le",
+        }
+    }
+
+    pub fn bytes_per_sample(&self) -> usize {
+        match self {
+            SampleFormat::S16le => 2,
+            SampleFormat::S32le | SampleFormat::F32le => 4,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RawVideo {
+    pub width: u32,
+    pub height: u32,
+    pub pix_fmt: PixelFormat,
+    // The rate of frames written to ffmpeg, e.g. `30000/1001`; ffmpeg assumes 25.
+    pub frame_rate: Option<String>,
+}
+
+impl RawVideo {
+    pub fn new(width: u32, height: u32, pix_fmt: PixelFormat) -> RawVideo {
+        RawVideo {
+            width,
+            height,
+            pix_fmt,
+            frame_rate: None,
+        }
+    }
+
+    pub fn frame_rate(mut self, frame_rate: impl ToString) -> Self {
+        self.frame_rate = Some(frame_rate.to_string());
+        self
+    }
+
+    pub fn frame_size(&self) -> usize {
+        self.pix_fmt.frame_size(self.width, self.height)
+    }
+
+    // Frames written to ffmpeg's stdin.
+    pub fn input(&self) -> InputNode {
+        let mut kwargs = self.kwargs();
+        if let Some(frame_rate) = &self.frame_rate {
+            kwargs.push(("framerate".to_owned(), frame_rate.clone()));
+        }
+        input_node("pipe:0", kwargs)
+    }
+
+    // `stream` converted to this format and written to ffmpeg's stdout.
+    pub fn output(&self, stream: &FilterableStream) -> OutputNode {
+        output_node(stream, "pipe:1", self.kwargs())
+    }
+
+    fn kwargs(&self) -> Kwargs {
+        crate::kwargs! {
+            "f" => "rawvideo",
+            "pix_fmt" => self.pix_fmt.as_str(),
+            "s" => format!("{}x{}", self.width, self.height),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RawAudio {
+    pub sample_format: SampleFormat,
+    pub sample_rate: u32,
+    pub channels: u32,
+}
+
+impl RawAudio {
+    // Interleaved samples written to ffmpeg's stdin.
+    pub fn input(&self) -> InputNode {
+        input_node("pipe:0", self.kwargs())
+    }
+
+    // `stream` converted to this format and written to ffmpeg's stdout.
+    pub fn output(&self, stream: &FilterableStream) -> OutputNode {
+        output_node(stream, "pipe:1", self.kwargs())
+    }
+
+    fn kwargs(&self) -> Kwargs {
+        crate::kwargs! {
+            "f" => self.sample_format.as_str(),
+            "ar" => self.sample_rate,
+            "ac" => self.channels,
+        }
+    }
+}
+
+// One video frame, tightly packed with planes in order.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub pix_fmt: PixelFormat,
+    pub data: Vec<u8>,
+}
+
+impl Frame {
+    // Fails unless `data` holds exactly one frame of `format`.
+    pub fn new(format: &RawVideo, data: Vec<u8>) -> Result<Frame, String> {
+        if data.len() != format.frame_size() {
+            return Err(format!(
+                "A {}x{} {} frame is {} bytes, got {}",
+                format.width,
+                format.height,
+                format.pix_fmt.as_str(),
+                format.frame_size(),
+                data.len()
+            ));
+        }
+        Ok(Frame {
+            width: format.width,
+            height: format.height,
+            pix_fmt: format.pix_fmt,
+            data,
+        })
+    }
+}
+
+// The Rust side of a `pipe:N` input or output.  `PipeRunner` takes it, so it can
+// only be used by one run of the command.
+pub(crate) struct PipeEnd {
+    id: u64,
+    fd: u32,
+    io: RefCell<Option<PipeIo>>,
+}
+
+enum PipeIo {
+    Read(Box<dyn Read + Send>),
+    Write(Box<dyn Write + Send>),
+}
+
+impl PipeEnd {
+    fn new(fd: u32, io: PipeIo) -> PipeEnd {
+        static COUNTER: AtomicU64 = AtomicU64::new(1);
+        PipeEnd {
+            id: COUNTER.fetch_add(1, Ordering::Relaxed),
+            fd,
+            io: RefCell::new(Some(io)),
+        }
+    }
+
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn take(&self) -> Result<PipeIo, String> {
+        self.io.borrow_mut().take().ok_or_else(|| {
+            format!(
+                "The stream attached to {} was consumed by an earlier run",
+                describe_fd(self.fd)
+            )
+        })
+    }
+}
+
+// An input ffmpeg reads from `reader` through stdin (`fd` 0) or, on Linux, any
+// fd from 3 up.  Unless the data can be probed, `kwargs` needs an `f`.
+pub fn from_reader(
+    reader: impl Read + Send + 'static,
+    fd: u32,
+    mut kwargs: Kwargs,
+) -> Result<InputNode, String> {
+    check_fd(fd, 0, "input")?;
+    kwargs.push(("filename".to_owned(), format!("pipe:{}", fd)));
+    Ok(InputNode::with_pipe(
+        "input",
+        kwargs,
+        PipeEnd::new(fd, PipeIo::Read(Box::new(reader))),
+    ))
+}
+
+// An output ffmpeg writes into `writer` through stdout (`fd` 1) or, on Linux,
+// any fd from 3 up.  Pipes cannot seek, so `kwargs` needs an `f` that can be
+// streamed, e.g. `matroska` or `mp4` with `movflags=frag_keyframe+empty_moov`.
+pub fn to_writer(
+    stream: &FilterableStream,
+    writer: impl Write + Send + 'static,
+    fd: u32,
+    kwargs: Kwargs,
+) -> Result<OutputNode, String> {
+    check_fd(fd, 1, "output")?;
+    Ok(output_node(stream, &format!("pipe:{}", fd), kwargs)
+        .with_pipe(PipeEnd::new(fd, PipeIo::Write(Box::new(writer)))))
+}
+
+fn check_fd(fd: u32, standard: u32, kind: &str) -> Result<(), String> {
+    if fd == standard {
+        Ok(())
+    } else if fd < 3 {
+        Err(format!("An {} cannot use {}", kind, describe_fd(fd)))
+    } else if !cfg!(target_os = "linux") {
+        Err(format!(
+            "pipe:{} is only supported on Linux; use {} instead",
+            fd,
+            describe_fd(standard)
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// The fd an input or output `filename` refers to, if it is a pipe.
+pub(crate) fn pipe_fd(filename: &str, is_input: bool) -> Option<u32> {
+    let standard = if is_input { 0 } else { 1 };
+    match filename {
+        "-" | "pipe:" => Some(standard),
+        _ => filename.strip_prefix("pipe:")?.parse().ok(),
+    }
+}
+
+pub(crate) fn describe_fd(fd: u32) -> String {
+    match fd {
+        0 => "stdin".to_owned(),
+        1 => "stdout".to_owned(),
+        2 => "stderr".to_owned(),
+        _ => format!("pipe:{}", fd),
+    }
+}
+
+type Writer<'a> = Box<dyn FnOnce(&mut dyn Write) -> io::Result<()> + Send + 'a>;
+type Reader<'a> = Box<dyn FnOnce(&mut dyn Read) -> io::Result<()> + 'a>;
+
+pub struct PipeRunner<'a> {
+    command: &'a CompiledCommand,
+    writer: Option<Writer<'a>>,
+    reader: Option<Reader<'a>>,
+}
+
+impl<'a> PipeRunner<'a> {
+    pub fn new(command: &'a CompiledCommand) -> PipeRunner<'a> {
+        PipeRunner {
+            command,
+            writer: None,
+            reader: None,
+        }
+    }
+
+    // Feeds ffmpeg's stdin; it is closed once `write` returns.
+    pub fn stdin(
+        mut self,
+        write: impl FnOnce(&mut dyn Write) -> io::Result<()> + Send + 'a,
+    ) -> Self {
+        self.writer = Some(Box::new(write));
+        self
+    }
+
+    // Consumes ffmpeg's stdout on the thread calling `run`.
+    pub fn stdout(mut self, read: impl FnOnce(&mut dyn Read) -> io::Result<()> + 'a) -> Self {
+        self.reader = Some(Box::new(read));
+        self
+    }
+
+    pub fn write_frames<I>(self, frames: I) -> Self
+    where
+        I: IntoIterator<Item = Frame>,
+        I::IntoIter: Send + 'a,
+    {
+        let frames = frames.into_iter();
+        self.stdin(move |stdin| {
+            for frame in frames {
+                stdin.write_all(&frame.data)?;
+            }
+            Ok(())
+        })
+    }
+
+    // Calls `on_frame` with every frame of `format` ffmpeg writes to stdout.
+    pub fn read_frames(
+        self,
+        format: &RawVideo,
+        mut on_frame: impl FnMut(Frame) -> io::Result<()> + 'a,
+    ) -> Self {
+        let format = format.clone();
+        self.stdout(move |stdout| {
+            while let Some(data) = read_frame(stdout, format.frame_size())? {
+                on_frame(Frame {
+                    width: format.width,
+                    height: format.height,
+                    pix_fmt: format.pix_fmt,
+                    data,
+                })?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn run(mut self) -> Result<(), Box<dyn Error>> {
+        let mut transfers = Vec::new();
+        for pipe in self.command.pipes() {
+            match (pipe.fd, pipe.take()?) {
+                (0, PipeIo::Read(mut reader)) => {
+                    if self.writer.is_some() {
+                        return Err("stdin is fed by both an input's reader and `stdin`".into());
+                    }
+                    self.writer = Some(Box::new(move |stdin| {
+                        io::copy(&mut reader, stdin).map(drop)
+                    }));
+                }
+                (1, PipeIo::Write(mut writer)) => {
+                    if self.reader.is_some() {
+                        return Err("stdout is read by both an output's writer and `stdout`".into());
+                    }
+                    self.reader = Some(Box::new(move |stdout| {
+                        io::copy(stdout, &mut writer)?;
+                        writer.flush()
+                    }));
+                }
+                (fd, io) => transfers.push((fd, io)),
+            }
+        }
+
+        let mut command = self.command.command();
+        command
+            .stdin(piped_if(self.writer.is_some()))
+            .stdout(piped_if(self.reader.is_some()))
+            .stderr(Stdio::piped());
+        let (child_ends, transfers) = attach_fds(&mut command, transfers)?;
+        let mut child = command.spawn()?;
+        // Only ffmpeg may hold the other ends, or readers never see the end of file.
+        drop(child_ends);
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take();
+        let mut stderr = child.stderr.take().ok_or("No stderr pipe")?;
+
+        let (write_results, read_result, stderr) = thread::scope(|scope| {
+            let writer = self.writer.zip(stdin).map(|(write, mut stdin)| {
+                scope.spawn(move || {
+                    let result = write(&mut stdin);
+                    // Closing stdin signals the end of input.
+                    drop(stdin);
+                    result
+                })
+            });
+            let transfers: Vec<_> = transfers
+                .into_iter()
+                .map(|transfer| scope.spawn(move || transfer.run()))
+                .collect();
+            let drain = scope.spawn(move || {
+                let mut output = String::new();
+                stderr.read_to_string(&mut output).map(|_| output)
+            });
+            let read_result = match self.reader.zip(stdout) {
+                Some((read, mut stdout)) => {
+                    let result = read(&mut stdout);
+                    if result.is_err() {
+                        // Unblocks the writer, which may be waiting on a full pipe.
+                        let _ = child.kill();
+                    }
+                    result
+                }
+                None => Ok(()),
+            };
+            let write_results: Vec<io::Result<()>> = writer
+                .into_iter()
+                .chain(transfers)
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err(io::Error::other("pipe writer panicked")))
+                })
+                .collect();
+            let stderr = drain.join().ok().and_then(Result::ok).unwrap_or_default();
+            (write_results, read_result, stderr)
+        });
+        let status = child.wait()?;
+
+        read_result?;
+        if !status.success() {
+            let last_line = stderr.lines().last().unwrap_or_default();
+            return Err(
+                format!("{} failed: {}: {}", self.command.args[0], status, last_line).into(),
+            );
+        }
+        for result in write_results {
+            match result {
+                // ffmpeg may stop reading early, e.g. after `-frames:v`.
+                Err(error) if error.kind() == io::ErrorKind::BrokenPipe => {}
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// Copies between an attached reader or writer and ffmpeg's end of a `pipe:N`.
+enum Transfer {
+    Feed(io::PipeWriter, Box<dyn Read + Send>),
+    Drain(io::PipeReader, Box<dyn Write + Send>),
+}
+
+impl Transfer {
+    fn run(self) -> io::Result<()> {
+        match self {
+            Transfer::Feed(mut pipe, mut reader) => io::copy(&mut reader, &mut pipe).map(drop),
+            Transfer::Drain(mut pipe, mut writer) => {
+                io::copy(&mut pipe, &mut writer)?;
+                writer.flush()
+            }
+        }
+    }
+}
+
+// Opens a pipe for each of `pipes` and has `command` move ffmpeg's ends to their
+// fds.  Returns ffmpeg's ends, to be closed once it is spawned, and the copies
+// to run.
+#[cfg(target_os = "linux")]
+fn attach_fds(
+    command: &mut Command,
+    pipes: Vec<(u32, PipeIo)>,
+) -> io::Result<(Vec<std::os::fd::OwnedFd>, Vec<Transfer>)> {
+    use std::ffi::c_int;
+    use std::os::fd::{AsRawFd, OwnedFd};
+    use std::os::unix::process::CommandExt;
+
+    extern "C" {
+        fn dup2(old: c_int, new: c_int) -> c_int;
+        fn fcntl(fd: c_int, cmd: c_int, ...) -> c_int;
+    }
+    const F_DUPFD_CLOEXEC: c_int = 1030;
+
+    if pipes.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    let mut child_ends: Vec<OwnedFd> = Vec::new();
+    let mut transfers = Vec::new();
+    let mut targets: Vec<c_int> = Vec::new();
+    for (fd, io) in pipes {
+        let (reader, writer) = io::pipe()?;
+        match io {
+            PipeIo::Read(source) => {
+                child_ends.push(reader.into());
+                transfers.push(Transfer::Feed(writer, source));
+            }
+            PipeIo::Write(sink) => {
+                child_ends.push(writer.into());
+                transfers.push(Transfer::Drain(reader, sink));
+            }
+        }
+        targets.push(fd as c_int);
+    }
+    let sources: Vec<c_int> = child_ends.iter().map(AsRawFd::as_raw_fd).collect();
+    // The lowest fd above every target; a fixed one could exceed `RLIMIT_NOFILE`.
+    let above_targets = targets.iter().max().map_or(0, |fd| fd + 1);
+    // Allocated up front: the child may not allocate between fork and exec.
+    let mut moved: Vec<c_int> = vec![-1; sources.len()];
+    unsafe {
+        command.pre_exec(move || {
+            // Every end is first copied above any target, so moving one end onto
+            // its fd cannot close another that still has to be moved.
+            for (source, copy) in sources.iter().zip(moved.iter_mut()) {
+                *copy = fcntl(*source, F_DUPFD_CLOEXEC, above_targets);
+                if *copy < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            for (copy, target) in moved.iter().zip(&targets) {
+                if dup2(*copy, *target) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+    Ok((child_ends, transfers))
+}
+
+// `check_fd` only allows stdin and stdout elsewhere, which need no extra pipes.
+#[cfg(not(target_os = "linux"))]
+fn attach_fds(
+    _command: &mut Command,
+    pipes: Vec<(u32, PipeIo)>,
+) -> io::Result<(Vec<()>, Vec<Transfer>)> {
+    match pipes.first() {
+        Some((fd, _)) => Err(io::Error::other(format!(
+            "pipe:{} is only supported on Linux",
+            fd
+        ))),
+        None => Ok((Vec::new(), Vec::new())),
+    }
+}
+
+fn piped_if(piped: bool) -> Stdio {
+    if piped {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    }
+}
+
+// The next `size` bytes, or `None` at the end of the stream.  A partial frame
+// at the end is an error.
+fn read_frame(reader: &mut dyn Read, size: usize) -> io::Result<Option<Vec<u8>>> {
+    let mut data = vec![0; size];
+    let mut filled = 0;
+    while filled < size {
+        match reader.read(&mut data[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("Truncated frame: {} of {} bytes", filled, size),
+                ))
+            }
+            Ok(n) => filled += n,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(Some(data))
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use crate::daq::Kwargs;
+use crate::ffmpeg::{filter_multi_output, input_node, output_node};
+use crate::graph::Graph;
+use crate::node::{FilterNode, FilterableStream, InputNode, OutputStream, StreamSpec};
+
+/*
+Pipeline descriptions, for authoring graphs without writing Rust.
+
+    A pipeline names its inputs and filter steps, which later steps and the
+    outputs refer to by id.  ``ffmpeg-cmd`` reads them from YAML or TOML files:
+
+        globals = { loglevel = "error" }
+
+        [[inputs]]
+        id = "movie"
+        file = "movie.mp4"
+        options = { ss = 10 }
+
+        [[steps]]
+        id = "small"
+        filter = "scale"
+        inputs = ["movie:v"]
+        args = { w = 640, h = -2 }
+
+        [[outputs]]
+        file = "small.mp4"
+        streams = ["small", "movie:a"]
+        options = { "c:v" = "libx264" }
+
+    A reference is an id, optionally followed by ``.LABEL`` to pick one output
+    of a step with several (``split.1``) and by ``:v``, ``:a`` or ``:s`` to
+    select a stream type.  Steps can only refer to inputs and earlier steps.
+    Options and args keep the order they are written in.
+*/
+
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Pipeline {
+    #[serde(default)]
+    pub globals: Options,
+    pub inputs: Vec<PipelineInput>,
+    #[serde(default)]
+    pub steps: Vec<PipelineStep>,
+    pub outputs: Vec<PipelineOutput>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineInput {
+    pub id: String,
+    pub file: String,
+    #[serde(default)]
+    pub options: Options,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineStep {
+    pub id: String,
+    pub filter: String,
+    pub inputs: Vec<String>,
+    #[serde(default)]
+    pub args: Options,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineOutput {
+    pub file: String,
+    pub streams: Vec<String>,
+    #[serde(default)]
+    pub options: Options,
+}
+
+// A map of options in document order, with strings, numbers and booleans as
+// values.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Options(pub Kwargs);
+
+impl<'de> Deserialize<'de> for Options {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(OptionsVisitor)
+    }
+}
+
+struct OptionsVisitor;
+
+impl<'de> Visitor<'de> for OptionsVisitor {
+    type Value = Options;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of options")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Options, A::Error> {
+        let mut kwargs = Kwargs::new();
+        while let Some((key, value)) = map.next_entry::<String, OptionValue>()? {
+            kwargs.push((key, value.0));
+        }
+        Ok(Options(kwargs))
+    }
+}
+
+struct OptionValue(String);
+
+impl<'de> Deserialize<'de> for OptionValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(OptionValueVisitor)
+    }
+}
+
+struct OptionValueVisitor;
+
+impl Visitor<'_> for OptionValueVisitor {
+    type Value = OptionValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string, number or boolean")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<OptionValue, E> {
+        Ok(OptionValue(value.to_owned()))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<OptionValue, E> {
+        Ok(OptionValue(value.to_string()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<OptionValue, E> {
+        Ok(OptionValue(value.to_string()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<OptionValue, E> {
+        Ok(OptionValue(value.to_string()))
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<OptionValue, E> {
+        Ok(OptionValue(value.to_string()))
+    }
+}
+
+enum Named {
+    Input(InputNode),
+    Step(FilterNode),
+}
+
+impl Pipeline {
+    pub fn outputs(&self) -> Result<Vec<OutputStream>, Box<dyn Error>> {
+        let mut named: HashMap<&str, Named> = HashMap::new();
+        for input in &self.inputs {
+            let node = input_node(&input.file, input.options.0.clone());
+            if named.insert(&input.id, Named::Input(node)).is_some() {
+                return Err(format!("Duplicate id `{}`", input.id).into());
+            }
+        }
+        for step in &self.steps {
+            let streams = resolve_all(&named, &step.inputs)
+                .map_err(|err| format!("Step `{}`: {}", step.id, err))?;
+            let node = filter_multi_output(streams, &step.filter, step.args.0.clone());
+            if named.insert(&step.id, Named::Step(node)).is_some() {
+                return Err(format!("Duplicate id `{}`", step.id).into());
+            }
+        }
+        if self.outputs.is_empty() {
+            return Err("Pipeline has no outputs".into());
+        }
+        self.outputs
+            .iter()
+            .map(|output| {
+                let streams = resolve_all(&named, &output.streams)
+                    .map_err(|err| format!("Output `{}`: {}", output.file, err))?;
+                Ok(output_node(streams, &output.file, output.options.0.clone()).stream("", None))
+            })
+            .collect()
+    }
+
+    // The pipeline as a graph, with its globals.
+    pub fn graph(&self) -> Result<Graph, Box<dyn Error>> {
+        let mut graph = Graph::new(self.outputs()?)?;
+        graph.globals = self.globals.0.clone();
+        Ok(graph)
+    }
+}
+
+// One stream stays a single stream, so it connects like one passed in code.
+fn resolve_all(named: &HashMap<&str, Named>, references: &[String]) -> Result<StreamSpec, String> {
+    let mut streams = references
+        .iter()
+        .map(|reference| resolve(named, reference))
+        .collect::<Result<Vec<FilterableStream>, String>>()?;
+    match streams.len() {
+        0 => Err("No streams".to_owned()),
+        1 => Ok(streams.remove(0).into()),
+        _ => Ok(streams.into()),
+    }
+}
+
+fn resolve(named: &HashMap<&str, Named>, reference: &str) -> Result<FilterableStream, String> {
+    let (name, selector) = match reference.rsplit_once(':') {
+        Some((name, selector)) if selector.chars().count() == 1 => (name, selector.chars().next()),
+        _ => (reference, None),
+    };
+    let (id, label) = name.split_once('.').unwrap_or((name, ""));
+    let stream = match named.get(id) {
+        Some(Named::Input(_)) if !label.is_empty() => {
+            return Err(format!("Input `{}` has no output `{}`", id, label))
+        }
+        Some(Named::Input(node)) => node.stream("", None),
+        Some(Named::Step(node)) => node.stream(label, None),
+        None => return Err(format!("Unknown stream `{}`", reference)),
+    };
+    match selector {
+        Some(selector) => stream.get(selector),
+        None => Ok(stream),
+    }
+}
@@ -0,0 +1,105 @@
+use crate::daq::Kwargs;
+use crate::ffmpeg::output_node;
+use crate::node::{OutputNode, OutputStream, StreamSpec};
+use crate::time::{Duration, Timestamp};
+
+/*
+Segment muxer output.
+
+    Splits one encode into numbered (or, with ``strftime``, dated) files, cutting
+    at keyframes after every ``segment_time`` or at the listed ``segment_times``:
+
+        SegmentOutput::new(&input, "out%03d.ts")
+            .segment_time(Duration::from_secs(10))
+            .segment_list("out.csv")
+            .reset_timestamps(true)
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SegmentListType {
+    Flat,
+    Csv,
+    Ext,
+    Ffconcat,
+    M3u8,
+}
+
+impl SegmentListType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SegmentListType::Flat => "flat",
+            SegmentListType::Csv => "csv",
+            SegmentListType::Ext => "ext",
+            SegmentListType::Ffconcat => "ffconcat",
+            SegmentListType::M3u8 => "m3u8",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SegmentOutput {
+    stream_spec: StreamSpec,
+    filename: String,
+    kwargs: Kwargs,
+}
+
+impl SegmentOutput {
+    // `filename` is a pattern such as `out%03d.ts`, or a `strftime` format.
+    pub fn new(stream_spec: impl Into<StreamSpec>, filename: &str) -> SegmentOutput {
+        SegmentOutput {
+            stream_spec: stream_spec.into(),
+            filename: filename.to_owned(),
+            kwargs: crate::kwargs! {"f" => "segment"},
+        }
+    }
+
+    pub fn segment_time(self, duration: Duration) -> Self {
+        self.arg("segment_time", duration.format_seconds())
+    }
+
+    // Split points instead of a fixed segment duration.
+    pub fn segment_times(self, times: &[Timestamp]) -> Self {
+        let times: Vec<String> = times.iter().map(Timestamp::format_seconds).collect();
+        self.arg("segment_times", times.join(","))
+    }
+
+    pub fn segment_format(self, format: &str) -> Self {
+        self.arg("segment_format", format)
+    }
+
+    pub fn segment_list(self, filename: &str) -> Self {
+        self.arg("segment_list", filename)
+    }
+
+    pub fn segment_list_type(self, list_type: SegmentListType) -> Self {
+        self.arg("segment_list_type", list_type.as_str())
+    }
+
+    // Expands `filename` with `strftime` instead of a segment number.
+    pub fn strftime(self, value: bool) -> Self {
+        self.arg("strftime", value as u8)
+    }
+
+    // Starts the timestamps of every segment at zero.
+    pub fn reset_timestamps(self, value: bool) -> Self {
+        self.arg("reset_timestamps", value as u8)
+    }
+
+    // Any other output option, e.g. `c:v` or `segment_start_number`.
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn node(&self) -> OutputNode {
+        output_node(
+            self.stream_spec.clone(),
+            &self.filename,
+            self.kwargs.clone(),
+        )
+    }
+
+    pub fn stream(&self) -> OutputStream {
+        self.node().stream("", None)
+    }
+}
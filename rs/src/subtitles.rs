@@ -0,0 +1,295 @@
+use crate::daq::Kwargs;
+use crate::ffmpeg::output_node;
+use crate::node::{FilterableStream, NodeType, OutputNode, OutputStream, Stream};
+
+/*
+Subtitles: burning them into the video, muxing them as tracks, and converting
+between formats.
+
+    ``BurnIn`` renders a subtitle file with the ``subtitles`` or ``ass`` filter.
+    File names are ordinary option values, so ``FilterNode::get_filter`` escapes
+    them for both the option parser and the filtergraph parser; a Windows path
+    needs no quoting by hand:
+
+        BurnIn::new(&video, r"C:\subs\it's.srt")  =>  subtitles=filename=C\\:\\\\subs\\\\it\\\'s.srt
+
+    ``SubtitledOutput`` maps video, audio and subtitle tracks to one file, with a
+    codec, language and title per subtitle track.  MP4 and MOV only hold
+    ``mov_text`` and WebM only ``webvtt``, which is checked up front.
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SubtitleCodec {
+    MovText,
+    Srt,
+    WebVtt,
+    Ass,
+    Copy,
+}
+
+impl SubtitleCodec {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubtitleCodec::MovText => "mov_text",
+            SubtitleCodec::Srt => "srt",
+            SubtitleCodec::WebVtt => "webvtt",
+            SubtitleCodec::Ass => "ass",
+            SubtitleCodec::Copy => "copy",
+        }
+    }
+
+    // The codec of subtitle files named `filename`, e.g. `subs.vtt`.
+    pub fn from_filename(filename: &str) -> Option<SubtitleCodec> {
+        match extension(filename).as_str() {
+            "srt" => Some(SubtitleCodec::Srt),
+            "vtt" => Some(SubtitleCodec::WebVtt),
+            "ass" | "ssa" => Some(SubtitleCodec::Ass),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct BurnIn {
+    video: FilterableStream,
+    filter_name: &'static str,
+    kwargs: Kwargs,
+}
+
+impl BurnIn {
+    // Renders any subtitle format libavformat reads with the `subtitles` filter.
+    pub fn new(video: &FilterableStream, filename: &str) -> BurnIn {
+        BurnIn {
+            video: video.clone(),
+            filter_name: "subtitles",
+            kwargs: crate::kwargs! {"filename" => filename},
+        }
+    }
+
+    // Renders an ASS/SSA file with the `ass` filter, keeping its styling as is.
+    pub fn ass(video: &FilterableStream, filename: &str) -> BurnIn {
+        BurnIn {
+            video: video.clone(),
+            filter_name: "ass",
+            kwargs: crate::kwargs! {"filename" => filename},
+        }
+    }
+
+    // The subtitle stream to render when the file has several, e.g. a movie.
+    pub fn stream_index(self, index: u32) -> Self {
+        self.arg("stream_index", index)
+    }
+
+    // The character encoding of text subtitles, e.g. `CP1252`.
+    pub fn charenc(self, charenc: &str) -> Self {
+        self.arg("charenc", charenc)
+    }
+
+    // Overrides ASS style fields, e.g. `FontName=Arial,FontSize=24`.
+    pub fn force_style(self, style: &str) -> Self {
+        self.arg("force_style", style)
+    }
+
+    pub fn fonts_dir(self, dir: &str) -> Self {
+        self.arg("fontsdir", dir)
+    }
+
+    // The size the subtitles were authored for, needed to place them correctly
+    // when the video was scaled.
+    pub fn original_size(self, width: u32, height: u32) -> Self {
+        self.arg("original_size", format!("{}x{}", width, height))
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn stream(&self) -> Result<FilterableStream, String> {
+        if self.filter_name == "ass" {
+            for key in ["stream_index", "charenc", "force_style"] {
+                if self.kwargs.iter().any(|(k, _)| k == key) {
+                    return Err(format!(
+                        "`{}` is only supported by the `subtitles` filter",
+                        key
+                    ));
+                }
+            }
+        }
+        Ok(self.video.filter(self.filter_name, self.kwargs.clone()))
+    }
+}
+
+#[derive(Clone)]
+pub struct SubtitleTrack {
+    stream: Stream,
+    codec: Option<SubtitleCodec>,
+    language: Option<String>,
+    title: Option<String>,
+    default: bool,
+    forced: bool,
+}
+
+impl SubtitleTrack {
+    // `stream` is usually `input.subtitle()`.
+    pub fn new(stream: &Stream) -> SubtitleTrack {
+        SubtitleTrack {
+            stream: stream.clone(),
+            codec: None,
+            language: None,
+            title: None,
+            default: false,
+            forced: false,
+        }
+    }
+
+    pub fn codec(mut self, codec: SubtitleCodec) -> Self {
+        self.codec = Some(codec);
+        self
+    }
+
+    // An ISO 639-2 code, e.g. `eng`.
+    pub fn language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_owned());
+        self
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_owned());
+        self
+    }
+
+    pub fn default(mut self, default: bool) -> Self {
+        self.default = default;
+        self
+    }
+
+    pub fn forced(mut self, forced: bool) -> Self {
+        self.forced = forced;
+        self
+    }
+}
+
+#[derive(Clone)]
+pub struct SubtitledOutput {
+    filename: String,
+    streams: Vec<Stream>,
+    tracks: Vec<SubtitleTrack>,
+    kwargs: Kwargs,
+}
+
+impl SubtitledOutput {
+    pub fn new(filename: &str) -> SubtitledOutput {
+        SubtitledOutput {
+            filename: filename.to_owned(),
+            streams: Vec::new(),
+            tracks: Vec::new(),
+            kwargs: Kwargs::new(),
+        }
+    }
+
+    // A stream mapped before the subtitle tracks.  Subtitles selected with
+    // `subtitle()` count as one stream each, like the tracks' own.
+    pub fn map(mut self, stream: &Stream) -> Self {
+        self.streams.push(stream.clone());
+        self
+    }
+
+    pub fn subtitle(mut self, track: SubtitleTrack) -> Self {
+        self.tracks.push(track);
+        self
+    }
+
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn node(&self) -> Result<OutputNode, String> {
+        let allowed: &[SubtitleCodec] = match extension(&self.filename).as_str() {
+            "mp4" | "m4v" | "mov" => &[SubtitleCodec::MovText],
+            "webm" => &[SubtitleCodec::WebVtt],
+            _ => &[],
+        };
+
+        // Options address the tracks by their index among the output's subtitle
+        // streams, which start with the mapped ones.
+        let mut mapped_subtitles = 0;
+        for (i, stream) in self.streams.iter().enumerate() {
+            match stream.selector {
+                Some('s') => mapped_subtitles += 1,
+                // All streams of an input, however many subtitles it holds.
+                None if stream.node.node_type() == NodeType::Input && !self.tracks.is_empty() => {
+                    return Err(format!(
+                        "Mapped stream #{} is a whole input; select its streams by type to add subtitle tracks",
+                        i
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        let mut streams = self.streams.clone();
+        let mut kwargs = self.kwargs.clone();
+        for (track_index, track) in self.tracks.iter().enumerate() {
+            let i = mapped_subtitles + track_index;
+            // Without a codec, the container's default subtitle encoder is used.
+            if let Some(codec) = track.codec {
+                if codec != SubtitleCodec::Copy && !allowed.is_empty() && !allowed.contains(&codec)
+                {
+                    return Err(format!(
+                        "Subtitle track #{} cannot be muxed into {} as {}",
+                        track_index,
+                        self.filename,
+                        codec.as_str()
+                    ));
+                }
+                kwargs.push((format!("c:s:{}", i), codec.as_str().to_owned()));
+            }
+            if let Some(language) = &track.language {
+                kwargs.push((
+                    format!("metadata:s:s:{}", i),
+                    format!("language={}", language),
+                ));
+            }
+            if let Some(title) = &track.title {
+                kwargs.push((format!("metadata:s:s:{}", i), format!("title={}", title)));
+            }
+            let disposition = match (track.default, track.forced) {
+                (false, false) => None,
+                (true, false) => Some("default"),
+                (false, true) => Some("forced"),
+                (true, true) => Some("default+forced"),
+            };
+            if let Some(disposition) = disposition {
+                kwargs.push((format!("disposition:s:{}", i), disposition.to_owned()));
+            }
+            streams.push(track.stream.clone());
+        }
+        Ok(output_node(streams, &self.filename, kwargs))
+    }
+
+    pub fn stream(&self) -> Result<OutputStream, String> {
+        Ok(self.node()?.stream("", None))
+    }
+}
+
+// Converts the subtitles of `input` to the format of `filename`, e.g. SRT to
+// WebVTT.
+pub fn convert(input: &FilterableStream, filename: &str) -> Result<OutputStream, String> {
+    let codec = SubtitleCodec::from_filename(filename)
+        .ok_or_else(|| format!("Unknown subtitle format of {}", filename))?;
+    Ok(output_node(
+        input.subtitle(),
+        filename,
+        crate::kwargs! {"c:s" => codec.as_str()},
+    )
+    .stream("", None))
+}
+
+fn extension(filename: &str) -> String {
+    match filename.rsplit_once('.') {
+        Some((_, extension)) if !extension.contains('/') => extension.to_ascii_lowercase(),
+        _ => String::new(),
+    }
+}
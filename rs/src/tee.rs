@@ -0,0 +1,161 @@
+use crate::daq::Kwargs;
+use crate::ffmpeg::output_node;
+use crate::node::{OutputNode, OutputStream, StreamSpec};
+use crate::utils::escape_chars;
+
+/*
+Tee muxer output.
+
+    Writes one encode to several destinations.  The output filename is the list
+    of slaves, each with its own muxer options in brackets:
+
+        [f=mpegts:select=v]out.ts|[f=flv:onfail=ignore]rtmp://host/app
+
+    Option values are escaped for the slave option parser (`\`, `'`, `:`, `]`),
+    then each slave for the list parser (`\`, `'`, `|`).
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TeeOnFail {
+    Abort,
+    Ignore,
+}
+
+impl TeeOnFail {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TeeOnFail::Abort => "abort",
+            TeeOnFail::Ignore => "ignore",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TeeSlave {
+    filename: String,
+    kwargs: Kwargs,
+}
+
+impl TeeSlave {
+    pub fn new(filename: &str) -> TeeSlave {
+        TeeSlave {
+            filename: filename.to_owned(),
+            kwargs: Kwargs::new(),
+        }
+    }
+
+    pub fn format(self, format: &str) -> Self {
+        self.arg("f", format)
+    }
+
+    // Stream specifier of the streams written to this slave, e.g. `v` or `a:0`.
+    pub fn select(self, stream_specifier: &str) -> Self {
+        self.arg("select", stream_specifier)
+    }
+
+    pub fn onfail(self, onfail: TeeOnFail) -> Self {
+        self.arg("onfail", onfail.as_str())
+    }
+
+    // Bitstream filters, e.g. `bsfs/v` => `h264_mp4toannexb`.
+    pub fn bsfs(self, stream_specifier: &str, filters: &str) -> Self {
+        let key = if stream_specifier.is_empty() {
+            "bsfs".to_owned()
+        } else {
+            format!("bsfs/{}", stream_specifier)
+        };
+        self.arg(&key, filters)
+    }
+
+    // Any other option of the slave's muxer.
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    fn get_spec(&self) -> String {
+        let options: Vec<String> = self
+            .kwargs
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "{}={}",
+                    escape_chars(key, "'=:]"),
+                    escape_chars(value, "':]")
+                )
+            })
+            .collect();
+        // A slave starting with `[` is read as options, so a filename starting
+        // with one always gets a block of its own, even an empty `[]`.
+        let options = if options.is_empty() && !self.filename.starts_with('[') {
+            String::new()
+        } else {
+            format!("[{}]", options.join(":"))
+        };
+        escape_outer_whitespace(&escape_chars(
+            &format!("{}{}", options, self.filename),
+            "'|",
+        ))
+    }
+}
+
+// The list parser drops unescaped whitespace around each slave, which would also
+// leave a filename like ` [draft].mkv` starting with `[`.
+fn escape_outer_whitespace(text: &str) -> String {
+    const WHITESPACE: &[char] = &[' ', '\n', '\t', '\r'];
+    let start = text.len() - text.trim_start_matches(WHITESPACE).len();
+    let end = text.trim_end_matches(WHITESPACE).len().max(start);
+    let escape = |part: &str| part.chars().map(|c| format!("\\{}", c)).collect::<String>();
+    format!(
+        "{}{}{}",
+        escape(&text[..start]),
+        &text[start..end],
+        escape(&text[end..])
+    )
+}
+
+#[derive(Clone)]
+pub struct TeeOutput {
+    stream_spec: StreamSpec,
+    slaves: Vec<TeeSlave>,
+    kwargs: Kwargs,
+}
+
+impl TeeOutput {
+    pub fn new(stream_spec: impl Into<StreamSpec>) -> TeeOutput {
+        TeeOutput {
+            stream_spec: stream_spec.into(),
+            slaves: Vec::new(),
+            kwargs: Kwargs::new(),
+        }
+    }
+
+    pub fn slave(mut self, slave: TeeSlave) -> Self {
+        self.slaves.push(slave);
+        self
+    }
+
+    // Output options shared by all slaves, typically the encoders.
+    pub fn arg(mut self, key: &str, value: impl ToString) -> Self {
+        self.kwargs.push((key.to_owned(), value.to_string()));
+        self
+    }
+
+    pub fn node(&self) -> Result<OutputNode, String> {
+        if self.slaves.is_empty() {
+            return Err("Tee output needs at least one slave".to_owned());
+        }
+        let slaves: Vec<String> = self.slaves.iter().map(TeeSlave::get_spec).collect();
+        let mut kwargs: Kwargs = crate::kwargs! {"f" => "tee"};
+        kwargs.extend(self.kwargs.iter().cloned());
+        Ok(output_node(
+            self.stream_spec.clone(),
+            &slaves.join("|"),
+            kwargs,
+        ))
+    }
+
+    pub fn stream(&self) -> Result<OutputStream, String> {
+        Ok(self.node()?.stream("", None))
+    }
+}
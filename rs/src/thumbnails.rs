@@ -0,0 +1,305 @@
+use std::fmt::Write;
+
+use crate::expr::{Expr, IH, IW, OH, OW, T};
+use crate::image_sequence::{ImagePattern, ImageSequenceOutput};
+use crate::node::FilterableStream;
+use crate::time::Duration;
+
+/*
+Thumbnails, contact sheets and sprite sheets for player previews.
+
+    ``thumbnails`` samples one frame per interval with ``select``, optionally
+    letting the ``thumbnail`` filter pick the most representative frame of each
+    batch first.  The source duration is needed up front to spread a number of
+    thumbnails evenly and to know how many an interval yields:
+
+        thumbnails(&input, Duration::from_secs(600), 10)                    // every 60s
+        thumbnails(&input, Duration::from_secs(600), Duration::from_secs(5)) // 120 frames
+
+    The samples are then written as separate images, tiled into contact sheets
+    with ``tile``, or tiled into fixed size sprite sheets with a WebVTT track
+    pointing every interval at its tile:
+
+        00:00:05.000 --> 00:00:10.000
+        sprite_001.jpg#xywh=160,0,160,90
+*/
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Spacing {
+    // This many thumbnails, evenly spread over the source.
+    Count(u32),
+    // One thumbnail per interval.
+    Interval(Duration),
+}
+
+impl From<u32> for Spacing {
+    fn from(count: u32) -> Self {
+        Spacing::Count(count)
+    }
+}
+
+impl From<Duration> for Spacing {
+    fn from(interval: Duration) -> Self {
+        Spacing::Interval(interval)
+    }
+}
+
+#[derive(Clone)]
+pub struct Thumbnails {
+    stream: FilterableStream,
+    duration: Duration,
+    spacing: Spacing,
+    representative: Option<u32>,
+    width: Option<u32>,
+}
+
+// Thumbnails of the video of `input`, which lasts `duration`.
+pub fn thumbnails(
+    input: &FilterableStream,
+    duration: Duration,
+    spacing: impl Into<Spacing>,
+) -> Thumbnails {
+    Thumbnails {
+        stream: input.video(),
+        duration,
+        spacing: spacing.into(),
+        representative: None,
+        width: None,
+    }
+}
+
+impl Thumbnails {
+    // Runs the `thumbnail` filter over batches of `frames` frames before sampling,
+    // which skips black and blurry frames at the cost of decoding time.
+    pub fn representative(mut self, frames: u32) -> Self {
+        self.representative = Some(frames);
+        self
+    }
+
+    // Scales separate thumbnails to `width`, keeping the aspect ratio.
+    pub fn width(mut self, width: u32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    // The time between two thumbnails.
+    pub fn interval(&self) -> Result<Duration, String> {
+        match self.spacing {
+            Spacing::Count(count) => {
+                if count == 0 {
+                    return Err("Thumbnail count must be positive".to_owned());
+                }
+                Ok(Duration::from_micros(
+                    self.duration.as_micros() / count as i64,
+                ))
+            }
+            Spacing::Interval(interval) => {
+                if interval <= Duration::ZERO {
+                    return Err(format!("Thumbnail interval {} must be positive", interval));
+                }
+                Ok(interval)
+            }
+        }
+    }
+
+    // The number of thumbnails produced.
+    pub fn count(&self) -> Result<u32, String> {
+        match self.spacing {
+            Spacing::Count(count) => self.interval().map(|_| count),
+            Spacing::Interval(interval) => {
+                self.interval()?;
+                let (total, interval) = (self.duration.as_micros(), interval.as_micros());
+                Ok(((total + interval - 1) / interval).max(1) as u32)
+            }
+        }
+    }
+
+    // The sampled frames at their original size.
+    fn samples(&self) -> Result<FilterableStream, String> {
+        let interval = self.interval()?;
+        let mut stream = self.stream.clone();
+        if let Some(frames) = self.representative {
+            stream = stream.filter("thumbnail", crate::kwargs! {"n" => frames});
+        }
+        // The first frame, then every frame at least `interval` after the last one.
+        let prev = Expr::var("prev_selected_t");
+        let select =
+            Expr::call("isnan", vec![prev.clone()]) + (T - prev).gte(interval.as_secs_f64());
+        Ok(stream.filter("select", crate::kwargs! {"expr" => select}))
+    }
+
+    pub fn stream(&self) -> Result<FilterableStream, String> {
+        let samples = self.samples()?;
+        Ok(match self.width {
+            Some(width) => samples.filter("scale", crate::kwargs! {"w" => width, "h" => -2}),
+            None => samples,
+        })
+    }
+
+    // Every thumbnail as a separate image, numbered from 1.
+    pub fn output(&self, pattern: &ImagePattern) -> Result<ImageSequenceOutput, String> {
+        Ok(ImageSequenceOutput::new(&self.stream()?, pattern)
+            .frames(self.count()? as u64)
+            .arg("fps_mode", "vfr"))
+    }
+
+    // Sheets of `cols` by `rows` thumbnails, `width` pixels wide; long sources
+    // fill several sheets.
+    pub fn contact_sheet(
+        &self,
+        cols: u32,
+        rows: u32,
+        width: u32,
+    ) -> Result<FilterableStream, String> {
+        check_layout(cols, rows)?;
+        if width < cols {
+            return Err(format!(
+                "Contact sheet of {} columns must be at least {} pixels wide",
+                cols, cols
+            ));
+        }
+        Ok(self
+            .samples()?
+            .filter("scale", crate::kwargs! {"w" => width / cols, "h" => -2})
+            .filter(
+                "tile",
+                crate::kwargs! {"layout" => format!("{}x{}", cols, rows)},
+            ))
+    }
+
+    // Sheets of `cols` by `rows` tiles of exactly `tile_width` by `tile_height`,
+    // letterboxed as needed, so every tile's position is known for the WebVTT
+    // track.
+    pub fn sprite_sheet(
+        &self,
+        cols: u32,
+        rows: u32,
+        tile_width: u32,
+        tile_height: u32,
+    ) -> Result<SpriteSheet, String> {
+        check_layout(cols, rows)?;
+        if tile_width == 0 || tile_height == 0 {
+            return Err("Sprite tiles must not be empty".to_owned());
+        }
+        let stream = self
+            .samples()?
+            .filter(
+                "scale",
+                crate::kwargs! {
+                    "w" => tile_width,
+                    "h" => tile_height,
+                    "force_original_aspect_ratio" => "decrease",
+                },
+            )
+            .filter(
+                "pad",
+                crate::kwargs! {
+                    "w" => tile_width,
+                    "h" => tile_height,
+                    "x" => (OW - IW) / 2,
+                    "y" => (OH - IH) / 2,
+                },
+            )
+            .filter(
+                "tile",
+                crate::kwargs! {"layout" => format!("{}x{}", cols, rows)},
+            );
+        Ok(SpriteSheet {
+            stream,
+            duration: self.duration,
+            interval: self.interval()?,
+            count: self.count()?,
+            cols,
+            rows,
+            tile_width,
+            tile_height,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct SpriteSheet {
+    stream: FilterableStream,
+    duration: Duration,
+    interval: Duration,
+    count: u32,
+    cols: u32,
+    rows: u32,
+    tile_width: u32,
+    tile_height: u32,
+}
+
+impl SpriteSheet {
+    pub fn stream(&self) -> FilterableStream {
+        self.stream.clone()
+    }
+
+    // The number of sheets needed for all thumbnails.
+    pub fn sheets(&self) -> u32 {
+        let per_sheet = self.cols * self.rows;
+        self.count.div_ceil(per_sheet)
+    }
+
+    // The sheets as images named after `pattern`, numbered from 1.
+    pub fn output(&self, pattern: &ImagePattern) -> ImageSequenceOutput {
+        // Like the thumbnails, the sheets are sparse; a constant frame rate would
+        // repeat the first one.
+        ImageSequenceOutput::new(&self.stream, pattern)
+            .frames(self.sheets() as u64)
+            .arg("fps_mode", "vfr")
+    }
+
+    // The WebVTT thumbnail track for sheets written with `output(pattern)`.  Cues
+    // refer to the sheets by file name, prefixed with `url_prefix` (e.g. an
+    // absolute URL, or `""` when the track is served next to the sheets).
+    pub fn webvtt(&self, pattern: &ImagePattern, url_prefix: &str) -> Result<String, String> {
+        let mut vtt = "WEBVTT\n".to_owned();
+        let per_sheet = self.cols * self.rows;
+        for i in 0..self.count {
+            let sheet = pattern
+                .filename((i / per_sheet + 1) as u64)
+                .ok_or_else(|| format!("Sprite sheet pattern {} is not numbered", pattern))?;
+            let name = sheet.rsplit('/').next().unwrap_or(&sheet);
+            let tile = i % per_sheet;
+            let start = self.interval * i as i64;
+            let end = if i + 1 == self.count {
+                self.duration
+            } else {
+                start + self.interval
+            };
+            // Writing to a String cannot fail.
+            let _ = write!(
+                vtt,
+                "\n{} --> {}\n{}{}#xywh={},{},{},{}\n",
+                cue_time(start),
+                cue_time(end),
+                url_prefix,
+                name,
+                tile % self.cols * self.tile_width,
+                tile / self.cols * self.tile_height,
+                self.tile_width,
+                self.tile_height
+            );
+        }
+        Ok(vtt)
+    }
+}
+
+fn check_layout(cols: u32, rows: u32) -> Result<(), String> {
+    if cols == 0 || rows == 0 {
+        return Err(format!("Invalid tile layout {}x{}", cols, rows));
+    }
+    Ok(())
+}
+
+// WebVTT timestamps always have hours and exactly three fraction digits.
+fn cue_time(time: Duration) -> String {
+    let millis = (time.as_micros() + 500) / 1000;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
@@ -0,0 +1,269 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/*
+Time values in ffmpeg's time duration syntax.
+
+    ``Timestamp`` is a position in a stream (``-ss``, ``-to``, ``trim`` start and
+    end) and ``Duration`` a length (``-t``).  Both are stored in microseconds, the
+    resolution ffmpeg itself parses, and accept every form ffmpeg does:
+
+        [-][HH:]MM:SS[.m...]     01:29, 1:02:03.5
+        [-]S+[.m...][s|ms|us]    89, 89.5s, 1500ms, 250us
+
+    Subtracting two timestamps gives a duration and adding a duration to a
+    timestamp gives a timestamp, so cut points are computed here rather than by
+    editing strings.  ``Display`` renders ``HH:MM:SS.mmm`` (microseconds only when
+    needed); ``format_seconds`` and ``format_micros`` render the other two forms.
+*/
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Timestamp {
+    micros: i64,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Duration {
+    micros: i64,
+}
+
+macro_rules! impl_time {
+    ($($time:ident),*) => {
+        $(
+            impl $time {
+                pub const ZERO: $time = $time { micros: 0 };
+
+                pub const fn from_micros(micros: i64) -> $time {
+                    $time { micros }
+                }
+
+                pub const fn from_millis(millis: i64) -> $time {
+                    $time { micros: millis * 1_000 }
+                }
+
+                pub const fn from_secs(secs: i64) -> $time {
+                    $time { micros: secs * MICROS_PER_SECOND }
+                }
+
+                // Rounded to the nearest microsecond.
+                pub fn from_secs_f64(secs: f64) -> $time {
+                    $time { micros: (secs * MICROS_PER_SECOND as f64).round() as i64 }
+                }
+
+                pub const fn as_micros(&self) -> i64 {
+                    self.micros
+                }
+
+                pub fn as_secs_f64(&self) -> f64 {
+                    self.micros as f64 / MICROS_PER_SECOND as f64
+                }
+
+                // `[-]S[.m...]`, e.g. `89.5`.
+                pub fn format_seconds(&self) -> String {
+                    let sign = if self.micros < 0 { "-" } else { "" };
+                    let micros = self.micros.unsigned_abs();
+                    let secs = micros / MICROS_PER_SECOND as u64;
+                    format!("{}{}{}", sign, secs, format_fraction(micros, false))
+                }
+
+                // `[-]Nus`, e.g. `89500000us`.
+                pub fn format_micros(&self) -> String {
+                    format!("{}us", self.micros)
+                }
+            }
+
+            impl fmt::Display for $time {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    let sign = if self.micros < 0 { "-" } else { "" };
+                    let micros = self.micros.unsigned_abs();
+                    let secs = micros / MICROS_PER_SECOND as u64;
+                    write!(
+                        f,
+                        "{}{:02}:{:02}:{:02}{}",
+                        sign,
+                        secs / 3600,
+                        secs / 60 % 60,
+                        secs % 60,
+                        format_fraction(micros, true)
+                    )
+                }
+            }
+
+            impl FromStr for $time {
+                type Err = String;
+
+                fn from_str(text: &str) -> Result<$time, String> {
+                    parse_micros(text)
+                        .map($time::from_micros)
+                        .ok_or_else(|| format!("Invalid time: {:?}", text))
+                }
+            }
+        )*
+    };
+}
+
+impl_time!(Timestamp, Duration);
+
+impl From<std::time::Duration> for Duration {
+    fn from(duration: std::time::Duration) -> Self {
+        Duration::from_micros(duration.as_micros() as i64)
+    }
+}
+
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, other: Duration) -> Timestamp {
+        Timestamp::from_micros(self.micros + other.micros)
+    }
+}
+
+impl Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, other: Duration) -> Timestamp {
+        Timestamp::from_micros(self.micros - other.micros)
+    }
+}
+
+impl Sub for Timestamp {
+    type Output = Duration;
+
+    fn sub(self, other: Timestamp) -> Duration {
+        Duration::from_micros(self.micros - other.micros)
+    }
+}
+
+impl AddAssign<Duration> for Timestamp {
+    fn add_assign(&mut self, other: Duration) {
+        self.micros += other.micros;
+    }
+}
+
+impl SubAssign<Duration> for Timestamp {
+    fn sub_assign(&mut self, other: Duration) {
+        self.micros -= other.micros;
+    }
+}
+
+impl Add for Duration {
+    type Output = Duration;
+
+    fn add(self, other: Duration) -> Duration {
+        Duration::from_micros(self.micros + other.micros)
+    }
+}
+
+impl Sub for Duration {
+    type Output = Duration;
+
+    fn sub(self, other: Duration) -> Duration {
+        Duration::from_micros(self.micros - other.micros)
+    }
+}
+
+impl AddAssign for Duration {
+    fn add_assign(&mut self, other: Duration) {
+        self.micros += other.micros;
+    }
+}
+
+impl SubAssign for Duration {
+    fn sub_assign(&mut self, other: Duration) {
+        self.micros -= other.micros;
+    }
+}
+
+impl Mul<i64> for Duration {
+    type Output = Duration;
+
+    fn mul(self, factor: i64) -> Duration {
+        Duration::from_micros(self.micros * factor)
+    }
+}
+
+impl Neg for Duration {
+    type Output = Duration;
+
+    fn neg(self) -> Duration {
+        Duration::from_micros(-self.micros)
+    }
+}
+
+// `.mmm`, or `.mmmmmm` when the value is not a whole number of milliseconds.
+// Without `pad`, trailing zeros are dropped and a whole number of seconds has no
+// fraction at all.
+fn format_fraction(micros: u64, pad: bool) -> String {
+    let fraction = micros % MICROS_PER_SECOND as u64;
+    if pad {
+        if fraction.is_multiple_of(1_000) {
+            format!(".{:03}", fraction / 1_000)
+        } else {
+            format!(".{:06}", fraction)
+        }
+    } else if fraction == 0 {
+        String::new()
+    } else {
+        format!(".{:06}", fraction).trim_end_matches('0').to_owned()
+    }
+}
+
+// Mirrors `av_parse_time` with `duration` set.
+fn parse_micros(text: &str) -> Option<i64> {
+    let (negative, text) = match text.trim().strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.trim()),
+    };
+
+    let micros = if text.contains(':') {
+        let (clock, fraction) = split_fraction(text);
+        let parts: Vec<&str> = clock.split(':').collect();
+        let (hours, minutes, seconds) = match parts[..] {
+            [minutes, seconds] => ("0", minutes, seconds),
+            [hours, minutes, seconds] => (hours, minutes, seconds),
+            _ => return None,
+        };
+        let hours = parse_digits(hours)?;
+        let minutes = parse_digits(minutes).filter(|minutes| *minutes < 60)?;
+        let seconds = parse_digits(seconds).filter(|seconds| *seconds < 60)?;
+        let secs = hours.checked_mul(3600)? + minutes * 60 + seconds;
+        secs.checked_mul(MICROS_PER_SECOND)? + parse_fraction(fraction)?
+    } else {
+        let (number, unit) = if let Some(number) = text.strip_suffix("ms") {
+            (number, 1_000)
+        } else if let Some(number) = text.strip_suffix("us") {
+            (number, 1)
+        } else {
+            (text.strip_suffix('s').unwrap_or(text), MICROS_PER_SECOND)
+        };
+        let (whole, fraction) = split_fraction(number);
+        // The fraction is parsed in microseconds and scaled down to the unit.
+        let whole = parse_digits(whole)?.checked_mul(unit)?;
+        whole + parse_fraction(fraction)? * unit / MICROS_PER_SECOND
+    };
+    Some(if negative { -micros } else { micros })
+}
+
+fn split_fraction(text: &str) -> (&str, &str) {
+    text.split_once('.').unwrap_or((text, ""))
+}
+
+fn parse_digits(text: &str) -> Option<i64> {
+    if text.is_empty() || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+// Digits after the decimal point as microseconds; like ffmpeg, only the first six count.
+fn parse_fraction(text: &str) -> Option<i64> {
+    if text.is_empty() {
+        return Some(0);
+    }
+    parse_digits(text)?;
+    let digits = &text[..text.len().min(6)];
+    Some(parse_digits(digits)? * 10_i64.pow(6 - digits.len() as u32))
+}
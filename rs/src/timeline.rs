@@ -0,0 +1,335 @@
+use crate::expr::{PTS, STARTPTS};
+use crate::ffmpeg::{concat, filter, filter_multi_output};
+use crate::node::{FilterableStream, Stream};
+use crate::time::{Duration, Timestamp};
+
+/*
+Timelines of clips, gaps and transitions.
+
+    A ``Timeline`` lists items in program order.  Every clip is cut from its
+    source with ``trim``/``atrim`` and restarted at zero with ``setpts``; gaps are
+    black video and silence in the timeline's format.  Consecutive items are
+    joined with ``concat`` unless a crossfade was requested between them, in
+    which case ``xfade``/``acrossfade`` overlap them by the crossfade duration:
+
+        Timeline::new(format)
+            .clip(Clip::new(&interview, Timestamp::from_secs(12), Timestamp::from_secs(40)))
+            .crossfade(Duration::from_secs(1))
+            .clip(Clip::new(&broll, Timestamp::ZERO, Timestamp::from_secs(8)))
+            .gap(Duration::from_secs(2))
+            .build()
+
+    Durations of every part are known up front, so ``xfade`` offsets and the
+    program duration are computed rather than measured.  Clips are expected to
+    already match the timeline's format; only gaps are generated in it.
+*/
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TimelineFormat {
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: u32,
+    pub sample_rate: u32,
+}
+
+#[derive(Clone)]
+pub struct Clip {
+    video: FilterableStream,
+    audio: FilterableStream,
+    start: Timestamp,
+    end: Timestamp,
+}
+
+impl Clip {
+    // The part of `source` between `start` and `end`, using its video and audio.
+    pub fn new(source: &FilterableStream, start: Timestamp, end: Timestamp) -> Clip {
+        Clip::from_streams(&source.video(), &source.audio(), start, end)
+    }
+
+    pub fn from_streams(
+        video: &FilterableStream,
+        audio: &FilterableStream,
+        start: Timestamp,
+        end: Timestamp,
+    ) -> Clip {
+        Clip {
+            video: video.clone(),
+            audio: audio.clone(),
+            start,
+            end,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Transition {
+    Cut,
+    // `xfade` with the named transition (e.g. `fade`, `wipeleft`) and `acrossfade`.
+    Crossfade {
+        transition: String,
+        duration: Duration,
+    },
+}
+
+// Lowers the music whenever the program audio is loud, with `sidechaincompress`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ducking {
+    pub threshold: f64,
+    pub ratio: f64,
+    // Milliseconds.
+    pub attack: f64,
+    pub release: f64,
+}
+
+impl Default for Ducking {
+    fn default() -> Self {
+        Ducking {
+            threshold: 0.05,
+            ratio: 8.0,
+            attack: 20.0,
+            release: 400.0,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Item {
+    Clip(Clip),
+    Gap(Duration),
+}
+
+#[derive(Clone)]
+pub struct Timeline {
+    format: TimelineFormat,
+    // Every item with the transition from the item before it.
+    items: Vec<(Transition, Item)>,
+    pending: Option<Transition>,
+    music: Option<(FilterableStream, Ducking)>,
+}
+
+// The compiled program.
+#[derive(Clone)]
+pub struct Program {
+    pub video: FilterableStream,
+    pub audio: FilterableStream,
+    pub duration: Duration,
+}
+
+// A part of the program being assembled: its streams and their duration.
+struct Segment {
+    video: FilterableStream,
+    audio: FilterableStream,
+    duration: Duration,
+}
+
+impl Timeline {
+    pub fn new(format: TimelineFormat) -> Timeline {
+        Timeline {
+            format,
+            items: Vec::new(),
+            pending: None,
+            music: None,
+        }
+    }
+
+    pub fn clip(self, clip: Clip) -> Self {
+        self.push(Item::Clip(clip))
+    }
+
+    pub fn gap(self, duration: Duration) -> Self {
+        self.push(Item::Gap(duration))
+    }
+
+    // Crossfades the previous item into the next one.
+    pub fn crossfade(self, duration: Duration) -> Self {
+        self.transition("fade", duration)
+    }
+
+    // Like `crossfade` with another `xfade` transition, e.g. `wipeleft`.
+    pub fn transition(mut self, transition: &str, duration: Duration) -> Self {
+        self.pending = Some(Transition::Crossfade {
+            transition: transition.to_owned(),
+            duration,
+        });
+        self
+    }
+
+    // Background music under the whole program, ducked under its audio.
+    pub fn music(mut self, audio: &FilterableStream, ducking: Ducking) -> Self {
+        self.music = Some((audio.clone(), ducking));
+        self
+    }
+
+    fn push(mut self, item: Item) -> Self {
+        let transition = self.pending.take().unwrap_or(Transition::Cut);
+        self.items.push((transition, item));
+        self
+    }
+
+    pub fn build(&self) -> Result<Program, String> {
+        if self.pending.is_some() {
+            return Err("Transition after the last item".to_owned());
+        }
+        match self.items.first() {
+            None => return Err("Timeline has no items".to_owned()),
+            Some((Transition::Crossfade { .. }, _)) => {
+                return Err("Transition before the first item".to_owned())
+            }
+            Some((Transition::Cut, _)) => {}
+        }
+
+        // Items joined by cuts are collected and concatenated together.
+        let mut run: Vec<Segment> = Vec::new();
+        for (i, (transition, item)) in self.items.iter().enumerate() {
+            let segment = self.segment(i, item)?;
+            match transition {
+                Transition::Cut => run.push(segment),
+                Transition::Crossfade {
+                    transition,
+                    duration,
+                } => {
+                    let previous = join(std::mem::take(&mut run))?;
+                    let overlap = *duration;
+                    if overlap <= Duration::ZERO
+                        || overlap > previous.duration
+                        || overlap > segment.duration
+                    {
+                        return Err(format!(
+                            "Crossfade of {} into item #{} is longer than the items it joins",
+                            overlap, i
+                        ));
+                    }
+                    let offset = previous.duration - overlap;
+                    let video = filter(
+                        [&previous.video, &segment.video],
+                        "xfade",
+                        crate::kwargs! {
+                            "transition" => transition,
+                            "duration" => overlap.format_seconds(),
+                            "offset" => offset.format_seconds(),
+                        },
+                    );
+                    let audio = filter(
+                        [&previous.audio, &segment.audio],
+                        "acrossfade",
+                        crate::kwargs! {"d" => overlap.format_seconds()},
+                    );
+                    run.push(Segment {
+                        video,
+                        audio,
+                        duration: previous.duration + segment.duration - overlap,
+                    });
+                }
+            }
+        }
+        let program = join(run)?;
+
+        let audio = match &self.music {
+            Some((music, ducking)) => duck(&program, music, ducking),
+            None => program.audio,
+        };
+        Ok(Program {
+            video: program.video,
+            audio,
+            duration: program.duration,
+        })
+    }
+
+    fn segment(&self, index: usize, item: &Item) -> Result<Segment, String> {
+        match item {
+            Item::Clip(clip) => {
+                if clip.end <= clip.start {
+                    return Err(format!("Clip #{} ends before it starts", index));
+                }
+                let (start, end) = (clip.start.format_seconds(), clip.end.format_seconds());
+                let video = clip
+                    .video
+                    .filter("trim", crate::kwargs! {"start" => start, "end" => end})
+                    .filter("setpts", crate::kwargs! {"expr" => PTS - STARTPTS});
+                let audio = clip
+                    .audio
+                    .filter("atrim", crate::kwargs! {"start" => start, "end" => end})
+                    .filter("asetpts", crate::kwargs! {"expr" => PTS - STARTPTS});
+                Ok(Segment {
+                    video,
+                    audio,
+                    duration: clip.end - clip.start,
+                })
+            }
+            Item::Gap(duration) => {
+                if *duration <= Duration::ZERO {
+                    return Err(format!("Gap #{} is empty", index));
+                }
+                let format = &self.format;
+                let video = filter(
+                    Vec::<Stream>::new(),
+                    "color",
+                    crate::kwargs! {
+                        "c" => "black",
+                        "s" => format!("{}x{}", format.width, format.height),
+                        "r" => format.frame_rate,
+                        "d" => duration.format_seconds(),
+                    },
+                );
+                let audio = filter(
+                    Vec::<Stream>::new(),
+                    "anullsrc",
+                    crate::kwargs! {
+                        "r" => format.sample_rate,
+                        "cl" => "stereo",
+                        "d" => duration.format_seconds(),
+                    },
+                );
+                Ok(Segment {
+                    video,
+                    audio,
+                    duration: *duration,
+                })
+            }
+        }
+    }
+}
+
+fn join(mut segments: Vec<Segment>) -> Result<Segment, String> {
+    if segments.len() == 1 {
+        return Ok(segments.remove(0));
+    }
+    let duration = segments
+        .iter()
+        .fold(Duration::ZERO, |total, segment| total + segment.duration);
+    let pairs: Vec<(FilterableStream, FilterableStream)> = segments
+        .into_iter()
+        .map(|segment| (segment.video, segment.audio))
+        .collect();
+    let (video, audio) = concat(&pairs).map_err(|error| error.to_string())?;
+    Ok(Segment {
+        video,
+        audio,
+        duration,
+    })
+}
+
+// [program]asplit[mix][key]; [music]atrim[trimmed]; [trimmed][key]sidechaincompress[ducked];
+// [mix][ducked]amix
+fn duck(program: &Segment, music: &FilterableStream, ducking: &Ducking) -> FilterableStream {
+    let split = filter_multi_output(&program.audio, "asplit", crate::kwargs! {});
+    let music = music.filter(
+        "atrim",
+        crate::kwargs! {"end" => program.duration.format_seconds()},
+    );
+    let ducked = filter(
+        [&music, &split.stream("1", None)],
+        "sidechaincompress",
+        crate::kwargs! {
+            "threshold" => ducking.threshold,
+            "ratio" => ducking.ratio,
+            "attack" => ducking.attack,
+            "release" => ducking.release,
+        },
+    );
+    filter(
+        [&split.stream("0", None), &ducked],
+        "amix",
+        crate::kwargs! {"inputs" => 2, "duration" => "first", "normalize" => 0},
+    )
+}
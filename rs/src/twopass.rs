@@ -0,0 +1,115 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::ffmpeg::compile;
+use crate::node::{get_stream_spec_node, OutputNode, StreamSpec};
+
+/*
+Two-pass encoding.
+
+    ``two_pass`` turns a graph with a single output into the two commands of a
+    two-pass encode.  Both share the inputs and filtergraph; the first pass only
+    analyses the video, writing its statistics to the passlog files and the
+    encoded output to the null muxer, always with ``-y``:
+
+        ... -pass 1 -passlogfile X -an -f null /dev/null
+        ... -pass 2 -passlogfile X output.mp4
+
+    ``TwoPass::run`` executes them in order and removes the passlog files
+    afterwards, whether or not the passes succeeded.
+*/
+
+#[cfg(windows)]
+const NULL_DEVICE: &str = "NUL";
+#[cfg(not(windows))]
+const NULL_DEVICE: &str = "/dev/null";
+
+#[derive(Clone, Debug)]
+pub struct TwoPass {
+    // Prefix of the statistics files, e.g. `x` for `x-0.log` and `x-0.log.mbtree`.
+    pub passlogfile: PathBuf,
+    pub first_pass: Vec<String>,
+    pub second_pass: Vec<String>,
+}
+
+pub fn two_pass(
+    stream_spec: impl Into<StreamSpec>,
+    cmd: &str,
+    overwrite_output: bool,
+    passlogfile: impl Into<PathBuf>,
+) -> Result<TwoPass, Box<dyn Error>> {
+    let nodes = get_stream_spec_node(stream_spec.into());
+    let outputs: Vec<OutputNode> = nodes.iter().filter_map(|node| node.as_output()).collect();
+    let [output] = &outputs[..] else {
+        return Err(format!(
+            "Two-pass encoding needs exactly one output, got {}",
+            outputs.len()
+        )
+        .into());
+    };
+    let passlogfile = passlogfile.into();
+    let passlog = passlogfile.to_string_lossy();
+
+    let first = output.with_kwargs(crate::kwargs! {
+        "pass" => 1,
+        "passlogfile" => passlog,
+        "an" => "",
+        "f" => "null",
+        "filename" => NULL_DEVICE,
+    });
+    let second = output.with_kwargs(crate::kwargs! {"pass" => 2, "passlogfile" => passlog});
+    Ok(TwoPass {
+        // The null device always exists; without `-y` ffmpeg asks to overwrite it.
+        first_pass: compile(first.stream("", None), cmd, true)?,
+        second_pass: compile(second.stream("", None), cmd, overwrite_output)?,
+        passlogfile,
+    })
+}
+
+impl TwoPass {
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        let result = run_command(&self.first_pass).and_then(|_| run_command(&self.second_pass));
+        let cleanup = self.remove_passlog_files();
+        result?;
+        Ok(cleanup?)
+    }
+
+    // Removes `{passlogfile}-N.log` and the encoder specific files next to it
+    // (`.log.mbtree`, and their `.temp` variants from an interrupted pass).
+    pub fn remove_passlog_files(&self) -> std::io::Result<()> {
+        let Some(file_name) = self.passlogfile.file_name() else {
+            return Ok(());
+        };
+        let prefix = format!("{}-", file_name.to_string_lossy());
+        let dir = match self.passlogfile.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let Some(rest) = name.strip_prefix(&prefix) else {
+                continue;
+            };
+            let is_passlog = rest
+                .split_once(".log")
+                .is_some_and(|(index, _)| index.parse::<u32>().is_ok());
+            if is_passlog {
+                fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn run_command(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let (program, args) = args.split_first().ok_or("Empty command")?;
+    let status = Command::new(program).args(args).status()?;
+    if !status.success() {
+        return Err(format!("{} failed: {}", program, status).into());
+    }
+    Ok(())
+}
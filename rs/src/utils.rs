@@ -0,0 +1,137 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::ffmpeg::filter_multi_output;
+use crate::node::{FilterNode, NodeType, Stream};
+
+// Backslash-escapes every occurrence of `chars` (and backslashes) in `text`.
+pub fn escape_chars(text: &str, chars: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '\\' || chars.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// Single quotes protect everything but a single quote, which is written as `'\''`.
+pub fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+// `quote`, unless `arg` is made of characters no POSIX shell treats specially.
+pub fn shell_quote(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./:=+,@%".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        arg.to_owned()
+    } else {
+        quote(arg)
+    }
+}
+
+// A file in the system temp directory, removed when dropped.
+pub struct TempFile {
+    path: PathBuf,
+}
+
+// Attempts at a fresh name before giving up on the temp directory.
+const MAX_ATTEMPTS: usize = 16;
+
+impl TempFile {
+    // The temp directory is shared, so the file is always created anew: a file or
+    // symlink already at the name gets another name with a random part.
+    pub fn create(suffix: &str, contents: &str) -> io::Result<TempFile> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let base = format!(
+            "ffmpeg-rs-{}-{}",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut name = format!("{}{}", base, suffix);
+        for _ in 0..MAX_ATTEMPTS {
+            let path = env::temp_dir().join(&name);
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    let temp_file = TempFile { path };
+                    file.write_all(contents.as_bytes())?;
+                    return Ok(temp_file);
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    let random = RandomState::new().build_hasher().finish();
+                    name = format!("{}-{:016x}{}", base, random, suffix);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("No free temp file name for {}", base),
+        ))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// Filter outputs mapped more than once, each handed out through its own output
+// of a split; a filter output can only be mapped once.
+pub struct SharedStreams {
+    uses: HashMap<u64, usize>,
+    splits: HashMap<u64, (FilterNode, usize)>,
+}
+
+impl SharedStreams {
+    pub fn new<'a>(streams: impl IntoIterator<Item = &'a Stream>) -> SharedStreams {
+        let mut uses = HashMap::new();
+        for stream in streams {
+            // Input streams can be mapped any number of times.
+            if stream.node.node_type() != NodeType::Input {
+                *uses.entry(stream.hash()).or_insert(0) += 1;
+            }
+        }
+        SharedStreams {
+            uses,
+            splits: HashMap::new(),
+        }
+    }
+
+    fn uses(&self, stream: &Stream) -> usize {
+        self.uses.get(&stream.hash()).copied().unwrap_or_default()
+    }
+
+    pub fn is_shared(&self, stream: &Stream) -> bool {
+        self.uses(stream) > 1
+    }
+
+    // `stream`, or the next output of its `split_filter` when it is shared.
+    pub fn take(&mut self, stream: &Stream, split_filter: &str) -> Stream {
+        let uses = self.uses(stream);
+        if uses < 2 {
+            return stream.clone();
+        }
+        let (split, next) = self.splits.entry(stream.hash()).or_insert_with(|| {
+            let split =
+                filter_multi_output(stream, split_filter, crate::kwargs! {"outputs" => uses});
+            (split, 0)
+        });
+        let output = split.stream(&next.to_string(), None);
+        *next += 1;
+        Stream::clone(&output)
+    }
+}
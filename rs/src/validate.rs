@@ -0,0 +1,291 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::catalog::{FilterCatalog, FilterInfo, FilterOption, MediaType, OptionType, Pads};
+use crate::daq::{DagNode, OutgoingEdgeMaps};
+use crate::node::{FilterNode, Node};
+
+/*
+Filter graph validation against a filter catalog.
+
+    Every filter node is checked for an unknown filter name, unknown option keys,
+    option values outside the option's type or range, and the number and media
+    type of its connected inputs and outputs.  Diagnostics point at the node by its
+    position in the ``-filter_complex`` chain, which is also the topological order.
+*/
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DiagnosticKind {
+    UnknownFilter,
+    UnknownOption,
+    InvalidValue,
+    OutOfRange,
+    InputCount,
+    OutputCount,
+    MediaType,
+}
+
+#[derive(Clone)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    // Position of the node among the filters of the compiled filtergraph.
+    pub filter_index: usize,
+    pub node: Node,
+    pub message: String,
+}
+
+impl fmt::Debug for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Diagnostic")
+            .field("kind", &self.kind)
+            .field("filter_index", &self.filter_index)
+            .field("filter", &self.node.name())
+            .field("message", &self.message)
+            .finish()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "filter #{} `{}`: {}",
+            self.filter_index,
+            self.node.name(),
+            self.message
+        )
+    }
+}
+
+// Returned by `ffmpeg::compile_validated` when the graph has diagnostics.
+#[derive(Debug)]
+pub struct ValidationError {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self.diagnostics.iter().map(ToString::to_string).collect();
+        write!(f, "Invalid filter graph: {}", messages.join("; "))
+    }
+}
+
+impl Error for ValidationError {}
+
+pub fn validate_filters(
+    filter_nodes: &[FilterNode],
+    outgoing_edge_maps: &OutgoingEdgeMaps<Node>,
+    catalog: &FilterCatalog,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (filter_index, filter_node) in filter_nodes.iter().enumerate() {
+        let node = filter_node.node();
+        let mut report = |kind: DiagnosticKind, message: String| {
+            diagnostics.push(Diagnostic {
+                kind,
+                filter_index,
+                node: node.clone(),
+                message,
+            })
+        };
+
+        let Some(filter) = catalog.get(node.name()) else {
+            report(
+                DiagnosticKind::UnknownFilter,
+                format!("unknown filter `{}`", node.name()),
+            );
+            continue;
+        };
+
+        for (key, value) in node.kwargs() {
+            match filter.get_option(key) {
+                Some(option) => {
+                    if let Err((kind, message)) = check_value(option, value) {
+                        report(kind, message);
+                    }
+                }
+                None if key == "enable" && filter.timeline => {}
+                None => report(
+                    DiagnosticKind::UnknownOption,
+                    format!("unknown option `{}`", key),
+                ),
+            }
+        }
+
+        check_inputs(filter, node, &mut report);
+
+        let output_count = outgoing_edge_maps
+            .get(&node.hash())
+            .map(|outgoing_edge_map| outgoing_edge_map.len())
+            .unwrap_or_default();
+        if let Some(expected) = filter.outputs.len() {
+            if output_count > expected {
+                report(
+                    DiagnosticKind::OutputCount,
+                    format!(
+                        "expected at most {} outputs, got {}",
+                        expected, output_count
+                    ),
+                );
+            }
+        }
+    }
+    diagnostics
+}
+
+fn check_inputs(filter: &FilterInfo, node: &Node, report: &mut impl FnMut(DiagnosticKind, String)) {
+    let Pads::Fixed(pads) = &filter.inputs else {
+        return;
+    };
+    let incoming_edges = node.get_incoming_edges();
+    if incoming_edges.len() != pads.len() {
+        report(
+            DiagnosticKind::InputCount,
+            format!(
+                "expected {} inputs, got {}",
+                pads.len(),
+                incoming_edges.len()
+            ),
+        );
+    }
+    for (i, (pad, edge)) in pads.iter().zip(&incoming_edges).enumerate() {
+        let selected = match edge.upstream_selector {
+            Some('a') => MediaType::Audio,
+            Some('v') => MediaType::Video,
+            Some('s') => {
+                report(
+                    DiagnosticKind::MediaType,
+                    format!(
+                        "input #{} `{}` expects {:?} but is connected to a subtitle stream",
+                        i, pad.name, pad.media_type
+                    ),
+                );
+                continue;
+            }
+            _ => continue,
+        };
+        if selected != pad.media_type {
+            report(
+                DiagnosticKind::MediaType,
+                format!(
+                    "input #{} `{}` expects {:?} but is connected to {:?}",
+                    i, pad.name, pad.media_type, selected
+                ),
+            );
+        }
+    }
+}
+
+fn check_value(option: &FilterOption, value: &str) -> Result<(), (DiagnosticKind, String)> {
+    let invalid = || {
+        (
+            DiagnosticKind::InvalidValue,
+            format!(
+                "invalid value `{}` for option `{}` of type {:?}",
+                value, option.name, option.option_type
+            ),
+        )
+    };
+    let is_constant = |name: &str| {
+        option
+            .constants
+            .iter()
+            .any(|constant| constant.name == name)
+    };
+
+    match option.option_type {
+        OptionType::Flags => {
+            let flags = value.split(['+', '-']).filter(|flag| !flag.is_empty());
+            for flag in flags {
+                if !is_constant(flag) && flag.parse::<i64>().is_err() {
+                    return Err(invalid());
+                }
+            }
+            Ok(())
+        }
+        OptionType::Boolean => match value {
+            "true" | "false" | "1" | "0" | "yes" | "no" | "on" | "off" | "enable" | "disable" => {
+                Ok(())
+            }
+            _ if is_constant(value) => Ok(()),
+            _ => Err(invalid()),
+        },
+        ref option_type if option_type.is_numeric() => {
+            // Numeric options are evaluated by ffmpeg, so expressions are left alone.
+            if is_constant(value) || is_expression(value) {
+                return Ok(());
+            }
+            let number = parse_number(value).ok_or_else(invalid)?;
+            let below = option.min.is_some_and(|min| number < min);
+            let above = option.max.is_some_and(|max| number > max);
+            if below || above {
+                return Err((
+                    DiagnosticKind::OutOfRange,
+                    format!(
+                        "value {} for option `{}` is out of range [{}, {}]",
+                        value,
+                        option.name,
+                        option.min.unwrap_or(f64::NEG_INFINITY),
+                        option.max.unwrap_or(f64::INFINITY)
+                    ),
+                ));
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn is_expression(value: &str) -> bool {
+    value.chars().skip(1).any(|c| "+-*/^()".contains(c)) && parse_number(value).is_none()
+}
+
+// SI prefixes and their powers of ten; the ones from `k` up are powers of 1024 when
+// followed by `i`.
+const SI_PREFIXES: [(char, i32); 20] = [
+    ('y', -24),
+    ('z', -21),
+    ('a', -18),
+    ('f', -15),
+    ('p', -12),
+    ('n', -9),
+    ('u', -6),
+    ('m', -3),
+    ('c', -2),
+    ('d', -1),
+    ('h', 2),
+    ('k', 3),
+    ('K', 3),
+    ('M', 6),
+    ('G', 9),
+    ('T', 12),
+    ('P', 15),
+    ('E', 18),
+    ('Z', 21),
+    ('Y', 24),
+];
+
+// A number as `av_strtod` reads it, e.g. `48k`, `1.5M`, `2Mi` or `64KiB`: a
+// prefix scales it, and a trailing `B` (bytes) multiplies it by 8.
+fn parse_number(value: &str) -> Option<f64> {
+    if let Ok(number) = value.parse() {
+        return Some(number);
+    }
+    let (rest, bytes) = match value.strip_suffix('B') {
+        Some(rest) => (rest, 8.0),
+        None => (value, 1.0),
+    };
+    let (rest, binary) = match rest.strip_suffix('i') {
+        Some(rest) => (rest, true),
+        None => (rest, false),
+    };
+    let prefix = rest.chars().last()?;
+    let &(_, exponent) = SI_PREFIXES.iter().find(|(name, _)| *name == prefix)?;
+    let number: f64 = rest[..rest.len() - prefix.len_utf8()].parse().ok()?;
+    let scale = match binary {
+        true if exponent > 0 => 1024f64.powi(exponent / 3),
+        true => return None,
+        false => 10f64.powi(exponent),
+    };
+    Some(number * scale * bytes)
+}
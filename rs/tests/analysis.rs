@@ -0,0 +1,205 @@
+mod common;
+
+use ffmpeg_rs::analysis::{
+    blackdetect, cropdetect, ebur128, loudnorm, scene_changes, silencedetect, volumedetect, Crop,
+    Ebur128Summary, Interval, LoudnormMeasurement, LoudnormTarget, Volume,
+};
+use ffmpeg_rs::time::{Duration, Timestamp};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+use common::fixture;
+
+fn at(millis: i64) -> Timestamp {
+    Timestamp::from_micros(millis * 1000)
+}
+
+#[test]
+fn graphs() {
+    let input = ffmpeg::input("movie.mp4", kwargs! {});
+    assert_eq!(
+        ffmpeg::get_args(silencedetect(&input, -50.0, Duration::from_millis(500)).output())
+            .unwrap(),
+        [
+            "-i",
+            "movie.mp4",
+            "-filter_complex",
+            "[0:a]silencedetect=noise=-50dB:duration=0.5[s0]",
+            "-map",
+            "[s0]",
+            "-f",
+            "null",
+            "-"
+        ]
+    );
+    assert_eq!(
+        ffmpeg::get_args(scene_changes(&input, 0.4).output()).unwrap()[3],
+        "[0:v]select=expr=gt(scene\\,0.4)[s0];[s0]showinfo[s1]"
+    );
+    assert_eq!(
+        ffmpeg::get_args(loudnorm(&input, LoudnormTarget::default()).output()).unwrap()[3],
+        "[0:a]loudnorm=I=-24:TP=-2:LRA=7:print_format=json[s0]"
+    );
+    assert_eq!(
+        ffmpeg::get_args(blackdetect(&input, Duration::from_secs(2), 0.1).output()).unwrap()[3],
+        "[0:v]blackdetect=d=2:pix_th=0.1[s0]"
+    );
+    assert_eq!(
+        ffmpeg::get_args(ebur128(&input).output()).unwrap()[3],
+        "[0:a]ebur128=peak=true[s0]"
+    );
+
+    // Filtered and selected streams are analysed as they are.
+    let trimmed = input.audio().filter("atrim", kwargs! {"end" => 60});
+    assert_eq!(
+        ffmpeg::get_args(volumedetect(&trimmed).output()).unwrap()[3],
+        "[0:a]atrim=end=60[s0];[s0]volumedetect[s1]"
+    );
+    assert_eq!(
+        ffmpeg::get_args(cropdetect(&input.video()).output()).unwrap()[3],
+        "[0:v]cropdetect[s0]"
+    );
+}
+
+#[test]
+fn loudness() {
+    let input = ffmpeg::input("episode.wav", kwargs! {});
+    assert_eq!(
+        ebur128(&input)
+            .parse(&fixture("analysis/ebur128.txt"))
+            .unwrap(),
+        Ebur128Summary {
+            integrated: -19.6,
+            integrated_threshold: -29.8,
+            loudness_range: 6.5,
+            lra_threshold: -39.7,
+            lra_low: -24.1,
+            lra_high: -17.6,
+            true_peak: Some(-1.2),
+        }
+    );
+    assert_eq!(
+        loudnorm(&input, LoudnormTarget::default())
+            .parse(&fixture("analysis/loudnorm.txt"))
+            .unwrap(),
+        LoudnormMeasurement {
+            input_i: -27.61,
+            input_tp: -4.47,
+            input_lra: 18.06,
+            input_thresh: -39.2,
+            output_i: -16.58,
+            output_tp: -1.5,
+            output_lra: 14.78,
+            output_thresh: -27.71,
+            normalization_type: "dynamic".to_owned(),
+            target_offset: 0.58,
+        }
+    );
+    assert_eq!(
+        volumedetect(&input)
+            .parse(&fixture("analysis/volumedetect.txt"))
+            .unwrap(),
+        Volume {
+            n_samples: 2646000,
+            mean_volume: -23.4,
+            max_volume: -4.2,
+        }
+    );
+
+    let silence = fixture("analysis/silencedetect.txt");
+    assert!(ebur128(&input).parse(&silence).is_err());
+    assert!(loudnorm(&input, LoudnormTarget::default())
+        .parse(&silence)
+        .is_err());
+    assert!(volumedetect(&input).parse(&silence).is_err());
+}
+
+#[test]
+fn intervals() {
+    let input = ffmpeg::input("movie.mp4", kwargs! {});
+    assert_eq!(
+        silencedetect(&input, -50.0, Duration::from_millis(500))
+            .parse(&fixture("analysis/silencedetect.txt"))
+            .unwrap(),
+        [
+            Interval {
+                start: Timestamp::from_micros(-1330),
+                end: Some(at(1200)),
+            },
+            Interval {
+                start: Timestamp::from_micros(4_517_240),
+                end: Some(Timestamp::from_micros(6_804_540)),
+            },
+            Interval {
+                start: at(10_500),
+                end: None,
+            },
+        ]
+    );
+    assert_eq!(
+        blackdetect(&input, Duration::from_secs(2), 0.1)
+            .parse(&fixture("analysis/blackdetect.txt"))
+            .unwrap(),
+        [
+            Interval {
+                start: Timestamp::ZERO,
+                end: Some(Timestamp::from_micros(2_041_670)),
+            },
+            Interval {
+                start: at(57_500),
+                end: Some(Timestamp::from_micros(60_041_700)),
+            },
+        ]
+    );
+    assert_eq!(
+        scene_changes(&input, 0.4)
+            .parse(&fixture("analysis/scene.txt"))
+            .unwrap(),
+        [
+            Timestamp::from_micros(12_512_500),
+            Timestamp::from_micros(31_072_700),
+            Timestamp::from_micros(50_300_300),
+        ]
+    );
+}
+
+#[test]
+fn crop() {
+    let input = ffmpeg::input("movie.mp4", kwargs! {});
+    let crop = cropdetect(&input)
+        .parse(&fixture("analysis/cropdetect.txt"))
+        .unwrap();
+    assert_eq!(
+        crop,
+        Crop {
+            width: 1920,
+            height: 800,
+            x: 0,
+            y: 140,
+        }
+    );
+    assert_eq!(crop.to_string(), "1920:800:0:140");
+    assert_eq!(
+        ffmpeg::get_args(crop.apply(&input.video()).output("out.mp4", kwargs! {})).unwrap()[3],
+        "[0:v]crop=w=1920:h=800:x=0:y=140[s0]"
+    );
+    assert_eq!(
+        cropdetect(&input).parse("").unwrap_err(),
+        "No cropdetect results in ffmpeg output"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn run_parses_stderr() {
+    let fake = common::fake_ffmpeg(
+        "analysis",
+        concat!(
+            "cat '",
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/analysis/volumedetect.txt' >&2"
+        ),
+    );
+    let input = ffmpeg::input("episode.wav", kwargs! {});
+    let volume = volumedetect(&input).run(fake.cmd()).unwrap();
+    assert_eq!(volume.max_volume, -4.2);
+}
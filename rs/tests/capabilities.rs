@@ -0,0 +1,123 @@
+mod common;
+
+use ffmpeg_rs::capabilities::{
+    parse_codecs, parse_formats, parse_pix_fmts, parse_protocols, parse_version, Capabilities,
+    CodecType,
+};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+use common::fixture;
+
+fn fixture_capabilities() -> Capabilities {
+    Capabilities::from_runner(|flag| {
+        let name = match flag {
+            "-filters" => "filters/filters.txt".to_owned(),
+            _ => format!("capabilities/{}.txt", flag.trim_start_matches('-')),
+        };
+        Ok(fixture(&name))
+    })
+    .unwrap()
+}
+
+#[test]
+fn version() {
+    let (version, configuration) = parse_version(&fixture("capabilities/version.txt")).unwrap();
+    assert_eq!(version, "6.1.1");
+    assert_eq!(configuration[0], "--prefix=/usr");
+    assert!(configuration.contains(&"--enable-libx264".to_owned()));
+    assert!(parse_version("").is_err());
+}
+
+#[test]
+fn codecs() {
+    let encoders = parse_codecs(&fixture("capabilities/encoders.txt")).unwrap();
+    assert_eq!(encoders.len(), 18);
+    let libx264 = &encoders["libx264"];
+    assert_eq!(libx264.codec_type, CodecType::Video);
+    assert_eq!(
+        libx264.description,
+        "libx264 H.264 / AVC / MPEG-4 AVC / MPEG-4 part 10 (codec h264)"
+    );
+    assert!(encoders["opus"].experimental);
+    assert_eq!(encoders["libvpx-vp9"].codec_type, CodecType::Video);
+    assert_eq!(encoders["mov_text"].codec_type, CodecType::Subtitle);
+
+    let decoders = parse_codecs(&fixture("capabilities/decoders.txt")).unwrap();
+    assert_eq!(decoders["aac"].codec_type, CodecType::Audio);
+    assert!(!decoders.contains_key("libx264"));
+}
+
+#[test]
+fn formats() {
+    let muxers = parse_formats(&fixture("capabilities/muxers.txt"), 'E');
+    assert!(muxers.contains("mp4") && muxers.contains("hls") && muxers.contains("tee"));
+    assert!(!muxers.contains("concat"));
+
+    let demuxers = parse_formats(&fixture("capabilities/demuxers.txt"), 'D');
+    for name in ["concat", "mov", "mp4", "3g2", "matroska", "webm"] {
+        assert!(demuxers.contains(name), "{}", name);
+    }
+    assert!(!demuxers.contains("segment"));
+}
+
+#[test]
+fn pix_fmts_and_protocols() {
+    let pix_fmts = parse_pix_fmts(&fixture("capabilities/pix_fmts.txt"));
+    assert_eq!(pix_fmts.len(), 13);
+    assert!(pix_fmts.contains("yuv420p") && pix_fmts.contains("vaapi"));
+    assert!(!pix_fmts.contains("NAME"));
+
+    let protocols = parse_protocols(&fixture("capabilities/protocols.txt"));
+    assert!(protocols.input.contains("concat") && !protocols.output.contains("concat"));
+    assert!(protocols.output.contains("pipe"));
+}
+
+#[test]
+fn queries() {
+    let capabilities = fixture_capabilities();
+    assert_eq!(capabilities.version, "6.1.1");
+    assert!(capabilities.has_encoder("libx264") && !capabilities.has_encoder("libsvtav1"));
+    assert!(capabilities.has_configuration("--enable-gpl"));
+    assert_eq!(
+        capabilities.first_encoder(&["libsvtav1", "libx265", "mpeg4"]),
+        Some("libx265")
+    );
+    assert_eq!(capabilities.first_encoder(&["libsvtav1"]), None);
+    assert!(capabilities.has_filter("overlay") && capabilities.has_muxer("webm"));
+    assert!(capabilities.has_input_protocol("https") && capabilities.has_pix_fmt("nv12"));
+
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let output = input
+        .filter("zscale", kwargs! {})
+        .filter("hflip", kwargs! {})
+        .filter("lut3d", kwargs! {})
+        .output("output.mp4", kwargs! {});
+    assert_eq!(capabilities.missing_filters(&output), ["lut3d", "zscale"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn detect_is_cached_per_binary() {
+    use std::fs;
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime};
+
+    let fixtures = format!("{}/tests/fixtures", env!("CARGO_MANIFEST_DIR"));
+    let fake = common::fake_ffmpeg(
+        "capabilities",
+        &format!(
+            "case \"$2\" in\n-filters) cat {0}/filters/filters.txt ;;\n*) cat {0}/capabilities/${{2#-}}.txt ;;\nesac",
+            fixtures
+        ),
+    );
+    let path = fake.path();
+
+    let first = Capabilities::detect(path).unwrap();
+    assert!(first.has_encoder("libx264") && first.has_filter("scale"));
+    assert!(Arc::ptr_eq(&first, &Capabilities::detect(path).unwrap()));
+
+    let file = fs::File::open(path).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(60))
+        .unwrap();
+    assert!(!Arc::ptr_eq(&first, &Capabilities::detect(path).unwrap()));
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use ffmpeg_rs::catalog::{
+    parse_filter_help, parse_filter_list, FilterCatalog, MediaType, OptionType, Pads,
+};
+
+use common::fixture;
+
+#[test]
+fn filter_list() {
+    let filters = parse_filter_list(&fixture("filters/filters.txt")).unwrap();
+    assert_eq!(filters.len(), 27);
+
+    let overlay = filters
+        .iter()
+        .find(|filter| filter.name == "overlay")
+        .unwrap();
+    assert!(overlay.timeline && overlay.slice_threads && overlay.command);
+    assert_eq!(
+        overlay.description,
+        "Overlay a video source on top of the input."
+    );
+    assert_eq!(overlay.inputs.len(), Some(2));
+    assert_eq!(overlay.outputs.len(), Some(1));
+
+    let anullsrc = filters
+        .iter()
+        .find(|filter| filter.name == "anullsrc")
+        .unwrap();
+    assert!(anullsrc.inputs.is_empty());
+    let nullsink = filters
+        .iter()
+        .find(|filter| filter.name == "nullsink")
+        .unwrap();
+    assert!(nullsink.outputs.is_empty());
+    let split = filters
+        .iter()
+        .find(|filter| filter.name == "split")
+        .unwrap();
+    assert_eq!(split.outputs, Pads::Dynamic);
+}
+
+#[test]
+fn filter_help() {
+    let overlay = parse_filter_help(&fixture("filters/help/overlay.txt")).unwrap();
+    assert_eq!(overlay.name, "overlay");
+    assert!(overlay.timeline && overlay.slice_threads);
+    match &overlay.inputs {
+        Pads::Fixed(pads) => {
+            let names: Vec<&str> = pads.iter().map(|pad| pad.name.as_str()).collect();
+            assert_eq!(names, ["main", "overlay"]);
+            assert!(pads.iter().all(|pad| pad.media_type == MediaType::Video));
+        }
+        Pads::Dynamic => panic!("overlay has fixed inputs"),
+    }
+
+    // Options repeated by the `framesync` child class are only listed once.
+    let names: Vec<&str> = overlay
+        .options
+        .iter()
+        .map(|option| option.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "x",
+            "y",
+            "eof_action",
+            "eval",
+            "shortest",
+            "format",
+            "repeatlast",
+            "alpha",
+            "ts_sync_mode"
+        ]
+    );
+
+    let x = overlay.get_option("x").unwrap();
+    assert_eq!(x.option_type, OptionType::String);
+    assert_eq!(x.description, "set the x expression");
+    assert_eq!(x.default.as_deref(), Some("0"));
+
+    let format = overlay.get_option("format").unwrap();
+    assert_eq!(format.option_type, OptionType::Int);
+    assert_eq!((format.min, format.max), (Some(0.0), Some(7.0)));
+    assert_eq!(format.default.as_deref(), Some("yuv420"));
+    assert_eq!(format.constants.len(), 8);
+    assert_eq!(format.constants[1].name, "yuv420p10");
+    assert_eq!(format.constants[1].value, "1");
+
+    let eof_action = overlay.get_option("eof_action").unwrap();
+    assert_eq!(
+        eof_action.description,
+        "Action to take when encountering EOF from secondary input"
+    );
+    assert_eq!(
+        eof_action.constants[2].description,
+        "Pass through the main input."
+    );
+}
+
+#[test]
+fn filter_help_limits() {
+    let trim = parse_filter_help(&fixture("filters/help/trim.txt")).unwrap();
+    let start_pts = trim.get_option("start_pts").unwrap();
+    assert_eq!(start_pts.option_type, OptionType::Int64);
+    assert_eq!(
+        (start_pts.min, start_pts.max),
+        (Some(i64::MIN as f64), Some(i64::MAX as f64))
+    );
+    assert_eq!(
+        trim.get_option("start").unwrap().option_type,
+        OptionType::Duration
+    );
+
+    let fps = parse_filter_help(&fixture("filters/help/fps.txt")).unwrap();
+    let start_time = fps.get_option("start_time").unwrap();
+    assert_eq!(
+        (start_time.min, start_time.max),
+        (Some(-f64::MAX), Some(f64::MAX))
+    );
+
+    let amix = parse_filter_help(&fixture("filters/help/amix.txt")).unwrap();
+    assert_eq!(amix.inputs, Pads::Dynamic);
+    assert_eq!(
+        amix.get_option("dropout_transition").unwrap().max,
+        Some(i32::MAX as f64)
+    );
+    assert_eq!(
+        amix.get_option("weights").unwrap().default.as_deref(),
+        Some("1 1")
+    );
+
+    let anullsrc = parse_filter_help(&fixture("filters/help/anullsrc.txt")).unwrap();
+    assert!(anullsrc.inputs.is_empty());
+    assert_eq!(
+        anullsrc.get_option("cl").unwrap().option_type,
+        OptionType::ChannelLayout
+    );
+}
+
+#[test]
+fn catalog_merges_help() {
+    let help = fixture("filters/help/scale.txt");
+    let catalog = FilterCatalog::from_dumps(&fixture("filters/filters.txt"), &[&help]).unwrap();
+    assert_eq!(catalog.len(), 27);
+
+    let scale = catalog.get("scale").unwrap();
+    assert!(scale.command);
+    assert_eq!(scale.options.len(), 14);
+    assert!(catalog.get("hflip").unwrap().options.is_empty());
+    assert!(!catalog.contains("zscale"));
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use ffmpeg_rs::codegen;
+use ffmpeg_rs::filters::{self, OverlayEval, ScaleEval};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+use common::fixture_catalog;
+
+#[test]
+fn generated_filters_are_up_to_date() {
+    // Regenerate with `cargo run --features cli -- codegen tests/fixtures/filters/filters.txt tests/fixtures/filters/help/*.txt > src/filters.rs`
+    assert_eq!(
+        codegen::generate(&fixture_catalog()),
+        include_str!("../src/filters.rs")
+    );
+}
+
+#[test]
+fn generated_builders() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let logo = ffmpeg::input("logo.png", kwargs! {});
+    let scaled = filters::scale(input.video())
+        .w("1280")
+        .h("-2")
+        .eval(ScaleEval::Frame)
+        .stream();
+    let overlaid = filters::overlay(&scaled, &logo)
+        .x("W-w-10")
+        .eval(OverlayEval::Init)
+        .shortest(true)
+        .stream();
+    let output = overlaid.output("output.mp4", kwargs! {});
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i input.mp4 -i logo.png -filter_complex \
+         [0:v]scale=w=1280:h=-2:eval=frame[s0];[s0][1]overlay=x=W-w-10:eval=init:shortest=true[s1] \
+         -map [s1] output.mp4 -y"
+    );
+}
+
+#[test]
+fn generated_source_and_multi_output_builders() {
+    let silence = filters::anullsrc().sample_rate(48000).d("5").stream();
+    let split = filters::asplit(&silence).node();
+    let left = split.stream("0", None).output("left.wav", kwargs! {});
+    let right = filters::volume(split.stream("1", None))
+        .volume("0.5")
+        .stream()
+        .output("right.wav", kwargs! {});
+    assert_eq!(
+        ffmpeg::compile([left, right], "ffmpeg", true)
+            .unwrap()
+            .join(" "),
+        "ffmpeg -hide_banner -filter_complex \
+         anullsrc=sample_rate=48000:d=5[s0];[s0]asplit[s1][s2];[s2]volume=volume=0.5[s3] \
+         -map [s1] left.wav -map [s3] right.wav -y"
+    );
+}
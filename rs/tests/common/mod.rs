@@ -0,0 +1,77 @@
+// Shared by several test crates, each using a subset of the helpers.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ffmpeg_rs::catalog::FilterCatalog;
+
+pub fn fixture(path: &str) -> String {
+    fs::read_to_string(format!(
+        "{}/tests/fixtures/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        path
+    ))
+    .unwrap()
+}
+
+pub fn fixture_catalog() -> FilterCatalog {
+    let dir = format!("{}/tests/fixtures/filters/help", env!("CARGO_MANIFEST_DIR"));
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+    let helps: Vec<String> = paths
+        .iter()
+        .map(|path| fs::read_to_string(path).unwrap())
+        .collect();
+    let helps: Vec<&str> = helps.iter().map(String::as_str).collect();
+    FilterCatalog::from_dumps(&fixture("filters/filters.txt"), &helps).unwrap()
+}
+
+// A stand-in for ffmpeg running the shell `script`, whatever its arguments.  It
+// lives in a temp dir of its own, removed with everything in it on drop.
+pub struct FakeFfmpeg {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl FakeFfmpeg {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn cmd(&self) -> &str {
+        self.path.to_str().unwrap()
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for FakeFfmpeg {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(unix)]
+pub fn fake_ffmpeg(name: &str, script: &str) -> FakeFfmpeg {
+    use std::os::unix::fs::PermissionsExt;
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "ffmpeg-rs-{}-{}-{}",
+        name,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("ffmpeg");
+    fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    FakeFfmpeg { dir, path }
+}
@@ -0,0 +1,92 @@
+use std::fs;
+
+use ffmpeg_rs::concat::{ConcatFile, ConcatList};
+use ffmpeg_rs::time::{Duration, Timestamp};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn render() {
+    let list = ConcatList::new()
+        .file(ConcatFile::new("/media/intro.mp4").outpoint(Timestamp::from_secs(5)))
+        .file(
+            ConcatFile::new("/media/it's here.mp4")
+                .inpoint(Timestamp::from_millis(1500))
+                .duration(Duration::from_secs(30))
+                .metadata("title", "Main part"),
+        )
+        .file("clips/outro.mp4");
+    assert_eq!(
+        list.render(),
+        "ffconcat version 1.0\n\
+         file '/media/intro.mp4'\n\
+         outpoint 5\n\
+         file '/media/it'\\''s here.mp4'\n\
+         inpoint 1.5\n\
+         duration 30\n\
+         file_packet_meta 'title' 'Main part'\n\
+         file 'clips/outro.mp4'\n"
+    );
+}
+
+#[test]
+fn temp_file_lifetime() {
+    let list = ConcatList::new().file("/media/a.mp4").file("b.mp4");
+    let input = list.input(kwargs! {"re" => ""}).unwrap();
+    let path = input.node().kwargs().last().unwrap().1.clone();
+    let script = fs::read_to_string(&path).unwrap();
+    let cwd = std::env::current_dir().unwrap();
+    assert!(script.contains("file '/media/a.mp4'\n"));
+    assert!(script.contains(&format!("file '{}'\n", cwd.join("b.mp4").display())));
+
+    let command = ffmpeg::output(
+        input.stream("", None),
+        "output.mp4",
+        kwargs! {"c" => "copy"},
+    )
+    .compile_command("ffmpeg", true)
+    .unwrap();
+    assert_eq!(
+        command.args.join(" "),
+        format!(
+            "ffmpeg -hide_banner -f concat -safe 0 -re -i {} -c copy output.mp4 -y",
+            path
+        )
+    );
+    assert_eq!(command.temp_files(), [std::path::Path::new(&path)]);
+
+    // The graph is gone, but the command still needs the list.
+    drop(input);
+    assert!(fs::metadata(&path).is_ok());
+    drop(command);
+    assert!(fs::metadata(&path).is_err());
+
+    assert!(ConcatList::new().input(kwargs! {}).is_err());
+}
+
+#[test]
+fn temp_file_names_taken() {
+    // Every predictable name is taken, as if planted by another user.
+    let planted: Vec<_> = (0..64)
+        .map(|i| {
+            std::env::temp_dir().join(format!("ffmpeg-rs-{}-{}.ffconcat", std::process::id(), i))
+        })
+        .collect();
+    for path in &planted {
+        fs::write(path, "planted").unwrap();
+    }
+    let input = ConcatList::new()
+        .file("/media/a.mp4")
+        .input(kwargs! {})
+        .unwrap();
+    let path = input.node().kwargs().last().unwrap().1.clone();
+    assert!(!planted
+        .iter()
+        .any(|planted| planted.to_str() == Some(&path)));
+    assert!(fs::read_to_string(&path)
+        .unwrap()
+        .contains("file '/media/a.mp4'\n"));
+    for path in &planted {
+        assert_eq!(fs::read_to_string(path).unwrap(), "planted");
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+use ffmpeg_rs::dash::{AdaptationSet, DashOutput, Representation};
+use ffmpeg_rs::encoder::{LibOpus, LibVpxVp9};
+use ffmpeg_rs::time::Duration;
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn adaptation_sets() {
+    let input = ffmpeg::input("input.mkv", kwargs! {});
+    let split = ffmpeg::filter_multi_output(input.video(), "split", kwargs! {});
+    let hd = split
+        .stream("0", None)
+        .filter("scale", kwargs! {"w" => -2, "h" => 1080});
+    let sd = split
+        .stream("1", None)
+        .filter("scale", kwargs! {"w" => -2, "h" => 480});
+    let vp9 = |crf, bitrate| LibVpxVp9 {
+        crf: Some(crf),
+        bitrate: Some(bitrate),
+        row_mt: true,
+        ..Default::default()
+    };
+
+    let output = DashOutput::new("manifest.mpd")
+        .adaptation_set(
+            AdaptationSet::new()
+                .representation(Representation::new(&hd).encoder(vp9(31, 4_500_000)))
+                .representation(Representation::new(&sd).encoder(vp9(33, 1_000_000))),
+        )
+        .adaptation_set(AdaptationSet::new().representation(
+            Representation::new(&input.audio()).encoder(LibOpus {
+                bitrate: Some(128_000),
+                ..Default::default()
+            }),
+        ))
+        .seg_duration(Duration::from_secs(4))
+        .use_template(true)
+        .use_timeline(false)
+        .init_seg_name("init-$RepresentationID$.$ext$")
+        .media_seg_name("chunk-$RepresentationID$-$Number%05d$.$ext$")
+        .stream()
+        .unwrap();
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap(),
+        [
+            "ffmpeg",
+            "-hide_banner",
+            "-i",
+            "input.mkv",
+            "-filter_complex",
+            "[0:v]split[s0][s1];[s0]scale=w=-2:h=1080[s2];[s1]scale=w=-2:h=480[s3]",
+            "-map",
+            "[s2]",
+            "-map",
+            "[s3]",
+            "-map",
+            "0:a",
+            "-f",
+            "dash",
+            "-seg_duration",
+            "4",
+            "-use_template",
+            "1",
+            "-use_timeline",
+            "0",
+            "-init_seg_name",
+            "init-$RepresentationID$.$ext$",
+            "-media_seg_name",
+            "chunk-$RepresentationID$-$Number%05d$.$ext$",
+            "-adaptation_sets",
+            "id=0,streams=0,1 id=1,streams=2",
+            "-c:0",
+            "libvpx-vp9",
+            "-crf:0",
+            "31",
+            "-b:0",
+            "4500k",
+            "-row-mt:0",
+            "1",
+            "-c:1",
+            "libvpx-vp9",
+            "-crf:1",
+            "33",
+            "-b:1",
+            "1000k",
+            "-row-mt:1",
+            "1",
+            "-c:2",
+            "libopus",
+            "-b:2",
+            "128k",
+            "manifest.mpd",
+            "-y",
+        ]
+    );
+}
+
+#[test]
+fn invalid_outputs() {
+    assert_eq!(
+        DashOutput::new("manifest.mpd").node().err(),
+        Some("DASH output needs at least one adaptation set".to_owned())
+    );
+    assert_eq!(
+        DashOutput::new("manifest.mpd")
+            .adaptation_set(AdaptationSet::new())
+            .node()
+            .err(),
+        Some("DASH adaptation set #0 has no representations".to_owned())
+    );
+
+    // Encoder errors are reported when the output is built.
+    let input = ffmpeg::input("input.mkv", kwargs! {});
+    let lossless = LibVpxVp9 {
+        crf: Some(0),
+        lossless: true,
+        ..Default::default()
+    };
+    let output = DashOutput::new("manifest.mpd").adaptation_set(
+        AdaptationSet::new().representation(Representation::new(&input).encoder(lossless)),
+    );
+    assert!(output.stream().is_err());
+}
+
+#[test]
+fn shared_filtered_streams() {
+    let input = ffmpeg::input("input.mkv", kwargs! {});
+    let hd = input
+        .video()
+        .filter("scale", kwargs! {"w" => -2, "h" => 1080});
+    let audio = input.audio().filter("aresample", kwargs! {"osr" => 48000});
+    let vp9 = |bitrate| LibVpxVp9 {
+        bitrate: Some(bitrate),
+        ..Default::default()
+    };
+    let opus = |bitrate| LibOpus {
+        bitrate: Some(bitrate),
+        ..Default::default()
+    };
+    let output = DashOutput::new("manifest.mpd")
+        .adaptation_set(
+            AdaptationSet::new()
+                .representation(Representation::new(&hd).encoder(vp9(4_500_000)))
+                .representation(Representation::new(&hd).encoder(vp9(2_500_000))),
+        )
+        .adaptation_set(
+            AdaptationSet::new()
+                .representation(Representation::new(&audio).encoder(opus(128_000)))
+                .representation(Representation::new(&audio).encoder(opus(64_000))),
+        )
+        .stream()
+        .unwrap();
+    let args = output.compile("ffmpeg", true).unwrap();
+    assert_eq!(
+        args[3..14],
+        [
+            "input.mkv",
+            "-filter_complex",
+            "[0:v]scale=w=-2:h=1080[s0];[s0]split=outputs=2[s1][s2];\
+             [0:a]aresample=osr=48000[s3];[s3]asplit=outputs=2[s4][s5]",
+            "-map",
+            "[s1]",
+            "-map",
+            "[s2]",
+            "-map",
+            "[s4]",
+            "-map",
+            "[s5]",
+        ]
+    );
+
+    // Without an encoder there is no telling a split from an asplit.
+    let output = DashOutput::new("manifest.mpd").adaptation_set(
+        AdaptationSet::new()
+            .representation(Representation::new(&hd))
+            .representation(Representation::new(&hd)),
+    );
+    assert_eq!(
+        output.node().err(),
+        Some(
+            "DASH representation #0 shares a filtered stream; \
+             give it an audio or video encoder to split it"
+                .to_owned()
+        )
+    );
+}
@@ -0,0 +1,208 @@
+use ffmpeg_rs::encoder::{
+    Aac, Copy, Deadline, Encoder, Flac, LibAom, LibOpus, LibVpxVp9, OpusVbr, Preset, X264, X265,
+};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+fn args(stream_specifier: &str, encoder: impl Into<Encoder>) -> String {
+    encoder
+        .into()
+        .get_args(stream_specifier)
+        .unwrap()
+        .iter()
+        .map(|(key, value)| format!("-{} {}", key, value))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[test]
+fn video_encoders() {
+    let x264 = X264 {
+        crf: Some(20.0),
+        maxrate: Some(4_000_000),
+        bufsize: Some(8_000_000),
+        preset: Some(Preset::Slow),
+        tune: Some("film".to_owned()),
+        profile: Some("high".to_owned()),
+        level: Some("4.1".to_owned()),
+        x264_params: kwargs! {"keyint" => 48, "scenecut" => 0},
+        ..Default::default()
+    };
+    assert_eq!(
+        args("v", x264),
+        "-c:v libx264 -crf:v 20 -maxrate:v 4000k -bufsize:v 8000k -preset:v slow -tune:v film \
+         -profile:v high -level:v 4.1 -x264-params:v keyint=48:scenecut=0"
+    );
+    let x264 = X264 {
+        x264_params: kwargs! {"zones" => "0,100,q=20", "stats" => "C:\\pass.log"},
+        ..Default::default()
+    };
+    assert_eq!(
+        args("v", x264),
+        "-c:v libx264 -x264-params:v zones=0,100,q\\=20:stats=C\\:\\\\pass.log"
+    );
+    let x264 = X264 {
+        x264_params: kwargs! {"stats" => "/tmp/it's.log"},
+        ..Default::default()
+    };
+    assert_eq!(
+        args("v", x264),
+        "-c:v libx264 -x264-params:v stats=/tmp/it\\'s.log"
+    );
+    let x265 = X265 {
+        bitrate: Some(2_500_000),
+        preset: Some(Preset::Medium),
+        x265_params: kwargs! {"pass" => 1},
+        ..Default::default()
+    };
+    assert_eq!(
+        args("v:0", x265),
+        "-c:v:0 libx265 -b:v:0 2500k -preset:v:0 medium -x265-params:v:0 pass=1"
+    );
+
+    // Constant quality needs a zero target bitrate, constrained quality keeps it.
+    let vp9 = LibVpxVp9 {
+        crf: Some(31),
+        deadline: Some(Deadline::Good),
+        cpu_used: Some(2),
+        row_mt: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        args("v", vp9),
+        "-c:v libvpx-vp9 -crf:v 31 -b:v 0 -deadline:v good -cpu-used:v 2 -row-mt:v 1"
+    );
+    let av1 = LibAom {
+        crf: Some(30),
+        bitrate: Some(2_000_000),
+        ..Default::default()
+    };
+    assert_eq!(args("v", av1), "-c:v libaom-av1 -crf:v 30 -b:v 2000k");
+    assert_eq!(args("", Copy), "-c copy");
+}
+
+#[test]
+fn audio_encoders() {
+    let aac = Aac {
+        bitrate: Some(128_000),
+        ..Default::default()
+    };
+    assert_eq!(args("a", aac), "-c:a aac -b:a 128k");
+    let opus = LibOpus {
+        bitrate: Some(96_500),
+        vbr: Some(OpusVbr::Constrained),
+        compression_level: Some(10),
+        application: Some("audio".to_owned()),
+    };
+    assert_eq!(
+        args("a:1", opus),
+        "-c:a:1 libopus -b:a:1 96500 -vbr:a:1 constrained -compression_level:a:1 10 \
+         -application:a:1 audio"
+    );
+    let flac = Flac {
+        compression_level: Some(8),
+    };
+    assert_eq!(args("a", flac), "-c:a flac -compression_level:a 8");
+}
+
+#[test]
+fn invalid_configs() {
+    let error = |encoder: Encoder| encoder.get_args("v").unwrap_err();
+    assert_eq!(
+        error(
+            X264 {
+                crf: Some(23.0),
+                bitrate: Some(1_000_000),
+                ..Default::default()
+            }
+            .into()
+        ),
+        "libx264: rate control options crf and bitrate are mutually exclusive"
+    );
+    assert_eq!(
+        error(
+            LibVpxVp9 {
+                crf: Some(31),
+                lossless: true,
+                ..Default::default()
+            }
+            .into()
+        ),
+        "libvpx-vp9: rate control options lossless and crf are mutually exclusive"
+    );
+    assert_eq!(
+        error(
+            Aac {
+                bitrate: Some(128_000),
+                quality: Some(1.0),
+                ..Default::default()
+            }
+            .into()
+        ),
+        "aac: rate control options bitrate and quality are mutually exclusive"
+    );
+    assert_eq!(
+        error(
+            X265 {
+                crf: Some(60.0),
+                ..Default::default()
+            }
+            .into()
+        ),
+        "libx265: value 60 for option `crf` is out of range [0, 51]"
+    );
+}
+
+#[test]
+fn output_encoders() {
+    let input = ffmpeg::input("input.mov", kwargs! {});
+    let output = ffmpeg::output_node(&input, "output.mp4", kwargs! {"movflags" => "+faststart"})
+        .encoder(
+            "v",
+            X264 {
+                crf: Some(18.0),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .encoder("a", Copy)
+        .unwrap()
+        // Replaces the options of the first video encoder.
+        .encoder(
+            "v",
+            X264 {
+                crf: Some(23.0),
+                preset: Some(Preset::Veryfast),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        output
+            .stream("", None)
+            .compile("ffmpeg", true)
+            .unwrap()
+            .join(" "),
+        "ffmpeg -hide_banner -i input.mov -movflags +faststart -c:v libx264 -crf:v 23 -c:a copy \
+         -preset:v veryfast output.mp4 -y"
+    );
+
+    // Nothing of the previous config is left, even keys the new one does not set.
+    let output = output
+        .encoder(
+            "v",
+            X264 {
+                bitrate: Some(3_000_000),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        output
+            .stream("", None)
+            .compile("ffmpeg", true)
+            .unwrap()
+            .join(" "),
+        "ffmpeg -hide_banner -i input.mov -movflags +faststart -c:v libx264 -c:a copy \
+         -b:v 3000k output.mp4 -y"
+    );
+}
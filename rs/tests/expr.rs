@@ -0,0 +1,95 @@
+use ffmpeg_rs::expr::{
+    between, clip, if_then, if_then_else, ld, st, Expr, H, MAIN_H, MAIN_W, N, OVERLAY_H, OVERLAY_W,
+    PTS, STARTPTS, T, TB, W,
+};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn operators_and_precedence() {
+    assert_eq!((MAIN_W - OVERLAY_W - 10).to_string(), "main_w-overlay_w-10");
+    assert_eq!(
+        (MAIN_W - (OVERLAY_W - 10)).to_string(),
+        "main_w-(overlay_w-10)"
+    );
+    assert_eq!(
+        ((MAIN_H - OVERLAY_H) / 2).to_string(),
+        "(main_h-overlay_h)/2"
+    );
+    assert_eq!((W / (H * 2)).to_string(), "w/(h*2)");
+    assert_eq!((W * (H * 2)).to_string(), "w*h*2");
+    assert_eq!((PTS - STARTPTS).to_string(), "PTS-STARTPTS");
+    assert_eq!(
+        ((PTS - STARTPTS) * 0.5 / TB).to_string(),
+        "(PTS-STARTPTS)*0.5/TB"
+    );
+    assert_eq!((T * 2).pow(2).to_string(), "(t*2)^2");
+    assert_eq!((2 * T + 1).to_string(), "2*t+1");
+    assert_eq!(T.pow(N.pow(2)).to_string(), "t^(n^2)");
+    assert_eq!((-(T + 1)).to_string(), "-(t+1)");
+    assert_eq!((T * -1).to_string(), "t*(-1)");
+    assert_eq!((-T).pow(2).to_string(), "(-t)^2");
+    assert_eq!((-T.pow(2)).to_string(), "-(t^2)");
+    assert_eq!(T.pow(-N).to_string(), "t^(-n)");
+    assert_eq!((-T * N).to_string(), "(-t)*n");
+    assert_eq!((T - (-T)).to_string(), "t-(-t)");
+    assert_eq!((T + (-W)).to_string(), "t+(-w)");
+    assert_eq!((T * (-W)).to_string(), "t*(-w)");
+}
+
+#[test]
+fn functions() {
+    assert_eq!(
+        if_then_else(T.gte(2), 10, 20).to_string(),
+        "if(gte(t,2),10,20)"
+    );
+    assert_eq!(
+        if_then(between(T, 1, 5), W).to_string(),
+        "if(between(t,1,5),w)"
+    );
+    assert_eq!(clip(T * 100, 0, W - 1).to_string(), "clip(t*100,0,w-1)");
+    assert_eq!((st(0, T * 2) + ld(0)).to_string(), "st(0,t*2)+ld(0)");
+    assert_eq!(N.equals(0).to_string(), "eq(n,0)");
+    assert_eq!(
+        Expr::call("random", vec![Expr::from(0)]).to_string(),
+        "random(0)"
+    );
+    assert_eq!(Expr::var("scene").gt(0.4).to_string(), "gt(scene,0.4)");
+}
+
+#[test]
+fn expressions_are_escaped_in_filters() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let logo = ffmpeg::input("logo.png", kwargs! {});
+    let x = if_then_else(T.lt(5), MAIN_W - OVERLAY_W, 0);
+    let overlaid = ffmpeg::filter([input, logo], "overlay", kwargs! {"x" => x, "y" => 10});
+    let output = overlaid.output("output.mp4", kwargs! {});
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap(),
+        [
+            "ffmpeg",
+            "-hide_banner",
+            "-i",
+            "input.mp4",
+            "-i",
+            "logo.png",
+            "-filter_complex",
+            "[0][1]overlay=x=if(lt(t\\,5)\\,main_w-overlay_w\\,0):y=10[s0]",
+            "-map",
+            "[s0]",
+            "output.mp4",
+            "-y",
+        ]
+    );
+}
+
+#[test]
+fn special_characters_are_escaped_in_filters() {
+    // The example from the "Notes on filtergraph escaping" section of the ffmpeg docs.
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let text = "this is a 'string': may contain one, or more, special characters";
+    let filter = input.filter("drawtext", kwargs! {"text" => text});
+    assert_eq!(
+        ffmpeg::get_args(filter.output("output.mp4", kwargs! {})).unwrap()[3],
+        "[0]drawtext=text=this is a \\\\\\'string\\\\\\'\\\\: may contain one\\, or more\\, special characters[s0]"
+    );
+}
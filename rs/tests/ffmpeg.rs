@@ -0,0 +1,203 @@
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn filter_1() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let filter = input.filter("hflip", kwargs! {"x" => "10", "y" => "20"});
+    let output = filter.output("output.mp4", kwargs! {});
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i input.mp4 -filter_complex [0]hflip=x=10:y=20[s0] -map [s0] output.mp4 -y"
+    );
+}
+
+#[test]
+fn filter_2() {
+    let input = ffmpeg::input("hi.mp4", kwargs! {});
+    let image = ffmpeg::input("hi.png", kwargs! {});
+    let filter = ffmpeg::filter([input, image], "overlay", kwargs! {});
+    let output = filter.output("output.mp4", kwargs! {});
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i hi.mp4 -i hi.png -filter_complex [0][1]overlay[s0] -map [s0] output.mp4 -y"
+    );
+}
+
+#[test]
+fn filter_3() {
+    let input = ffmpeg::input("hi.mp4", kwargs! {"ss" => "0", "t" => "10"});
+    let image = ffmpeg::input("hi.png", kwargs! {});
+    let filter = ffmpeg::filter([input, image], "overlay", kwargs! {});
+    let output = filter.output("output.mp4", kwargs! {});
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -ss 0 -t 10 -i hi.mp4 -i hi.png -filter_complex [0][1]overlay[s0] -map [s0] output.mp4 -y"
+    );
+}
+
+#[test]
+fn concat_1() {
+    let concat = ffmpeg::input(
+        "list.txt",
+        kwargs! {"f" => "concat", "safe" => "0", "c" => "copy"},
+    );
+    let output = concat.output("output.mp4", kwargs! {});
+    assert_eq!(
+        output.compile("./ffmpeg", false).unwrap().join(" "),
+        "./ffmpeg -hide_banner -f concat -safe 0 -c copy -i list.txt output.mp4"
+    );
+}
+
+#[test]
+fn concat_2() {
+    let concat = ffmpeg::input("list.txt", kwargs! {"f" => "concat", "safe" => "0"});
+    let output = concat.output(
+        "output.mp4",
+        kwargs! {"c:v" => "copy", "af" => "aresample=async=1000", "map" => "0:V", "map" => "0:a?"},
+    );
+    assert_eq!(
+        output.compile("./ffmpeg", true).unwrap().join(" "),
+        "./ffmpeg -hide_banner -f concat -safe 0 -i list.txt -c:v copy -af aresample=async=1000 -map 0:V -map 0:a? output.mp4 -y"
+    );
+}
+
+#[test]
+fn video_filter_1() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let filter = ffmpeg::filter(input.video(), "hflip", kwargs! {"x" => "10", "y" => "20"});
+    let output = filter.output("output.mp4", kwargs! {});
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i input.mp4 -filter_complex [0:v]hflip=x=10:y=20[s0] -map [s0] output.mp4 -y"
+    );
+}
+
+#[test]
+fn audio_filter_1() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let filter = ffmpeg::filter(
+        input.audio(),
+        "afade",
+        kwargs! {"t" => "in", "st" => "2", "d" => "3"},
+    );
+    let output = filter.output("output.mp4", kwargs! {});
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i input.mp4 -filter_complex [0:a]afade=t=in:st=2:d=3[s0] -map [s0] output.mp4 -y"
+    );
+}
+
+#[test]
+fn combined_seek_1() {
+    let input = ffmpeg::input("input.mp4", kwargs! {"ss" => "01:29"});
+    let filter = input.filter("hflip", kwargs! {});
+    let output = filter.output(
+        "output.mp4",
+        kwargs! {"ss" => "00:01", "t" => "00:10", "map" => "0:a"},
+    );
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -ss 01:29 -i input.mp4 -filter_complex [0]hflip[s0] -map [s0] -ss 00:01 -t 00:10 -map 0:a output.mp4 -y"
+    );
+}
+
+#[test]
+fn split_requires_distinct_labels() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let flipped = input.filter("hflip", kwargs! {});
+    let left = flipped.output("left.mp4", kwargs! {});
+    let right = flipped.output("right.mp4", kwargs! {});
+    assert!(ffmpeg::compile([left, right], "ffmpeg", true).is_err());
+
+    let split = ffmpeg::filter_multi_output(&flipped, "split", kwargs! {});
+    let left = split.stream("0", None).output("left.mp4", kwargs! {});
+    let right = split.stream("1", None).output("right.mp4", kwargs! {});
+    assert_eq!(
+        ffmpeg::compile([left, right], "ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i input.mp4 -filter_complex [0]hflip[s0];[s0]split[s1][s2] -map [s1] left.mp4 -map [s2] right.mp4 -y"
+    );
+}
+
+#[test]
+fn concat_segments() {
+    let first = ffmpeg::input("first.mp4", kwargs! {});
+    let second = ffmpeg::input("second.mp4", kwargs! {});
+    let (video, audio) = ffmpeg::concat(&[
+        (first.video(), first.audio()),
+        (second.video(), second.audio()),
+    ])
+    .unwrap();
+    let output = ffmpeg::output([video, audio], "output.mp4", kwargs! {});
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i first.mp4 -i second.mp4 -filter_complex [0:v][0:a][1:v][1:a]concat=n=2:v=1:a=1[s0][s1] -map [s0] -map [s1] output.mp4 -y"
+    );
+    assert!(ffmpeg::concat(&[]).is_err());
+}
+
+#[test]
+fn concat_audio_only() {
+    let first = ffmpeg::input("first.mp4", kwargs! {});
+    let second = ffmpeg::input("second.mp4", kwargs! {});
+    let (_, audio) = ffmpeg::concat(&[
+        (first.video(), first.audio()),
+        (second.video(), second.audio()),
+    ])
+    .unwrap();
+    assert_eq!(
+        audio.output("output.m4a", kwargs! {}).compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i first.mp4 -i second.mp4 -filter_complex [0:v][0:a][1:v][1:a]concat=n=2:v=1:a=1[s0][s1];[s0]nullsink -map [s1] output.m4a -y"
+    );
+}
+
+#[test]
+fn concat_audio_before_video() {
+    let first = ffmpeg::input("first.mp4", kwargs! {});
+    let second = ffmpeg::input("second.mp4", kwargs! {});
+    let (video, audio) = ffmpeg::concat(&[
+        (first.video(), first.audio()),
+        (second.video(), second.audio()),
+    ])
+    .unwrap();
+    let output = ffmpeg::output([audio, video], "output.mp4", kwargs! {});
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i first.mp4 -i second.mp4 -filter_complex [0:v][0:a][1:v][1:a]concat=n=2:v=1:a=1[s0][s1] -map [s1] -map [s0] output.mp4 -y"
+    );
+}
+
+#[test]
+fn concat_normalized_segments() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let (video, audio) =
+        ffmpeg::concat_normalized(&[(input.video(), input.audio())], 1920, 1080, 48000).unwrap();
+    let output = ffmpeg::output([video, audio], "output.mp4", kwargs! {});
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i input.mp4 -filter_complex \
+         [0:v]scale=w=1920:h=1080:force_original_aspect_ratio=decrease[s0];\
+         [s0]pad=w=1920:h=1080:x=(ow-iw)/2:y=(oh-ih)/2[s1];\
+         [s1]setsar=r=1[s2];\
+         [0:a]aresample=osr=48000[s3];\
+         [s2][s3]concat=n=1:v=1:a=1[s4][s5] -map [s4] -map [s5] output.mp4 -y"
+    );
+}
+
+#[test]
+fn shell_command() {
+    let args: Vec<String> = [
+        "ffmpeg",
+        "-i",
+        "it's here.mp4",
+        "-vf",
+        "scale=w=640:h=-2",
+        "",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    assert_eq!(
+        ffmpeg::shell_command(&args),
+        r"ffmpeg -i 'it'\''s here.mp4' -vf scale=w=640:h=-2 ''"
+    );
+}
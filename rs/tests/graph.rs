@@ -0,0 +1,171 @@
+mod common;
+
+use ffmpeg_rs::graph::{Graph, GraphEdge, GraphStream, SCHEMA_VERSION};
+use ffmpeg_rs::node::OutputStream;
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+// Two outputs sharing a split, with selectors, a multi-input filter and metadata.
+fn pipeline() -> Vec<OutputStream> {
+    let movie = ffmpeg::input("movie.mp4", kwargs! {});
+    let logo = ffmpeg::input("logo.png", kwargs! {"loop" => 1});
+    let split = ffmpeg::filter_multi_output(
+        ffmpeg::filter(
+            [movie.video(), logo.clone()],
+            "overlay",
+            kwargs! {"x" => "W-w-10", "y" => 10},
+        ),
+        "split",
+        kwargs! {},
+    );
+    vec![
+        ffmpeg::output_node(
+            [split.stream("0", None), movie.audio()],
+            "full.mp4",
+            kwargs! {"c:v" => "libx264"},
+        )
+        .metadata("title", "Full")
+        .metadata("comment", "a=b")
+        .stream("", None),
+        split
+            .stream("1", None)
+            .filter("scale", kwargs! {"w" => 320, "h" => -2})
+            .output("preview.mp4", kwargs! {}),
+    ]
+}
+
+#[test]
+fn round_trip() {
+    let graph = Graph::new(pipeline()).unwrap();
+    assert_eq!(graph.version, SCHEMA_VERSION);
+    assert_eq!(graph.nodes.len(), 7);
+    assert_eq!(
+        graph.edges[0],
+        GraphEdge {
+            from: 0,
+            from_label: String::new(),
+            selector: Some('v'),
+            to: 2,
+            to_label: "0".to_owned(),
+        }
+    );
+
+    let expected = ffmpeg::compile(pipeline(), "ffmpeg", true).unwrap();
+    assert_eq!(graph.compile("ffmpeg", true).unwrap(), expected);
+    assert_eq!(Graph::new(graph.stream_spec().unwrap()).unwrap(), graph);
+
+    let quiet = graph.global("loglevel", "error").global("nostdin", "");
+    assert_eq!(
+        quiet.compile("ffmpeg", false).unwrap()[..6],
+        [
+            "ffmpeg",
+            "-hide_banner",
+            "-loglevel",
+            "error",
+            "-nostdin",
+            "-i"
+        ]
+    );
+}
+
+#[test]
+fn invalid_graphs() {
+    let graph = Graph::new(pipeline()).unwrap();
+
+    let mut future = graph.clone();
+    future.version = SCHEMA_VERSION + 1;
+    assert_eq!(
+        future.stream_spec().err().unwrap(),
+        format!(
+            "Unsupported graph schema version {}; expected {}",
+            SCHEMA_VERSION + 1,
+            SCHEMA_VERSION
+        )
+    );
+
+    let mut cyclic = graph.clone();
+    cyclic.edges[0].from = 5;
+    assert_eq!(
+        cyclic.stream_spec().err().unwrap(),
+        "Edge from node #5 to node #2 does not point back to an earlier node"
+    );
+
+    let mut duplicate = graph.clone();
+    duplicate.edges[1].to_label = "0".to_owned();
+    assert_eq!(
+        duplicate.stream_spec().err().unwrap(),
+        "Node #2 has more than one incoming edge labelled \"0\""
+    );
+
+    let mut dangling = graph.clone();
+    dangling.streams.push(GraphStream {
+        node: 9,
+        label: String::new(),
+        selector: None,
+    });
+    assert_eq!(
+        dangling.stream_spec().err().unwrap(),
+        "Stream of missing node #9"
+    );
+
+    let piped = ffmpeg_rs::pipe::from_reader(std::io::empty(), 0, kwargs! {}).unwrap();
+    assert_eq!(
+        Graph::new(piped.stream("", None).output("out.mp4", kwargs! {}))
+            .unwrap_err()
+            .to_string(),
+        "Node #0 `input` uses a pipe or generated file, which cannot be stored"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_schema() {
+    let graph = Graph::new(pipeline()).unwrap().global("loglevel", "error");
+    let stored = common::fixture("graph/pipeline.json");
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&stored).unwrap(),
+        serde_json::to_value(&graph).unwrap()
+    );
+    assert_eq!(serde_json::from_str::<Graph>(&stored).unwrap(), graph);
+}
+
+#[test]
+fn drawings() {
+    let movie = ffmpeg::input("movie.mp4", kwargs! {});
+    let split = ffmpeg::filter_multi_output(movie.video(), "split", kwargs! {});
+    let graph = Graph::new(ffmpeg::output(
+        [
+            split.stream("0", None),
+            split.stream("1", None),
+            movie.audio(),
+        ],
+        "out.mkv",
+        kwargs! {"metadata" => "title=\"Cut\" <v2>"},
+    ))
+    .unwrap();
+    assert_eq!(
+        graph.to_dot(),
+        r#"digraph {
+    rankdir=LR;
+    n0 [label="input\nfilename=movie.mp4", shape=box];
+    n1 [label="split", shape=ellipse];
+    n2 [label="output\nmetadata=title=\"Cut\" <v2>\nfilename=out.mkv", shape=box];
+    n0 -> n1 [label="0:v"];
+    n1 -> n2 [label="0 → 0"];
+    n1 -> n2 [label="1 → 1"];
+    n0 -> n2 [label="0:a → 2"];
+}
+"#
+    );
+    assert_eq!(
+        graph.to_mermaid(),
+        r#"flowchart LR
+    n0(["input<br>filename=movie.mp4"])
+    n1["split"]
+    n2(["output<br>metadata=title=#quot;Cut#quot; #lt;v2#gt;<br>filename=out.mkv"])
+    n0 -->|"0:v"| n1
+    n1 -->|"0 → 0"| n2
+    n1 -->|"1 → 1"| n2
+    n0 -->|"0:a → 2"| n2
+"#
+    );
+}
@@ -0,0 +1,147 @@
+use ffmpeg_rs::encoder::{Aac, X264};
+use ffmpeg_rs::hls::{HlsOutput, HlsPlaylistType, Rendition};
+use ffmpeg_rs::time::Duration;
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn variant_streams() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let split = ffmpeg::filter_multi_output(input.video(), "split", kwargs! {});
+    let hd = split
+        .stream("0", None)
+        .filter("scale", kwargs! {"w" => -2, "h" => 720});
+    let sd = split
+        .stream("1", None)
+        .filter("scale", kwargs! {"w" => -2, "h" => 360});
+    let audio = input.audio();
+    let x264 = |bitrate| X264 {
+        bitrate: Some(bitrate),
+        ..Default::default()
+    };
+
+    let output = HlsOutput::new("out/%v/index.m3u8")
+        .rendition(
+            Rendition::new()
+                .video(&hd)
+                .audio(&audio)
+                .name("720p")
+                .video_encoder(x264(3_000_000))
+                .audio_encoder(Aac {
+                    bitrate: Some(128_000),
+                    ..Default::default()
+                }),
+        )
+        .rendition(
+            Rendition::new()
+                .video(&sd)
+                .audio(&audio)
+                .name("360p")
+                .video_encoder(x264(800_000)),
+        )
+        .hls_time(Duration::from_secs(6))
+        .playlist_type(HlsPlaylistType::Vod)
+        .segment_filename("out/%v/%03d.ts")
+        .master_pl_name("master.m3u8")
+        .arg("hls_flags", "independent_segments")
+        .stream()
+        .unwrap();
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap(),
+        [
+            "ffmpeg",
+            "-hide_banner",
+            "-i",
+            "input.mp4",
+            "-filter_complex",
+            "[0:v]split[s0][s1];[s0]scale=w=-2:h=720[s2];[s1]scale=w=-2:h=360[s3]",
+            "-map",
+            "[s2]",
+            "-map",
+            "0:a",
+            "-map",
+            "[s3]",
+            "-map",
+            "0:a",
+            "-f",
+            "hls",
+            "-hls_time",
+            "6",
+            "-hls_playlist_type",
+            "vod",
+            "-hls_segment_filename",
+            "out/%v/%03d.ts",
+            "-master_pl_name",
+            "master.m3u8",
+            "-var_stream_map",
+            "v:0,a:0,name:720p v:1,a:1,name:360p",
+            "-hls_flags",
+            "independent_segments",
+            "-c:v:0",
+            "libx264",
+            "-b:v:0",
+            "3000k",
+            "-c:a:0",
+            "aac",
+            "-b:a:0",
+            "128k",
+            "-c:v:1",
+            "libx264",
+            "-b:v:1",
+            "800k",
+            "out/%v/index.m3u8",
+            "-y",
+        ]
+    );
+}
+
+#[test]
+fn shared_filtered_audio() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let audio = input.audio().filter("aresample", kwargs! {"osr" => 48000});
+    let output = HlsOutput::new("out_%v.m3u8")
+        .rendition(Rendition::new().video(&input.video()).audio(&audio))
+        .rendition(
+            Rendition::new()
+                .video(
+                    &input
+                        .video()
+                        .filter("scale", kwargs! {"w" => -2, "h" => 360}),
+                )
+                .audio(&audio),
+        )
+        .stream()
+        .unwrap();
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i input.mp4 -filter_complex \
+         [0:a]aresample=osr=48000[s0];[s0]asplit=outputs=2[s1][s2];[0:v]scale=w=-2:h=360[s3] \
+         -map 0:v -map [s1] -map [s3] -map [s2] -f hls -var_stream_map v:0,a:0 v:1,a:1 \
+         out_%v.m3u8 -y"
+    );
+}
+
+#[test]
+fn audio_only_rendition() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let output = HlsOutput::new("out_%v.m3u8")
+        .rendition(Rendition::new().video(&input.video()))
+        .rendition(Rendition::new().audio(&input.audio()).name("audio"))
+        .node()
+        .unwrap();
+    let var_stream_map = output
+        .node()
+        .kwargs()
+        .iter()
+        .find(|(key, _)| key == "var_stream_map")
+        .map(|(_, value)| value.as_str());
+    assert_eq!(var_stream_map, Some("v:0 a:0,name:audio"));
+
+    assert!(HlsOutput::new("out.m3u8").node().is_err());
+    assert_eq!(
+        HlsOutput::new("out.m3u8")
+            .rendition(Rendition::new())
+            .node()
+            .err(),
+        Some("HLS rendition #0 has no streams".to_owned())
+    );
+}
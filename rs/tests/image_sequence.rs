@@ -0,0 +1,97 @@
+use ffmpeg_rs::image_sequence::{ImagePattern, ImageSequenceInput, ImageSequenceOutput};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn patterns() {
+    assert_eq!(
+        ImagePattern::numbered("renders/100%", "frame_", 5, "png").as_str(),
+        "renders/100%%/frame_%05d.png"
+    );
+    assert_eq!(
+        ImagePattern::numbered("", "img", 1, "jpg").as_str(),
+        "img%d.jpg"
+    );
+    assert_eq!(
+        ImagePattern::glob("shots [final]/", "*.jpg").as_str(),
+        "shots \\[final\\]/*.jpg"
+    );
+
+    assert!(ImagePattern::parse("out/frame_%04d.png").is_ok());
+    assert!(ImagePattern::parse("100%%/frame_%d.png").is_ok());
+    assert_eq!(
+        ImagePattern::parse("100%/frame_%04d.png").unwrap_err(),
+        "Unescaped `%` in image pattern \"100%/frame_%04d.png\"; use `%%` for a literal `%`"
+    );
+    assert!(ImagePattern::parse("frame.png").is_err());
+    assert!(ImagePattern::parse("%d_%d.png").is_err());
+    assert!(ImagePattern::parse("frame_%d%").is_err());
+}
+
+#[test]
+fn sequence_input() {
+    let frames = ImageSequenceInput::new(ImagePattern::numbered("frames", "frame_", 5, "png"))
+        .framerate("30000/1001")
+        .start_number(1)
+        .stream();
+    let output = frames.output("video.mp4", kwargs! {"pix_fmt" => "yuv420p"});
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -framerate 30000/1001 -start_number 1 -i frames/frame_%05d.png \
+         -pix_fmt yuv420p video.mp4 -y"
+    );
+
+    let glob = ImageSequenceInput::new(ImagePattern::glob("shots", "*.jpg"))
+        .framerate(1)
+        .stream();
+    assert_eq!(
+        ffmpeg::get_args(glob.output("slideshow.mp4", kwargs! {})).unwrap(),
+        [
+            "-framerate",
+            "1",
+            "-pattern_type",
+            "glob",
+            "-i",
+            "shots/*.jpg",
+            "slideshow.mp4"
+        ]
+    );
+}
+
+#[test]
+fn frame_extraction() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let output = ImageSequenceOutput::new(&input, &ImagePattern::numbered("thumbs", "", 3, "jpg"))
+        .fps(1)
+        .frames(10)
+        .start_number(1)
+        .stream();
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i input.mp4 -filter_complex [0]fps=fps=1[s0] -map [s0] \
+         -frames:v 10 -start_number 1 thumbs/%03d.jpg -y"
+    );
+
+    let poster = ImageSequenceOutput::single(&input.video(), "poster.jpg")
+        .frames(1)
+        .stream();
+    assert_eq!(
+        poster.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i input.mp4 -map 0:v -update 1 -frames:v 1 poster.jpg -y"
+    );
+}
+
+#[test]
+fn pattern_filenames() {
+    let pattern = ImagePattern::numbered("renders/100%", "frame_", 5, "png");
+    assert_eq!(
+        pattern.filename(42).unwrap(),
+        "renders/100%/frame_00042.png"
+    );
+    assert_eq!(
+        ImagePattern::numbered("", "img", 1, "jpg")
+            .filename(7)
+            .unwrap(),
+        "img7.jpg"
+    );
+    assert_eq!(ImagePattern::glob("shots", "*.jpg").filename(1), None);
+}
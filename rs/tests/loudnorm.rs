@@ -0,0 +1,86 @@
+mod common;
+
+use ffmpeg_rs::analysis::{LoudnormMeasurement, LoudnormTarget};
+use ffmpeg_rs::loudnorm::second_pass;
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+fn measurement(input_i: f64) -> LoudnormMeasurement {
+    LoudnormMeasurement {
+        input_i,
+        input_tp: -4.47,
+        input_lra: 18.06,
+        input_thresh: -39.2,
+        output_i: -16.58,
+        output_tp: -1.5,
+        output_lra: 14.78,
+        output_thresh: -27.71,
+        normalization_type: "dynamic".to_owned(),
+        target_offset: 0.58,
+    }
+}
+
+#[test]
+fn second_pass_graph() {
+    let input = ffmpeg::input("episode.wav", kwargs! {});
+    let target = LoudnormTarget {
+        integrated: -16.0,
+        true_peak: -1.5,
+        loudness_range: 11.0,
+    };
+    let output = second_pass(&input, target, &measurement(-27.61))
+        .unwrap()
+        .output("episode.m4a", kwargs! {"ar" => 48000});
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i episode.wav -filter_complex \
+         [0:a]loudnorm=I=-16:TP=-1.5:LRA=11:measured_I=-27.61:measured_TP=-4.47:\
+         measured_LRA=18.06:measured_thresh=-39.2:offset=0.58:linear=true[s0] \
+         -map [s0] -ar 48000 episode.m4a -y"
+    );
+
+    // Already processed audio is normalized as it is.
+    let music = ffmpeg::input("music.wav", kwargs! {});
+    let mixed = ffmpeg::filter([input.audio(), music.audio()], "amix", kwargs! {});
+    assert_eq!(
+        ffmpeg::get_args(
+            second_pass(&mixed, target, &measurement(-27.61))
+                .unwrap()
+                .output("mix.flac", kwargs! {})
+        )
+        .unwrap()[5],
+        "[0:a][1:a]amix[s0];[s0]loudnorm=I=-16:TP=-1.5:LRA=11:measured_I=-27.61:\
+         measured_TP=-4.47:measured_LRA=18.06:measured_thresh=-39.2:offset=0.58:linear=true[s1]"
+    );
+
+    assert_eq!(
+        second_pass(&input, target, &measurement(f64::NEG_INFINITY))
+            .err()
+            .unwrap(),
+        "Input is silent; its loudness cannot be normalized"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn normalize_runs_first_pass() {
+    use ffmpeg_rs::loudnorm::normalize;
+
+    let fake = common::fake_ffmpeg(
+        "loudnorm",
+        concat!(
+            "cat '",
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/analysis/loudnorm.txt' >&2"
+        ),
+    );
+    let input = ffmpeg::input("episode.wav", kwargs! {});
+    let output = normalize(fake.cmd(), &input, LoudnormTarget::default(), |audio| {
+        audio.output("episode.flac", kwargs! {})
+    })
+    .unwrap();
+    assert_eq!(
+        ffmpeg::get_args(output).unwrap()[3],
+        "[0:a]loudnorm=I=-24:TP=-2:LRA=7:measured_I=-27.61:measured_TP=-4.47:\
+         measured_LRA=18.06:measured_thresh=-39.2:offset=0.58:linear=true[s0]"
+    );
+}
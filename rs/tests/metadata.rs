@@ -0,0 +1,152 @@
+use std::fs;
+
+use ffmpeg_rs::metadata::{Chapter, FfMetadata};
+use ffmpeg_rs::time::Timestamp;
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn output_metadata() {
+    let input = ffmpeg::input("episode.wav", kwargs! {});
+    let output = ffmpeg::output_node(&input, "episode.m4a", kwargs! {"c:a" => "aac"})
+        .metadata("title", "Draft")
+        .metadata("artist", "The Show")
+        .metadata("title", "Episode 12")
+        .stream_metadata("a:0", "language", "eng")
+        .map_metadata("-1")
+        .map_chapters(-1)
+        .stream("", None);
+    assert_eq!(
+        ffmpeg::get_args(output).unwrap(),
+        [
+            "-i",
+            "episode.wav",
+            "-c:a",
+            "aac",
+            "-metadata",
+            "title=Episode 12",
+            "-metadata",
+            "artist=The Show",
+            "-metadata:s:a:0",
+            "language=eng",
+            "-map_metadata",
+            "-1",
+            "-map_chapters",
+            "-1",
+            "episode.m4a"
+        ]
+    );
+}
+
+#[test]
+fn render_ffmetadata() {
+    let metadata = FfMetadata::new()
+        .tag("title", "Episode 12; the end")
+        .tag("comment", "a=b\nc#d")
+        .chapter(Chapter::new(
+            Timestamp::ZERO,
+            Timestamp::from_secs(90),
+            "Intro",
+        ))
+        .chapter(
+            Chapter::new(
+                Timestamp::from_secs(90),
+                Timestamp::from_millis(1_800_500),
+                "Interview",
+            )
+            .tag("artist", "Guest"),
+        );
+    assert_eq!(
+        metadata.render().unwrap(),
+        ";FFMETADATA1\n\
+         title=Episode 12\\; the end\n\
+         comment=a\\=b\\\nc\\#d\n\
+         \n\
+         [CHAPTER]\n\
+         TIMEBASE=1/1000000\n\
+         START=0\n\
+         END=90000000\n\
+         title=Intro\n\
+         \n\
+         [CHAPTER]\n\
+         TIMEBASE=1/1000000\n\
+         START=90000000\n\
+         END=1800500000\n\
+         title=Interview\n\
+         artist=Guest\n"
+    );
+
+    let overlapping = FfMetadata::new()
+        .chapter(Chapter::new(
+            Timestamp::ZERO,
+            Timestamp::from_secs(10),
+            "One",
+        ))
+        .chapter(Chapter::new(
+            Timestamp::from_secs(5),
+            Timestamp::from_secs(20),
+            "Two",
+        ));
+    assert_eq!(
+        overlapping.render().unwrap_err(),
+        "Chapter #1 starts before chapter #0 ends"
+    );
+    assert!(overlapping.input().is_err());
+}
+
+#[test]
+fn chapters_input() {
+    let audio = ffmpeg::input("book.wav", kwargs! {});
+    let chapters = FfMetadata::new()
+        .tag("album", "Audiobook")
+        .chapter(Chapter::new(
+            Timestamp::ZERO,
+            Timestamp::from_secs(600),
+            "Chapter 1",
+        ))
+        .input()
+        .unwrap();
+    let path = chapters.node().kwargs().last().unwrap().1.clone();
+    assert!(fs::read_to_string(&path)
+        .unwrap()
+        .starts_with(";FFMETADATA1\nalbum=Audiobook\n"));
+
+    let output = ffmpeg::output_node(&audio, "book.m4b", kwargs! {"c:a" => "aac"})
+        .metadata_input(&chapters)
+        .stream("", None);
+    drop(chapters);
+    let command = output.compile_command("ffmpeg", true).unwrap();
+    assert_eq!(
+        command.args.join(" "),
+        format!(
+            "ffmpeg -hide_banner -i book.wav -f ffmetadata -i {} -map_metadata 1 \
+             -map_chapters 1 -c:a aac book.m4b -y",
+            path
+        )
+    );
+
+    drop(output);
+    assert!(fs::metadata(&path).is_ok());
+    drop(command);
+    assert!(fs::metadata(&path).is_err());
+
+    // Explicit options replace the generated ones instead of being overridden.
+    let chapters = FfMetadata::new()
+        .chapter(Chapter::new(
+            Timestamp::ZERO,
+            Timestamp::from_secs(600),
+            "Chapter 1",
+        ))
+        .input()
+        .unwrap();
+    let args = ffmpeg::get_args(
+        ffmpeg::output_node(&audio, "book.m4b", kwargs! {})
+            .metadata_input(&chapters)
+            .map_metadata("0")
+            .stream("", None),
+    )
+    .unwrap();
+    assert_eq!(
+        args[6..].join(" "),
+        "-map_chapters 1 -map_metadata 0 book.m4b"
+    );
+}
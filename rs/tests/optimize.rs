@@ -0,0 +1,259 @@
+mod common;
+
+use ffmpeg_rs::catalog::{parse_filter_list, FilterCatalog};
+use ffmpeg_rs::graph::Graph;
+use ffmpeg_rs::node::OutputStream;
+use ffmpeg_rs::optimize::{optimize, optimize_with_catalog, FuseScales, Optimizer, RemoveNoOps};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+use common::fixture_catalog;
+
+fn filters(outputs: impl Into<ffmpeg_rs::node::StreamSpec>) -> String {
+    let args = optimize(outputs).unwrap().compile("ffmpeg", false).unwrap();
+    match args.iter().position(|arg| arg == "-filter_complex") {
+        Some(i) => args[i + 1].clone(),
+        None => String::new(),
+    }
+}
+
+#[test]
+fn shared_streams_are_split() {
+    let movie = ffmpeg::input("movie.mp4", kwargs! {});
+    let outputs: Vec<OutputStream> = ["left.mp4", "right.mp4"]
+        .iter()
+        .map(|file| {
+            // Built twice, but identical: both collapse into one hflip.
+            ffmpeg::output(
+                [
+                    movie.video().filter("hflip", kwargs! {}),
+                    movie.audio().filter("volume", kwargs! {"volume" => 2}),
+                ],
+                file,
+                kwargs! {},
+            )
+        })
+        .collect();
+    assert!(ffmpeg::compile(outputs.clone(), "ffmpeg", false).is_err());
+    assert!(optimize(outputs.clone())
+        .unwrap()
+        .compile("ffmpeg", false)
+        .is_err());
+    assert_eq!(
+        optimize_with_catalog(outputs, &fixture_catalog())
+            .unwrap()
+            .compile("ffmpeg", false)
+            .unwrap(),
+        [
+            "ffmpeg",
+            "-hide_banner",
+            "-i",
+            "movie.mp4",
+            "-filter_complex",
+            "[0:v]hflip[s0];[s0]split[s1][s2];[0:a]volume=volume=2[s3];[s3]asplit[s4][s5]",
+            "-map",
+            "[s1]",
+            "-map",
+            "[s4]",
+            "left.mp4",
+            "-map",
+            "[s2]",
+            "-map",
+            "[s5]",
+            "right.mp4"
+        ]
+    );
+}
+
+#[test]
+fn shared_streams_take_output_types_from_catalog() {
+    let audio = ffmpeg::input("song.mp3", kwargs! {}).audio();
+    let outputs: Vec<OutputStream> = [("hflip", "left.mp4"), ("vflip", "right.mp4")]
+        .iter()
+        .map(|(flip, file)| {
+            audio
+                .filter("showwaves", kwargs! {})
+                .filter(flip, kwargs! {})
+                .output(file, kwargs! {})
+        })
+        .collect();
+    let split_filter = |catalog: &FilterCatalog| {
+        let args = optimize_with_catalog(outputs.clone(), catalog)
+            .unwrap()
+            .compile("ffmpeg", false);
+        args.ok().map(|args| args[5].clone())
+    };
+
+    // Unknown output types leave the stream shared.
+    assert_eq!(split_filter(&fixture_catalog()), None);
+
+    let mut catalog = fixture_catalog();
+    let showwaves = " ... showwaves         A->V       Convert input audio to a video output.";
+    catalog.insert(parse_filter_list(showwaves).unwrap().remove(0));
+    assert_eq!(
+        split_filter(&catalog).unwrap(),
+        "[0:a]showwaves[s0];[s0]split[s1][s2];[s1]hflip[s3];[s2]vflip[s4]"
+    );
+}
+
+#[test]
+fn no_ops() {
+    let movie = ffmpeg::input("movie.mp4", kwargs! {});
+    let video = movie
+        .video()
+        .filter("null", kwargs! {})
+        .filter("scale", kwargs! {"w" => "iw", "h" => "ih"})
+        .filter("setpts", kwargs! {"expr" => "PTS"})
+        .filter("hflip", kwargs! {})
+        .filter("null", kwargs! {});
+    let audio = movie
+        .audio()
+        .filter("volume", kwargs! {"volume" => 2})
+        .filter("anull", kwargs! {});
+    let graph = optimize(ffmpeg::output([video, audio], "out.mkv", kwargs! {})).unwrap();
+    assert_eq!(graph.nodes.len(), 4);
+    assert_eq!(
+        graph.compile("ffmpeg", false).unwrap()[2..],
+        [
+            "-i",
+            "movie.mp4",
+            "-filter_complex",
+            "[0:v]hflip[s0];[0:a]volume=volume=2[s1]",
+            "-map",
+            "[s0]",
+            "-map",
+            "[s1]",
+            "out.mkv"
+        ]
+    );
+
+    // Mapped straight from the input, they would take every matching stream
+    // instead of the first one.
+    assert_eq!(
+        filters(
+            movie
+                .audio()
+                .filter("anull", kwargs! {})
+                .output("out.m4a", kwargs! {})
+        ),
+        "[0:a]anull[s0]"
+    );
+    assert_eq!(
+        filters(ffmpeg::filter(&movie, "null", kwargs! {}).output("out.mkv", kwargs! {})),
+        "[0]null[s0]"
+    );
+
+    // Anything with other options stays.
+    let scaled = movie.video().filter(
+        "scale",
+        kwargs! {"w" => "iw", "h" => "ih", "flags" => "lanczos"},
+    );
+    assert_eq!(
+        filters(scaled.output("out.mkv", kwargs! {})),
+        "[0:v]scale=w=iw:h=ih:flags=lanczos[s0]"
+    );
+}
+
+#[test]
+fn fused_scales() {
+    let video = ffmpeg::input("movie.mp4", kwargs! {}).video();
+    let scale = |stream: &ffmpeg_rs::node::FilterableStream, w: &str, h: &str| {
+        stream.filter("scale", kwargs! {"w" => w, "h" => h})
+    };
+    assert_eq!(
+        filters(scale(&scale(&video, "iw/2", "ih/2"), "640", "360").output("a.mp4", kwargs! {})),
+        "[0:v]scale=w=640:h=360[s0]"
+    );
+    assert_eq!(
+        filters(scale(&scale(&video, "1280", "720"), "iw/2", "ih/2").output("b.mp4", kwargs! {})),
+        "[0:v]scale=w=1280/2:h=720/2[s0]"
+    );
+    assert_eq!(
+        filters(
+            scale(
+                &scale(&scale(&video, "iw/3", "0"), "iw*2", "ih"),
+                "iw",
+                "ih/2"
+            )
+            .output("c.mp4", kwargs! {})
+        ),
+        "[0:v]scale=w=trunc(trunc(iw/3)*2):h=ih/2[s0]"
+    );
+    // Keeping the aspect ratio depends on the intermediate size.
+    assert_eq!(
+        filters(scale(&scale(&video, "1280", "720"), "640", "-2").output("d.mp4", kwargs! {})),
+        "[0:v]scale=w=1280:h=720[s0];[s0]scale=w=640:h=-2[s1]"
+    );
+}
+
+#[test]
+fn fused_setpts() {
+    let video = ffmpeg::input("movie.mp4", kwargs! {}).video();
+    let setpts = |stream: &ffmpeg_rs::node::FilterableStream, expr: &str| {
+        stream.filter("setpts", kwargs! {"expr" => expr})
+    };
+    assert_eq!(
+        filters(setpts(&setpts(&video, "PTS-STARTPTS"), "0.5*PTS").output("a.mp4", kwargs! {})),
+        "[0:v]setpts=expr=0.5*(PTS-STARTPTS)[s0]"
+    );
+    assert_eq!(
+        filters(setpts(&setpts(&video, "0.5*PTS"), "PTS-STARTPTS").output("b.mp4", kwargs! {})),
+        "[0:v]setpts=expr=0.5*PTS[s0];[s0]setpts=expr=PTS-STARTPTS[s1]"
+    );
+}
+
+#[test]
+fn dead_branches() {
+    let movie = ffmpeg::input("movie.mp4", kwargs! {});
+    let split = ffmpeg::filter_multi_output(movie.video(), "split", kwargs! {"outputs" => 3});
+    assert_eq!(
+        filters(
+            split
+                .stream("1", None)
+                .filter("hflip", kwargs! {})
+                .output("a.mp4", kwargs! {})
+        ),
+        "[0:v]hflip[s0]"
+    );
+    assert_eq!(
+        filters(vec![
+            split.stream("0", None).output("a.mp4", kwargs! {}),
+            split.stream("2", None).output("b.mp4", kwargs! {}),
+        ]),
+        "[0:v]split[s0][s1]"
+    );
+}
+
+#[test]
+fn custom_optimizer() {
+    let video = ffmpeg::input("movie.mp4", kwargs! {}).video();
+    let output = video
+        .filter("null", kwargs! {})
+        .filter("scale", kwargs! {"w" => 1280, "h" => 720})
+        .filter("scale", kwargs! {"w" => 640, "h" => 360})
+        .output("out.mp4", kwargs! {});
+    let graph = Graph::new(output).unwrap().global("loglevel", "error");
+
+    let optimized = Optimizer::new()
+        .pass(RemoveNoOps)
+        .run(graph.clone())
+        .unwrap();
+    assert_eq!(optimized.nodes.len(), 4);
+    assert_eq!(optimized.globals, graph.globals);
+    let optimized = Optimizer::new()
+        .pass(RemoveNoOps)
+        .pass(FuseScales)
+        .run(graph)
+        .unwrap();
+    assert_eq!(
+        optimized.compile("ffmpeg", false).unwrap()[2..9],
+        [
+            "-loglevel",
+            "error",
+            "-i",
+            "movie.mp4",
+            "-filter_complex",
+            "[0:v]scale=w=640:h=360[s0]",
+            "-map"
+        ]
+    );
+}
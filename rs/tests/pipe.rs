@@ -0,0 +1,234 @@
+mod common;
+
+use std::io;
+
+use ffmpeg_rs::pipe::{self, Frame, PixelFormat, RawAudio, RawVideo, SampleFormat};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn raw_graphs() {
+    let source = RawVideo::new(640, 360, PixelFormat::Rgb24).frame_rate("30000/1001");
+    let preview = RawVideo::new(320, 180, PixelFormat::Gray);
+    let output = preview
+        .output(&source.input().stream("", None).filter("hflip", kwargs! {}))
+        .stream("", None);
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -f rawvideo -pix_fmt rgb24 -s 640x360 -framerate 30000/1001 \
+         -i pipe:0 -filter_complex [0]hflip[s0] -map [s0] -f rawvideo -pix_fmt gray \
+         -s 320x180 pipe:1 -y"
+    );
+
+    let pcm = RawAudio {
+        sample_format: SampleFormat::S16le,
+        sample_rate: 48000,
+        channels: 2,
+    };
+    let movie = ffmpeg::input("movie.mp4", kwargs! {});
+    assert_eq!(
+        ffmpeg::get_args(pcm.output(&movie.audio()).stream("", None)).unwrap(),
+        [
+            "-i",
+            "movie.mp4",
+            "-map",
+            "0:a",
+            "-f",
+            "s16le",
+            "-ar",
+            "48000",
+            "-ac",
+            "2",
+            "pipe:1"
+        ]
+    );
+    assert_eq!(
+        ffmpeg::get_args(pcm.input().stream("", None).output("out.wav", kwargs! {})).unwrap(),
+        ["-f", "s16le", "-ar", "48000", "-ac", "2", "-i", "pipe:0", "out.wav"]
+    );
+}
+
+#[test]
+fn frame_sizes() {
+    assert_eq!(PixelFormat::Rgb24.frame_size(640, 360), 691_200);
+    assert_eq!(PixelFormat::Rgba.frame_size(2, 2), 16);
+    assert_eq!(PixelFormat::Yuv420p.frame_size(4, 4), 24);
+    assert_eq!(PixelFormat::Yuv420p.frame_size(3, 3), 17);
+
+    let format = RawVideo::new(2, 2, PixelFormat::Gray);
+    assert_eq!(Frame::new(&format, vec![0; 4]).unwrap().width, 2);
+    assert_eq!(
+        Frame::new(&format, vec![0; 3]).unwrap_err(),
+        "A 2x2 gray frame is 4 bytes, got 3"
+    );
+}
+
+#[test]
+fn shared_pipes() {
+    let first = pipe::from_reader(io::empty(), 0, kwargs! {"f" => "wav"}).unwrap();
+    let second = pipe::from_reader(io::empty(), 0, kwargs! {"f" => "wav"}).unwrap();
+    let output = vec![
+        first.stream("", None).output("first.flac", kwargs! {}),
+        second.stream("", None).output("second.flac", kwargs! {}),
+    ];
+    assert_eq!(
+        ffmpeg::get_args(output).unwrap_err().to_string(),
+        "Both input #0 and input #1 use stdin"
+    );
+
+    let piped = RawVideo::new(2, 2, PixelFormat::Gray).input();
+    let output = pipe::to_writer(
+        &piped.stream("", None),
+        io::sink(),
+        1,
+        kwargs! {"f" => "nut"},
+    )
+    .unwrap()
+    .stream("", None);
+    assert_eq!(
+        ffmpeg::get_args(output).unwrap()[8..],
+        ["-f", "nut", "pipe:1"]
+    );
+
+    assert_eq!(
+        pipe::from_reader(io::empty(), 1, kwargs! {}).err().unwrap(),
+        "An input cannot use stdout"
+    );
+    assert_eq!(
+        pipe::to_writer(&piped.stream("", None), io::sink(), 2, kwargs! {})
+            .err()
+            .unwrap(),
+        "An output cannot use stderr"
+    );
+}
+
+#[cfg(unix)]
+mod runner {
+    use std::io::{self, Cursor, Write};
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    use ffmpeg_rs::pipe::{self, Frame, PipeRunner, PixelFormat, RawVideo};
+    use ffmpeg_rs::{ffmpeg, kwargs};
+
+    use crate::common::fake_ffmpeg;
+
+    fn compile(path: &Path, format: &RawVideo) -> ffmpeg_rs::ffmpeg::CompiledCommand {
+        format
+            .output(&format.input().stream("", None))
+            .stream("", None)
+            .compile_command(path.to_str().unwrap(), true)
+            .unwrap()
+    }
+
+    #[test]
+    fn round_trip_larger_than_pipe_buffers() {
+        let fake = fake_ffmpeg("pipe", "cat");
+        // Every frame alone is larger than a pipe buffer, so writing everything
+        // before reading would deadlock.
+        let format = RawVideo::new(640, 360, PixelFormat::Rgb24);
+        let frames: Vec<Frame> = (0..8u8)
+            .map(|i| Frame::new(&format, vec![i; format.frame_size()]).unwrap())
+            .collect();
+        let command = compile(fake.path(), &format);
+
+        let mut received: Vec<Frame> = Vec::new();
+        PipeRunner::new(&command)
+            .write_frames(frames.clone())
+            .read_frames(&format, |frame| {
+                received.push(frame);
+                Ok(())
+            })
+            .run()
+            .unwrap();
+        assert_eq!(received, frames);
+    }
+
+    #[test]
+    fn errors() {
+        let format = RawVideo::new(4, 4, PixelFormat::Gray);
+
+        let truncated = fake_ffmpeg("pipe", "head -c 20");
+        let command = compile(truncated.path(), &format);
+        let error = PipeRunner::new(&command)
+            .write_frames(vec![Frame::new(&format, vec![1; 16]).unwrap(); 2])
+            .read_frames(&format, |_| Ok(()))
+            .run()
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Truncated frame: 4 of 16 bytes");
+
+        let failing = fake_ffmpeg("pipe", "echo 'pipe:0: Invalid data' >&2; exit 1");
+        let command = compile(failing.path(), &format);
+        let error = PipeRunner::new(&command)
+            .read_frames(&format, |_| Ok(()))
+            .run()
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("failed: exit status: 1: pipe:0: Invalid data"));
+    }
+
+    // A `Write` the test can still inspect after the runner took it.
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn attached_reader_and_writer() {
+        let fake = fake_ffmpeg("pipe", "cat");
+        let body: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+        let received = Shared::default();
+        let input =
+            pipe::from_reader(Cursor::new(body.clone()), 0, kwargs! {"f" => "nut"}).unwrap();
+        let command = pipe::to_writer(
+            &input.stream("", None),
+            received.clone(),
+            1,
+            kwargs! {"f" => "nut"},
+        )
+        .unwrap()
+        .stream("", None)
+        .compile_command(fake.cmd(), true)
+        .unwrap();
+
+        PipeRunner::new(&command).run().unwrap();
+        assert_eq!(*received.0.lock().unwrap(), body);
+        assert_eq!(
+            PipeRunner::new(&command).run().unwrap_err().to_string(),
+            "The stream attached to stdin was consumed by an earlier run"
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn extra_fds() {
+        // Copies fd 3 to fd 4 and stdin to stdout, so each pair must be wired
+        // to the right end.
+        let fake = fake_ffmpeg("pipe", "cat <&3 >&4 && cat");
+        let (side, main) = (Shared::default(), Shared::default());
+        let video = pipe::from_reader(Cursor::new(b"video".to_vec()), 0, kwargs! {}).unwrap();
+        let audio = pipe::from_reader(Cursor::new(b"audio".to_vec()), 3, kwargs! {}).unwrap();
+        let outputs = vec![
+            pipe::to_writer(&video.stream("", None), main.clone(), 1, kwargs! {})
+                .unwrap()
+                .stream("", None),
+            pipe::to_writer(&audio.stream("", None), side.clone(), 4, kwargs! {})
+                .unwrap()
+                .stream("", None),
+        ];
+        let command = ffmpeg::compile_command(outputs, fake.cmd(), true).unwrap();
+        assert!(command.args.contains(&"pipe:3".to_owned()));
+
+        PipeRunner::new(&command).run().unwrap();
+        assert_eq!(*main.0.lock().unwrap(), b"video");
+        assert_eq!(*side.0.lock().unwrap(), b"audio");
+    }
+}
@@ -0,0 +1,60 @@
+// Lowers the open file limit of the whole test process, so it runs on its own.
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::ffi::c_int;
+use std::io::{self, Cursor, Write};
+use std::sync::{Arc, Mutex};
+
+use ffmpeg_rs::pipe::{self, PipeRunner};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+use common::fake_ffmpeg;
+
+#[repr(C)]
+struct Rlimit {
+    current: u64,
+    max: u64,
+}
+
+extern "C" {
+    fn getrlimit(resource: c_int, limit: *mut Rlimit) -> c_int;
+    fn setrlimit(resource: c_int, limit: *const Rlimit) -> c_int;
+}
+
+const RLIMIT_NOFILE: c_int = 7;
+
+#[derive(Clone, Default)]
+struct Shared(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn extra_fds_under_low_limit() {
+    let mut limit = Rlimit { current: 0, max: 0 };
+    unsafe {
+        assert_eq!(getrlimit(RLIMIT_NOFILE, &mut limit), 0);
+        limit.current = 256.min(limit.max);
+        assert_eq!(setrlimit(RLIMIT_NOFILE, &limit), 0);
+    }
+
+    let fake = fake_ffmpeg("pipe-fd-limit", "cat <&3 >&4");
+    let received = Shared::default();
+    let input = pipe::from_reader(Cursor::new(b"audio".to_vec()), 3, kwargs! {}).unwrap();
+    let output = pipe::to_writer(&input.stream("", None), received.clone(), 4, kwargs! {})
+        .unwrap()
+        .stream("", None);
+    let command = ffmpeg::compile_command(output, fake.cmd(), true).unwrap();
+
+    PipeRunner::new(&command).run().unwrap();
+    assert_eq!(*received.0.lock().unwrap(), b"audio");
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use ffmpeg_rs::pipeline::Pipeline;
+
+use common::fixture;
+
+const EXPECTED: &str = "ffmpeg -hide_banner -loglevel error -ss 10 -i movie.mp4 -i logo.png \
+    -filter_complex [0:v][1]overlay=x=W-w-10:y=10[s0];[s0]split[s1][s2];[s2]scale=w=640:h=-2[s3] \
+    -map [s1] -map 0:a -c:v libx264 -crf 23 full.mp4 -map [s3] small.mp4";
+
+#[test]
+fn formats() {
+    let toml: Pipeline = toml::from_str(&fixture("pipeline/preview.toml")).unwrap();
+    let yaml: Pipeline = serde_yaml_ng::from_str(&fixture("pipeline/preview.yaml")).unwrap();
+    assert_eq!(toml, yaml);
+    // Options keep the order they are written in, not alphabetical order.
+    assert_eq!(toml.steps[0].args.0[0].0, "x");
+    assert_eq!(
+        toml.graph()
+            .unwrap()
+            .compile("ffmpeg", false)
+            .unwrap()
+            .join(" "),
+        EXPECTED
+    );
+}
+
+#[test]
+fn references() {
+    let pipeline = |outputs: &str| -> Pipeline {
+        serde_yaml_ng::from_str(&format!(
+            "inputs: [{{id: movie, file: movie.mp4}}]\n\
+             steps: [{{id: flipped, filter: hflip, inputs: [movie:v]}}]\n\
+             outputs: [{{file: out.mp4, streams: [{}]}}]",
+            outputs
+        ))
+        .unwrap()
+    };
+    assert_eq!(
+        pipeline("flipped, movie:a")
+            .graph()
+            .unwrap()
+            .compile("ffmpeg", true)
+            .unwrap()[4..],
+        [
+            "-filter_complex",
+            "[0:v]hflip[s0]",
+            "-map",
+            "[s0]",
+            "-map",
+            "0:a",
+            "out.mp4",
+            "-y"
+        ]
+    );
+    assert_eq!(
+        pipeline("flopped").outputs().err().unwrap().to_string(),
+        "Output `out.mp4`: Unknown stream `flopped`"
+    );
+    assert_eq!(
+        pipeline("movie.1").outputs().err().unwrap().to_string(),
+        "Output `out.mp4`: Input `movie` has no output `1`"
+    );
+    assert_eq!(
+        pipeline("movie:x").outputs().err().unwrap().to_string(),
+        "Output `out.mp4`: Invalid item: x"
+    );
+
+    let forward: Pipeline = serde_yaml_ng::from_str(
+        "inputs: [{id: movie, file: movie.mp4}]\n\
+         steps:\n\
+         - {id: first, filter: hflip, inputs: [second]}\n\
+         - {id: second, filter: vflip, inputs: [movie]}\n\
+         outputs: [{file: out.mp4, streams: [first]}]",
+    )
+    .unwrap();
+    assert_eq!(
+        forward.outputs().err().unwrap().to_string(),
+        "Step `first`: Unknown stream `second`"
+    );
+
+    let duplicate: Pipeline = serde_yaml_ng::from_str(
+        "inputs: [{id: movie, file: a.mp4}, {id: movie, file: b.mp4}]\n\
+         outputs: [{file: out.mp4, streams: [movie]}]",
+    )
+    .unwrap();
+    assert_eq!(
+        duplicate.outputs().err().unwrap().to_string(),
+        "Duplicate id `movie`"
+    );
+}
+
+#[test]
+fn cli() {
+    use std::process::Command;
+
+    let path = format!(
+        "{}/tests/fixtures/pipeline/preview.toml",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_ffmpeg-cmd"))
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let argv: Vec<&str> = EXPECTED.split(' ').collect();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        argv.join("\n") + "\n"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ffmpeg-cmd"))
+        .args(["--shell", "--ffmpeg", "/opt/ffmpeg/bin/ffmpeg", "-y", &path])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "/opt/ffmpeg/bin/ffmpeg -hide_banner -loglevel error -ss 10 -i movie.mp4 -i logo.png \
+         -filter_complex '[0:v][1]overlay=x=W-w-10:y=10[s0];[s0]split[s1][s2];[s2]scale=w=640:h=-2[s3]' \
+         -map '[s1]' -map 0:a -c:v libx264 -crf 23 full.mp4 -map '[s3]' small.mp4 -y\n"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ffmpeg-cmd"))
+        .arg("pipeline.ini")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("error: pipeline.ini: "));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ffmpeg-cmd"))
+        .arg("codegen")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "usage: ffmpeg-cmd codegen FILTERS_DUMP [HELP_DUMP...]\n"
+    );
+}
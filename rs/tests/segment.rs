@@ -0,0 +1,36 @@
+use ffmpeg_rs::segment::{SegmentListType, SegmentOutput};
+use ffmpeg_rs::time::{Duration, Timestamp};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn time_based_segments() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let output = SegmentOutput::new(&input, "out%03d.ts")
+        .arg("c", "copy")
+        .segment_time(Duration::from_millis(10_500))
+        .segment_format("mpegts")
+        .segment_list("out.csv")
+        .segment_list_type(SegmentListType::Csv)
+        .reset_timestamps(true)
+        .stream();
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i input.mp4 -f segment -c copy -segment_time 10.5 \
+         -segment_format mpegts -segment_list out.csv -segment_list_type csv \
+         -reset_timestamps 1 out%03d.ts -y"
+    );
+}
+
+#[test]
+fn strftime_segments() {
+    let input = ffmpeg::input("rtsp://camera/stream", kwargs! {});
+    let output = SegmentOutput::new(input.video(), "capture-%Y%m%d-%H%M%S.mkv")
+        .segment_times(&[Timestamp::from_secs(60), Timestamp::from_millis(150_250)])
+        .strftime(true)
+        .stream();
+    assert_eq!(
+        output.compile("ffmpeg", false).unwrap().join(" "),
+        "ffmpeg -hide_banner -i rtsp://camera/stream -map 0:v -f segment \
+         -segment_times 60,150.25 -strftime 1 capture-%Y%m%d-%H%M%S.mkv"
+    );
+}
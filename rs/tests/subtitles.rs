@@ -0,0 +1,128 @@
+use ffmpeg_rs::subtitles::{convert, BurnIn, SubtitleCodec, SubtitleTrack, SubtitledOutput};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn burn_in_escapes_paths() {
+    let input = ffmpeg::input("movie.mp4", kwargs! {});
+    let burnt = BurnIn::new(&input.video(), r"C:\subs\it's [final].srt")
+        .charenc("CP1252")
+        .force_style("FontName=Arial,FontSize=24")
+        .stream()
+        .unwrap();
+    assert_eq!(
+        ffmpeg::get_args(burnt.output("out.mp4", kwargs! {})).unwrap()[3],
+        r"[0:v]subtitles=filename=C\\:\\\\subs\\\\it\\\'s \[final\].srt:charenc=CP1252:force_style=FontName=Arial\,FontSize=24[s0]"
+    );
+
+    let ass = BurnIn::ass(&input.video(), "/tmp/a:b.ass")
+        .fonts_dir("fonts")
+        .original_size(1920, 1080)
+        .stream()
+        .unwrap();
+    assert_eq!(
+        ffmpeg::get_args(ass.output("out.mp4", kwargs! {})).unwrap()[3],
+        r"[0:v]ass=filename=/tmp/a\\:b.ass:fontsdir=fonts:original_size=1920x1080[s0]"
+    );
+
+    assert_eq!(
+        BurnIn::ass(&input.video(), "a.ass")
+            .stream_index(1)
+            .stream()
+            .err()
+            .unwrap(),
+        "`stream_index` is only supported by the `subtitles` filter"
+    );
+}
+
+#[test]
+fn soft_subtitles() {
+    let movie = ffmpeg::input("movie.mp4", kwargs! {});
+    let english = ffmpeg::input("en.srt", kwargs! {});
+    let german = ffmpeg::input("de.srt", kwargs! {});
+    let output = SubtitledOutput::new("out.mp4")
+        .map(&movie.video())
+        .map(&movie.audio())
+        .subtitle(
+            SubtitleTrack::new(&english.subtitle())
+                .codec(SubtitleCodec::MovText)
+                .language("eng")
+                .title("English")
+                .default(true),
+        )
+        .subtitle(
+            SubtitleTrack::new(&german.subtitle())
+                .codec(SubtitleCodec::MovText)
+                .language("ger")
+                .forced(true),
+        )
+        .arg("c:v", "copy")
+        .stream()
+        .unwrap();
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i movie.mp4 -i en.srt -i de.srt -map 0:v -map 0:a -map 1:s \
+         -map 2:s -c:v copy -c:s:0 mov_text -metadata:s:s:0 language=eng \
+         -metadata:s:s:0 title=English -disposition:s:0 default -c:s:1 mov_text \
+         -metadata:s:s:1 language=ger -disposition:s:1 forced out.mp4 -y"
+    );
+
+    // Tracks come after the subtitles mapped from the inputs.
+    let output = SubtitledOutput::new("out.mkv")
+        .map(&movie.video())
+        .map(&movie.subtitle())
+        .subtitle(
+            SubtitleTrack::new(&english.subtitle())
+                .codec(SubtitleCodec::Srt)
+                .language("eng"),
+        )
+        .stream()
+        .unwrap();
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i movie.mp4 -i en.srt -map 0:v -map 0:s -map 1:s -c:s:1 srt \
+         -metadata:s:s:1 language=eng out.mkv -y"
+    );
+    let whole_input = SubtitledOutput::new("out.mkv")
+        .map(&movie)
+        .subtitle(SubtitleTrack::new(&english.subtitle()));
+    assert!(whole_input.node().is_err());
+
+    let srt_in_mp4 = SubtitledOutput::new("out.MP4")
+        .map(&movie.video())
+        .subtitle(SubtitleTrack::new(&english.subtitle()).codec(SubtitleCodec::Srt));
+    assert_eq!(
+        srt_in_mp4.node().err().unwrap(),
+        "Subtitle track #0 cannot be muxed into out.MP4 as srt"
+    );
+    let srt_in_mkv = SubtitledOutput::new("out.mkv")
+        .map(&movie.video())
+        .subtitle(SubtitleTrack::new(&english.subtitle()).codec(SubtitleCodec::Srt));
+    assert!(srt_in_mkv.node().is_ok());
+}
+
+#[test]
+fn conversion() {
+    let subs = ffmpeg::input("subs.srt", kwargs! {"sub_charenc" => "latin1"});
+    assert_eq!(
+        ffmpeg::get_args(convert(&subs, "subs.vtt").unwrap()).unwrap(),
+        [
+            "-sub_charenc",
+            "latin1",
+            "-i",
+            "subs.srt",
+            "-map",
+            "0:s",
+            "-c:s",
+            "webvtt",
+            "subs.vtt"
+        ]
+    );
+    assert_eq!(
+        SubtitleCodec::from_filename("dir.v2/subs.ASS"),
+        Some(SubtitleCodec::Ass)
+    );
+    assert_eq!(
+        convert(&subs, "subs.txt").err().unwrap(),
+        "Unknown subtitle format of subs.txt"
+    );
+}
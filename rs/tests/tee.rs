@@ -0,0 +1,66 @@
+use ffmpeg_rs::tee::{TeeOnFail, TeeOutput, TeeSlave};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn slaves() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let scaled = input
+        .video()
+        .filter("scale", kwargs! {"w" => 1280, "h" => 720});
+    let output = TeeOutput::new([scaled, input.audio()])
+        .arg("c:v", "libx264")
+        .arg("c:a", "aac")
+        .slave(TeeSlave::new("archive.mkv"))
+        .slave(TeeSlave::new("video.ts").format("mpegts").select("v"))
+        .slave(
+            TeeSlave::new("rtmp://live.example.com/app/key")
+                .format("flv")
+                .onfail(TeeOnFail::Ignore)
+                .bsfs("a", "aac_adtstoasc"),
+        )
+        .stream()
+        .unwrap();
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap(),
+        [
+            "ffmpeg",
+            "-hide_banner",
+            "-i",
+            "input.mp4",
+            "-filter_complex",
+            "[0:v]scale=w=1280:h=720[s0]",
+            "-map",
+            "[s0]",
+            "-map",
+            "0:a",
+            "-f",
+            "tee",
+            "-c:v",
+            "libx264",
+            "-c:a",
+            "aac",
+            "archive.mkv|[f=mpegts:select=v]video.ts|\
+             [f=flv:onfail=ignore:bsfs/a=aac_adtstoasc]rtmp://live.example.com/app/key",
+            "-y",
+        ]
+    );
+}
+
+#[test]
+fn escaping() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let output = TeeOutput::new(&input)
+        .slave(TeeSlave::new("[draft] a|b.mkv"))
+        .slave(TeeSlave::new("c:\\out's.ts").arg("select", "v:0,a"))
+        .slave(TeeSlave::new("[live].flv"))
+        .slave(TeeSlave::new(" [x].ts "))
+        .node()
+        .unwrap();
+    let args = ffmpeg::get_args(output.stream("", None)).unwrap();
+    assert_eq!(
+        args.last().unwrap(),
+        "[][draft] a\\|b.mkv|[select=v\\\\:0,a]c:\\\\out\\'s.ts|[][live].flv|\\ [x].ts\\ "
+    );
+
+    assert!(TeeOutput::new(&input).stream().is_err());
+}
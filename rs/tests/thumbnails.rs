@@ -0,0 +1,117 @@
+use ffmpeg_rs::image_sequence::ImagePattern;
+use ffmpeg_rs::thumbnails::thumbnails;
+use ffmpeg_rs::time::Duration;
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn separate_thumbnails() {
+    let input = ffmpeg::input("movie.mp4", kwargs! {});
+    let every_minute = thumbnails(&input, Duration::from_secs(600), 10).width(320);
+    assert_eq!(every_minute.interval().unwrap(), Duration::from_secs(60));
+    assert_eq!(every_minute.count().unwrap(), 10);
+    let output = every_minute
+        .output(&ImagePattern::numbered("thumbs", "", 3, "jpg"))
+        .unwrap()
+        .stream();
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i movie.mp4 -filter_complex \
+         [0:v]select=expr=isnan(prev_selected_t)+gte(t-prev_selected_t\\,60)[s0];\
+         [s0]scale=w=320:h=-2[s1] -map [s1] -frames:v 10 -fps_mode vfr thumbs/%03d.jpg -y"
+    );
+
+    let representative =
+        thumbnails(&input, Duration::from_secs(95), Duration::from_secs(10)).representative(100);
+    assert_eq!(representative.count().unwrap(), 10);
+    assert_eq!(
+        ffmpeg::get_args(
+            representative
+                .stream()
+                .unwrap()
+                .output("%d.png", kwargs! {})
+        )
+        .unwrap()[3],
+        "[0:v]thumbnail=n=100[s0];\
+         [s0]select=expr=isnan(prev_selected_t)+gte(t-prev_selected_t\\,10)[s1]"
+    );
+
+    assert!(thumbnails(&input, Duration::from_secs(10), 0)
+        .count()
+        .is_err());
+    assert!(thumbnails(&input, Duration::from_secs(10), Duration::ZERO)
+        .stream()
+        .is_err());
+}
+
+#[test]
+fn contact_sheet() {
+    let input = ffmpeg::input("movie.mp4", kwargs! {});
+    let sheet = thumbnails(&input, Duration::from_secs(120), 12)
+        .contact_sheet(4, 3, 1280)
+        .unwrap();
+    assert_eq!(
+        ffmpeg::get_args(sheet.output("sheet.png", kwargs! {"frames:v" => 1})).unwrap(),
+        [
+            "-i",
+            "movie.mp4",
+            "-filter_complex",
+            "[0:v]select=expr=isnan(prev_selected_t)+gte(t-prev_selected_t\\,10)[s0];\
+             [s0]scale=w=320:h=-2[s1];[s1]tile=layout=4x3[s2]",
+            "-map",
+            "[s2]",
+            "-frames:v",
+            "1",
+            "sheet.png"
+        ]
+    );
+
+    let thumbs = thumbnails(&input, Duration::from_secs(120), 12);
+    assert!(thumbs.contact_sheet(0, 3, 1280).is_err());
+    assert_eq!(
+        thumbs.contact_sheet(4, 3, 2).err().unwrap(),
+        "Contact sheet of 4 columns must be at least 4 pixels wide"
+    );
+}
+
+#[test]
+fn sprite_sheet_and_webvtt() {
+    let input = ffmpeg::input("movie.mp4", kwargs! {});
+    let sprites = thumbnails(
+        &input,
+        Duration::from_millis(12_500),
+        Duration::from_secs(5),
+    )
+    .sprite_sheet(2, 1, 160, 90)
+    .unwrap();
+    assert_eq!(sprites.sheets(), 2);
+
+    let pattern = ImagePattern::numbered("previews", "sprite_", 3, "jpg");
+    let output = sprites.output(&pattern).stream();
+    assert_eq!(
+        output.compile("ffmpeg", true).unwrap().join(" "),
+        "ffmpeg -hide_banner -i movie.mp4 -filter_complex \
+         [0:v]select=expr=isnan(prev_selected_t)+gte(t-prev_selected_t\\,5)[s0];\
+         [s0]scale=w=160:h=90:force_original_aspect_ratio=decrease[s1];\
+         [s1]pad=w=160:h=90:x=(ow-iw)/2:y=(oh-ih)/2[s2];[s2]tile=layout=2x1[s3] \
+         -map [s3] -frames:v 2 -fps_mode vfr previews/sprite_%03d.jpg -y"
+    );
+
+    assert_eq!(
+        sprites
+            .webvtt(&pattern, "https://cdn.example.com/previews/")
+            .unwrap(),
+        "WEBVTT\n\
+         \n\
+         00:00:00.000 --> 00:00:05.000\n\
+         https://cdn.example.com/previews/sprite_001.jpg#xywh=0,0,160,90\n\
+         \n\
+         00:00:05.000 --> 00:00:10.000\n\
+         https://cdn.example.com/previews/sprite_001.jpg#xywh=160,0,160,90\n\
+         \n\
+         00:00:10.000 --> 00:00:12.500\n\
+         https://cdn.example.com/previews/sprite_002.jpg#xywh=0,0,160,90\n"
+    );
+    assert!(sprites
+        .webvtt(&ImagePattern::glob("previews", "*.jpg"), "")
+        .is_err());
+}
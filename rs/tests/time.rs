@@ -0,0 +1,102 @@
+use ffmpeg_rs::time::{Duration, Timestamp};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn parse() {
+    let parse = |text: &str| text.parse::<Timestamp>().unwrap().as_micros();
+    assert_eq!(parse("01:29"), 89_000_000);
+    assert_eq!(parse("1:02:03.5"), 3_723_500_000);
+    assert_eq!(parse("-00:00:01.25"), -1_250_000);
+    assert_eq!(parse("89"), 89_000_000);
+    assert_eq!(parse("89.5s"), 89_500_000);
+    assert_eq!(parse("1500ms"), 1_500_000);
+    assert_eq!(parse("1.5ms"), 1_500);
+    assert_eq!(parse("250us"), 250);
+    assert_eq!(parse("0.1234567"), 123_456);
+    assert_eq!(parse("100:00:00"), 360_000_000_000);
+
+    for invalid in [
+        "", "-", "1:60", "1:2:3:4", "abc", "1.2.3", "12m", "1:-2", "0.5x",
+    ] {
+        assert!(invalid.parse::<Duration>().is_err(), "{:?}", invalid);
+    }
+}
+
+#[test]
+fn format() {
+    let position = Timestamp::from_micros(3_723_500_000);
+    assert_eq!(position.to_string(), "01:02:03.500");
+    assert_eq!(position.format_seconds(), "3723.5");
+    assert_eq!(position.format_micros(), "3723500000us");
+    assert_eq!(
+        Duration::from_micros(1_000_001).to_string(),
+        "00:00:01.000001"
+    );
+    assert_eq!(Duration::from_secs(-90).to_string(), "-00:01:30.000");
+    assert_eq!(Duration::from_secs(-90).format_seconds(), "-90");
+    assert_eq!(Duration::from_secs_f64(0.25).format_seconds(), "0.25");
+
+    for text in ["01:02:03.500", "00:00:01.000001", "-00:01:30.000"] {
+        assert_eq!(text.parse::<Timestamp>().unwrap().to_string(), text);
+    }
+}
+
+#[test]
+fn arithmetic() {
+    let start: Timestamp = "01:29".parse().unwrap();
+    let end = start + Duration::from_secs(10);
+    assert_eq!(end.to_string(), "00:01:39.000");
+    assert_eq!(end - start, Duration::from_secs(10));
+    assert_eq!(
+        end - Duration::from_millis(500),
+        Timestamp::from_millis(98_500)
+    );
+    assert_eq!(
+        Duration::from_millis(250) * 4 - Duration::from(std::time::Duration::from_millis(500)),
+        Duration::from_millis(500)
+    );
+    assert!(start < end);
+}
+
+#[test]
+fn seek_and_trim() {
+    let start = Timestamp::from_secs(89);
+    let input = ffmpeg::input_node("input.mp4", kwargs! {})
+        .seek(start)
+        .duration(Duration::from_secs(30))
+        .stream("", None);
+    let video = input.video().trim(
+        Timestamp::from_millis(1500),
+        start - Duration::from_secs(60),
+    );
+    let audio = input
+        .audio()
+        .trim(Timestamp::ZERO, Timestamp::from_secs(29));
+    let output = ffmpeg::output_node([video, audio], "output.mp4", kwargs! {})
+        .seek(Timestamp::from_secs(1))
+        .stream("", None);
+    assert_eq!(
+        output.compile("ffmpeg", false).unwrap().join(" "),
+        "ffmpeg -hide_banner -ss 00:01:29.000 -t 00:00:30.000 -i input.mp4 \
+         -filter_complex [0:v]trim=start=1.5:end=29[s0];[0:a]atrim=start=0:end=29[s1] \
+         -map [s0] -map [s1] -ss 00:00:01.000 output.mp4"
+    );
+
+    // Filter outputs have no selector, so audio ones are trimmed with `atrim`.
+    let music = ffmpeg::input("music.mp3", kwargs! {});
+    let mixed = ffmpeg::filter([input.audio(), music], "amix", kwargs! {})
+        .atrim(Timestamp::ZERO, Timestamp::from_secs(10));
+    assert_eq!(
+        ffmpeg::get_args(mixed.output("mix.m4a", kwargs! {})).unwrap()[9],
+        "[0:a][1]amix[s0];[s0]atrim=start=0:end=10[s1]"
+    );
+
+    // Seeking again replaces the option instead of adding a second `-ss`.
+    let input = ffmpeg::input_node("input.mp4", kwargs! {"ss" => "5"})
+        .to(Timestamp::from_secs(20))
+        .seek(Timestamp::from_secs(10));
+    assert_eq!(
+        input.node().kwargs(),
+        &kwargs! {"ss" => "00:00:10.000", "filename" => "input.mp4", "to" => "00:00:20.000"}
+    );
+}
@@ -0,0 +1,124 @@
+use ffmpeg_rs::ffmpeg;
+use ffmpeg_rs::kwargs;
+use ffmpeg_rs::time::{Duration, Timestamp};
+use ffmpeg_rs::timeline::{Clip, Ducking, Timeline, TimelineFormat};
+
+const FORMAT: TimelineFormat = TimelineFormat {
+    width: 1280,
+    height: 720,
+    frame_rate: 25,
+    sample_rate: 48000,
+};
+
+#[test]
+fn cuts_gaps_and_crossfades() {
+    let interview = ffmpeg::input("interview.mp4", kwargs! {});
+    let broll = ffmpeg::input("broll.mp4", kwargs! {});
+    let program = Timeline::new(FORMAT)
+        .clip(Clip::new(
+            &interview,
+            Timestamp::from_secs(12),
+            Timestamp::from_secs(20),
+        ))
+        .gap(Duration::from_secs(2))
+        .crossfade(Duration::from_millis(500))
+        .clip(Clip::new(&broll, Timestamp::ZERO, Timestamp::from_secs(4)))
+        .build()
+        .unwrap();
+    // 8s + 2s, then 4s overlapping by 0.5s.
+    assert_eq!(program.duration, Duration::from_millis(13_500));
+
+    let output = ffmpeg::output([program.video, program.audio], "program.mp4", kwargs! {});
+    let args = ffmpeg::get_args(output).unwrap();
+    let filters: Vec<&str> = args[5].split(';').collect();
+    assert_eq!(
+        filters,
+        [
+            "[0:v]trim=start=12:end=20[s0]",
+            "[s0]setpts=expr=PTS-STARTPTS[s1]",
+            "[0:a]atrim=start=12:end=20[s2]",
+            "[s2]asetpts=expr=PTS-STARTPTS[s3]",
+            "color=c=black:s=1280x720:r=25:d=2[s4]",
+            "anullsrc=r=48000:cl=stereo:d=2[s5]",
+            "[s1][s3][s4][s5]concat=n=2:v=1:a=1[s6][s7]",
+            "[1:v]trim=start=0:end=4[s8]",
+            "[s8]setpts=expr=PTS-STARTPTS[s9]",
+            "[s6][s9]xfade=transition=fade:duration=0.5:offset=9.5[s10]",
+            "[1:a]atrim=start=0:end=4[s11]",
+            "[s11]asetpts=expr=PTS-STARTPTS[s12]",
+            "[s7][s12]acrossfade=d=0.5[s13]",
+        ]
+    );
+}
+
+#[test]
+fn ducked_music() {
+    let interview = ffmpeg::input("interview.mp4", kwargs! {});
+    let music = ffmpeg::input("music.mp3", kwargs! {});
+    let program = Timeline::new(FORMAT)
+        .clip(Clip::new(
+            &interview,
+            Timestamp::from_secs(1),
+            Timestamp::from_secs(31),
+        ))
+        .music(&music.audio(), Ducking::default())
+        .build()
+        .unwrap();
+    let args = ffmpeg::get_args(ffmpeg::output(program.audio, "program.m4a", kwargs! {})).unwrap();
+    let filters: Vec<&str> = args[5].split(';').collect();
+    assert_eq!(
+        filters,
+        [
+            "[0:a]atrim=start=1:end=31[s0]",
+            "[s0]asetpts=expr=PTS-STARTPTS[s1]",
+            "[s1]asplit[s2][s3]",
+            "[1:a]atrim=end=30[s4]",
+            "[s4][s3]sidechaincompress=threshold=0.05:ratio=8:attack=20:release=400[s5]",
+            "[s2][s5]amix=inputs=2:duration=first:normalize=0[s6]",
+        ]
+    );
+}
+
+#[test]
+fn invalid_timelines() {
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let clip = |start, end| {
+        Clip::new(
+            &input,
+            Timestamp::from_secs(start),
+            Timestamp::from_secs(end),
+        )
+    };
+    let error = |timeline: Timeline| timeline.build().err().unwrap();
+
+    assert_eq!(error(Timeline::new(FORMAT)), "Timeline has no items");
+    assert_eq!(
+        error(
+            Timeline::new(FORMAT)
+                .crossfade(Duration::from_secs(1))
+                .clip(clip(0, 5))
+        ),
+        "Transition before the first item"
+    );
+    assert_eq!(
+        error(
+            Timeline::new(FORMAT)
+                .clip(clip(0, 5))
+                .crossfade(Duration::from_secs(1))
+        ),
+        "Transition after the last item"
+    );
+    assert_eq!(
+        error(Timeline::new(FORMAT).clip(clip(5, 5))),
+        "Clip #0 ends before it starts"
+    );
+    assert_eq!(
+        error(
+            Timeline::new(FORMAT)
+                .clip(clip(0, 5))
+                .crossfade(Duration::from_secs(3))
+                .clip(clip(10, 12))
+        ),
+        "Crossfade of 00:00:03.000 into item #1 is longer than the items it joins"
+    );
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use ffmpeg_rs::encoder::{Aac, X264};
+use ffmpeg_rs::twopass::two_pass;
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+#[test]
+fn commands() {
+    let input = ffmpeg::input("input.mov", kwargs! {});
+    let scaled = input
+        .video()
+        .filter("scale", kwargs! {"w" => 1280, "h" => -2});
+    let output = ffmpeg::output_node(
+        [scaled, input.audio()],
+        "output.mp4",
+        kwargs! {"f" => "mp4"},
+    )
+    .encoder(
+        "v",
+        X264 {
+            bitrate: Some(3_000_000),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+    .encoder(
+        "a",
+        Aac {
+            bitrate: Some(128_000),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+    .stream("", None);
+
+    let passes = two_pass(&output, "ffmpeg", true, "/tmp/delivery").unwrap();
+    let shared = "ffmpeg -hide_banner -i input.mov -filter_complex [0:v]scale=w=1280:h=-2[s0] \
+                  -map [s0] -map 0:a";
+    assert_eq!(
+        passes.first_pass.join(" "),
+        format!(
+            "{} -f null -c:v libx264 -b:v 3000k -c:a aac -b:a 128k -pass 1 \
+             -passlogfile /tmp/delivery -an /dev/null -y",
+            shared
+        )
+    );
+    assert_eq!(
+        passes.second_pass.join(" "),
+        format!(
+            "{} -f mp4 -c:v libx264 -b:v 3000k -c:a aac -b:a 128k -pass 2 \
+             -passlogfile /tmp/delivery output.mp4 -y",
+            shared
+        )
+    );
+
+    // Only the second pass writes a file the caller may want kept.
+    let passes = two_pass(&output, "ffmpeg", false, "/tmp/delivery").unwrap();
+    assert_eq!(passes.first_pass.last().unwrap(), "-y");
+    assert_eq!(passes.second_pass.last().unwrap(), "output.mp4");
+}
+
+#[test]
+fn single_output_only() {
+    let input = ffmpeg::input("input.mov", kwargs! {});
+    let first = input.output("a.mp4", kwargs! {});
+    let second = input.output("b.mp4", kwargs! {});
+    assert_eq!(
+        two_pass([first, second], "ffmpeg", true, "x")
+            .unwrap_err()
+            .to_string(),
+        "Two-pass encoding needs exactly one output, got 2"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn run_removes_passlog_files() {
+    use std::fs;
+
+    // Pass 1 writes the statistics, pass 2 fails unless it finds them.
+    let fake = common::fake_ffmpeg(
+        "twopass",
+        "while [ $# -gt 0 ]; do\n\
+        case \"$1\" in\n\
+        -pass) pass=$2 ;;\n\
+        -passlogfile) log=$2 ;;\n\
+        esac\n\
+        shift\n\
+        done\n\
+        if [ \"$pass\" = 1 ]; then touch \"$log-0.log\" \"$log-0.log.mbtree\"; exit 0; fi\n\
+        test -f \"$log-0.log.mbtree\"",
+    );
+    let dir = fake.dir();
+    fs::write(dir.join("delivery-notes.txt"), "").unwrap();
+
+    let output = ffmpeg::input("input.mov", kwargs! {}).output("output.mp4", kwargs! {});
+    let passes = two_pass(&output, fake.cmd(), true, dir.join("delivery")).unwrap();
+    passes.run().unwrap();
+
+    let mut remaining: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    remaining.sort();
+    assert_eq!(remaining, ["delivery-notes.txt", "ffmpeg"]);
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use ffmpeg_rs::validate::{DiagnosticKind, ValidationError};
+use ffmpeg_rs::{ffmpeg, kwargs};
+
+use common::fixture_catalog;
+
+#[test]
+fn valid_graph() {
+    let catalog = fixture_catalog();
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let logo = ffmpeg::input("logo.png", kwargs! {});
+    let scaled = input.video().filter(
+        "scale",
+        kwargs! {"w" => "1280", "h" => "-2", "eval" => "frame"},
+    );
+    let overlaid = ffmpeg::filter(
+        [scaled, logo],
+        "overlay",
+        kwargs! {"x" => "W-w-10", "format" => "5", "shortest" => "1", "enable" => "gte(t,2)"},
+    );
+    let output = overlaid.output("output.mp4", kwargs! {});
+    assert!(ffmpeg::validate(&output, &catalog).unwrap().is_empty());
+    assert_eq!(
+        ffmpeg::compile_validated(&output, "ffmpeg", true, &catalog).unwrap(),
+        ffmpeg::compile(&output, "ffmpeg", true).unwrap()
+    );
+}
+
+#[test]
+fn unknown_filter_and_options() {
+    let catalog = fixture_catalog();
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let output = input
+        .filter("hflip", kwargs! {"x" => "10"})
+        .filter("zscale", kwargs! {})
+        .output("output.mp4", kwargs! {});
+
+    let diagnostics = ffmpeg::validate(&output, &catalog).unwrap();
+    let kinds: Vec<DiagnosticKind> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [DiagnosticKind::UnknownOption, DiagnosticKind::UnknownFilter]
+    );
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "filter #0 `hflip`: unknown option `x`"
+    );
+    assert_eq!(diagnostics[1].filter_index, 1);
+    assert_eq!(diagnostics[1].node.name(), "zscale");
+}
+
+#[test]
+fn wrong_arity() {
+    let catalog = fixture_catalog();
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let output = input
+        .filter("overlay", kwargs! {})
+        .output("output.mp4", kwargs! {});
+
+    let diagnostics = ffmpeg::validate(&output, &catalog).unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::InputCount);
+    assert_eq!(diagnostics[0].message, "expected 2 inputs, got 1");
+
+    let split = ffmpeg::filter_multi_output(&input, "hflip", kwargs! {});
+    let left = split.stream("0", None).output("left.mp4", kwargs! {});
+    let right = split.stream("1", None).output("right.mp4", kwargs! {});
+    let diagnostics = ffmpeg::validate([left, right], &catalog).unwrap();
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::OutputCount);
+}
+
+#[test]
+fn wrong_media_type() {
+    let catalog = fixture_catalog();
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let output = input
+        .audio()
+        .filter("hflip", kwargs! {})
+        .output("output.mp4", kwargs! {});
+
+    let diagnostics = ffmpeg::validate(&output, &catalog).unwrap();
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::MediaType);
+    assert_eq!(
+        diagnostics[0].message,
+        "input #0 `default` expects Video but is connected to Audio"
+    );
+
+    let output = input
+        .subtitle()
+        .filter("hflip", kwargs! {})
+        .output("output.mp4", kwargs! {});
+    let diagnostics = ffmpeg::validate(&output, &catalog).unwrap();
+    assert_eq!(
+        diagnostics[0].message,
+        "input #0 `default` expects Video but is connected to a subtitle stream"
+    );
+}
+
+#[test]
+fn invalid_values() {
+    let catalog = fixture_catalog();
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let output = input
+        .audio()
+        .filter("volume", kwargs! {"precision" => "triple", "replaygain_preamp" => "20", "replaygain_noclip" => "maybe"})
+        .filter("amix", kwargs! {"inputs" => "2*2", "normalize" => "false"})
+        .output("output.mp4", kwargs! {});
+
+    let diagnostics = ffmpeg::validate(&output, &catalog).unwrap();
+    let kinds: Vec<DiagnosticKind> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            DiagnosticKind::InvalidValue,
+            DiagnosticKind::OutOfRange,
+            DiagnosticKind::InvalidValue
+        ]
+    );
+    assert_eq!(
+        diagnostics[1].message,
+        "value 20 for option `replaygain_preamp` is out of range [-15, 15]"
+    );
+
+    let err = ffmpeg::compile_validated(&output, "ffmpeg", true, &fixture_catalog()).unwrap_err();
+    let err = err.downcast::<ValidationError>().unwrap();
+    assert_eq!(err.diagnostics.len(), 3);
+}
+
+#[test]
+fn si_suffixed_numbers() {
+    let catalog = fixture_catalog();
+    let input = ffmpeg::input("input.mp4", kwargs! {});
+    let amix = |kwargs| {
+        let output = ffmpeg::filter([input.audio(), input.audio()], "amix", kwargs)
+            .output("output.mp4", kwargs! {});
+        let diagnostics = ffmpeg::validate(&output, &catalog).unwrap();
+        diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.kind)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        amix(kwargs! {"inputs" => "2", "dropout_transition" => "1.5k"}),
+        []
+    );
+    assert_eq!(
+        amix(kwargs! {"inputs" => "1Ki", "dropout_transition" => "500m"}),
+        []
+    );
+    assert_eq!(amix(kwargs! {"inputs" => "2KiB"}), []);
+    assert_eq!(
+        amix(kwargs! {"inputs" => "64k"}),
+        [DiagnosticKind::OutOfRange]
+    );
+    assert_eq!(
+        amix(kwargs! {"inputs" => "2q"}),
+        [DiagnosticKind::InvalidValue]
+    );
+    assert_eq!(
+        amix(kwargs! {"inputs" => "2mi"}),
+        [DiagnosticKind::InvalidValue]
+    );
+}